@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(McBuf, Debug, Clone, Default, Eq, PartialEq)]
+/// This is serialized with serde in the same format that Mojang's API uses.
+#[derive(McBuf, Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SerializableGameProfile", into = "SerializableGameProfile")]
 pub struct GameProfile {
     pub uuid: Uuid,
     pub name: String,
@@ -40,7 +42,7 @@ impl From<SerializableGameProfile> for GameProfile {
     }
 }
 
-#[derive(McBuf, Debug, Clone, Eq, PartialEq)]
+#[derive(McBuf, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ProfilePropertyValue {
     pub value: String,
     pub signature: Option<String>,
@@ -95,9 +97,8 @@ mod tests {
                 }
             ]
         }"#;
-        let profile = GameProfile::from(
-            serde_json::from_str::<SerializableProfilePropertyValue>(json).unwrap(),
-        );
+        let profile =
+            GameProfile::from(serde_json::from_str::<SerializableGameProfile>(json).unwrap());
         assert_eq!(
             profile,
             GameProfile {
@@ -106,9 +107,9 @@ mod tests {
                 properties: {
                     let mut map = HashMap::new();
                     map.insert(
-                        "asdf".to_string(),
+                        "qwer".to_string(),
                         ProfilePropertyValue {
-                            value: "qwer".to_string(),
+                            value: "asdf".to_string(),
                             signature: Some("zxcv".to_string()),
                         },
                     );
@@ -1,6 +1,7 @@
 mod auth;
 mod cache;
 pub mod game_profile;
+pub mod offline;
 pub mod sessionserver;
 
 pub use auth::*;
@@ -0,0 +1,26 @@
+//! Things for offline-mode servers.
+
+use md5::{Digest, Md5};
+use uuid::Uuid;
+
+/// Generate the UUID that offline-mode servers give to a player with this
+/// username. This is the same as Java's
+/// `UUID.nameUUIDFromBytes("OfflinePlayer:" + username)`.
+pub fn generate_uuid(username: &str) -> Uuid {
+    let mut hasher = Md5::new();
+    hasher.update(format!("OfflinePlayer:{username}").as_bytes());
+    uuid::Builder::from_md5_bytes(hasher.finalize().into()).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_uuid() {
+        assert_eq!(
+            generate_uuid("Notch"),
+            Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+        );
+    }
+}
@@ -2,6 +2,12 @@ pub struct BlockBehavior {
     pub has_collision: bool,
     pub friction: f32,
     pub jump_factor: f32,
+    /// How hard the block is to break. Unbreakable blocks like bedrock have
+    /// -1.
+    pub destroy_time: f32,
+    /// Whether the block only drops anything when it's broken with the right
+    /// tool, like stone with a pickaxe.
+    pub requires_correct_tool_for_drops: bool,
 }
 
 impl Default for BlockBehavior {
@@ -10,6 +16,8 @@ impl Default for BlockBehavior {
             has_collision: true,
             friction: 0.6,
             jump_factor: 1.0,
+            destroy_time: 0.,
+            requires_correct_tool_for_drops: false,
         }
     }
 }
@@ -32,4 +40,16 @@ impl BlockBehavior {
         self.jump_factor = jump_factor;
         self
     }
+
+    #[inline]
+    pub fn destroy_time(mut self, destroy_time: f32) -> Self {
+        self.destroy_time = destroy_time;
+        self
+    }
+
+    #[inline]
+    pub fn requires_correct_tool_for_drops(mut self) -> Self {
+        self.requires_correct_tool_for_drops = true;
+        self
+    }
 }
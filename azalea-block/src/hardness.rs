@@ -0,0 +1,65 @@
+//! How long blocks take to break.
+
+use crate::{Block, BlockState};
+
+impl BlockState {
+    /// How hard the block is to break, which vanilla calls its destroy time.
+    /// Unbreakable blocks like bedrock return -1.
+    pub fn destroy_time(&self) -> f32 {
+        Box::<dyn Block>::from(*self).behavior().destroy_time
+    }
+
+    /// Whether the block only drops anything when it's broken with the right
+    /// tool, like stone with a pickaxe. Blocks that need it are also slower to
+    /// break without the tool.
+    pub fn requires_correct_tool_for_drops(&self) -> bool {
+        Box::<dyn Block>::from(*self)
+            .behavior()
+            .requires_correct_tool_for_drops
+    }
+
+    /// Whether the block can't be broken in survival mode.
+    pub fn is_unbreakable(&self) -> bool {
+        self.destroy_time() < 0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OakSlabBlock, PetrifiedOakSlabBlock, StoneBrickSlabBlock, StoneBrickStairsBlock};
+
+    #[test]
+    fn test_destroy_time() {
+        assert_eq!(BlockState::Stone.destroy_time(), 1.5);
+        assert_eq!(BlockState::Bedrock.destroy_time(), -1.);
+        assert_eq!(BlockState::RedWool.destroy_time(), 0.8);
+        assert_eq!(BlockState::Glowstone.destroy_time(), 0.3);
+        assert_eq!(BlockState::Dandelion.destroy_time(), 0.);
+        assert_eq!(BlockState::Air.destroy_time(), 0.);
+        assert_eq!(
+            BlockState::from(StoneBrickStairsBlock::default()).destroy_time(),
+            1.5
+        );
+        assert_eq!(
+            BlockState::from(StoneBrickSlabBlock::default()).destroy_time(),
+            2.
+        );
+        assert!(BlockState::Bedrock.is_unbreakable());
+    }
+
+    #[test]
+    fn test_requires_correct_tool_for_drops() {
+        assert!(BlockState::Stone.requires_correct_tool_for_drops());
+        assert!(BlockState::PolishedAndesite.requires_correct_tool_for_drops());
+        assert!(BlockState::DeepslateIronOre.requires_correct_tool_for_drops());
+        assert!(BlockState::WaxedOxidizedCopper.requires_correct_tool_for_drops());
+        assert!(
+            BlockState::from(PetrifiedOakSlabBlock::default()).requires_correct_tool_for_drops()
+        );
+        assert!(!BlockState::OakPlanks.requires_correct_tool_for_drops());
+        assert!(!BlockState::from(OakSlabBlock::default()).requires_correct_tool_for_drops());
+        assert!(!BlockState::Dirt.requires_correct_tool_for_drops());
+        assert!(!BlockState::InfestedStoneBricks.requires_correct_tool_for_drops());
+    }
+}
@@ -1,7 +1,11 @@
 mod behavior;
 mod blocks;
+mod hardness;
 
-use azalea_buf::{BufReadError, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable};
+use azalea_buf::{
+    remap::{remap, server_id, Remappable, RemappedId},
+    BufReadError, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
+};
 pub use behavior::BlockBehavior;
 pub use blocks::*;
 use std::{
@@ -23,6 +27,46 @@ impl BlockState {
     pub fn is_valid_state(state_id: u32) -> bool {
         state_id <= Self::max_state()
     }
+
+    /// Whether the block is air, including cave air and void air.
+    pub fn is_air(&self) -> bool {
+        matches!(
+            self,
+            BlockState::Air | BlockState::CaveAir | BlockState::VoidAir
+        )
+    }
+
+    /// Whether placing a block here replaces it, like air, fluids, and tall
+    /// grass.
+    ///
+    /// Snow with one layer is replaceable too, but we don't check the number
+    /// of layers, so snow never counts.
+    pub fn is_replaceable(&self) -> bool {
+        self.is_air()
+            || matches!(
+                Box::<dyn Block>::from(*self).id(),
+                "water"
+                    | "lava"
+                    | "bubble_column"
+                    | "fire"
+                    | "soul_fire"
+                    | "grass"
+                    | "fern"
+                    | "dead_bush"
+                    | "tall_grass"
+                    | "large_fern"
+                    | "vine"
+                    | "glow_lichen"
+                    | "hanging_roots"
+                    | "crimson_roots"
+                    | "warped_roots"
+                    | "nether_sprouts"
+                    | "seagrass"
+                    | "tall_seagrass"
+                    | "structure_void"
+                    | "light"
+            )
+    }
 }
 
 impl TryFrom<u32> for BlockState {
@@ -38,17 +82,54 @@ impl TryFrom<u32> for BlockState {
     }
 }
 
+impl Remappable for BlockState {
+    /// Block states aren't a real registry, but servers can still remap them
+    /// when they add blocks.
+    const REGISTRY: &'static str = "minecraft:block_state";
+
+    fn from_vanilla_id(id: u32) -> Option<Self> {
+        Self::try_from(id).ok()
+    }
+    fn vanilla_id(&self) -> u32 {
+        *self as u32
+    }
+}
+
 impl McBufReadable for BlockState {
+    /// Read a block state, failing if it isn't a vanilla one. Use
+    /// [`MaybeModded<BlockState>`](azalea_buf::remap::MaybeModded) if it can
+    /// be modded.
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
         let state_id = u32::var_read_from(buf)?;
-        Self::try_from(state_id).map_err(|_| BufReadError::UnexpectedEnumVariant {
+        match remap(Self::REGISTRY, state_id) {
+            RemappedId::Vanilla(id) => Self::try_from(id).ok(),
+            RemappedId::Modded(_) => None,
+        }
+        .ok_or(BufReadError::UnexpectedEnumVariant {
             id: state_id as i32,
         })
     }
 }
 impl McBufWritable for BlockState {
     fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
-        u32::var_write_into(&(*self as u32), buf)
+        u32::var_write_into(&server_id(Self::REGISTRY, *self as u32), buf)
+    }
+}
+
+/// Block states are serialized as their state id, since there's too many of
+/// them to derive it.
+#[cfg(feature = "serde")]
+impl serde::Serialize for BlockState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(*self as u32)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BlockState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state_id = u32::deserialize(deserializer)?;
+        Self::try_from(state_id)
+            .map_err(|_| serde::de::Error::custom(format!("Invalid block state {state_id}")))
     }
 }
 
@@ -64,6 +145,13 @@ mod tests {
         assert!(BlockState::try_from(BlockState::max_state() + 1).is_err());
     }
 
+    #[test]
+    fn test_is_replaceable() {
+        assert!(BlockState::CaveAir.is_replaceable());
+        assert!(BlockState::Grass.is_replaceable());
+        assert!(!BlockState::Stone.is_replaceable());
+    }
+
     #[test]
     fn test_from_blockstate() {
         let block: Box<dyn Block> = Box::<dyn Block>::from(BlockState::Air);
@@ -11,6 +11,7 @@ use std::io::{Cursor, Write};
 use std::{collections::HashSet, hash::Hash};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Suggestions<M = String> {
     pub range: StringRange,
     pub suggestions: Vec<Suggestion<M>>,
@@ -0,0 +1,23 @@
+//! Serialize fixed-size byte arrays with serde, which only supports arrays
+//! of up to 32 elements by itself.
+//!
+//! Use it with `#[serde(with = "azalea_buf::byte_array")]`.
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer, const N: usize>(
+    bytes: &[u8; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(bytes)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    let bytes = Vec::<u8>::deserialize(deserializer)?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| de::Error::invalid_length(len, &format!("{N} bytes").as_str()))
+}
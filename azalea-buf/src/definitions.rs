@@ -2,6 +2,7 @@ use std::ops::Deref;
 
 /// A `Vec<u8>` that isn't prefixed by a VarInt with the size.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnsizedByteArray(pub Vec<u8>);
 
 impl Deref for UnsizedByteArray {
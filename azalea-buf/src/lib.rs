@@ -5,8 +5,11 @@
 #![feature(error_generic_member_access)]
 #![feature(provide_any)]
 
+#[cfg(feature = "serde")]
+pub mod byte_array;
 mod definitions;
 mod read;
+pub mod remap;
 mod serializable_uuid;
 mod write;
 
@@ -0,0 +1,247 @@
+//! Read and write the ids of registry entries for servers that use different
+//! ids than vanilla, like modded servers.
+//!
+//! [`McBufReadable`] and [`McBufWritable`] don't get any context about the
+//! connection they're used for, so the [`IdRemapper`] for a connection is set
+//! for the current thread with [`with_remapper`] while its packets are being
+//! decoded or encoded. Types that can be remapped read their id with
+//! [`remap`] and write it with [`server_id`].
+
+use crate::read::BufReadError;
+use crate::{McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable};
+use std::{
+    cell::RefCell,
+    io::{Cursor, Write},
+    sync::Arc,
+};
+
+/// Translates the raw ids a server sends into vanilla ones.
+pub trait IdRemapper: Send + Sync {
+    /// Translate a raw id from the given registry, like `minecraft:item`.
+    fn remap(&self, registry: &str, id: u32) -> RemappedId;
+    /// Translate a vanilla id from the given registry into the raw id the
+    /// server uses for it. This is the reverse of [`IdRemapper::remap`].
+    fn server_id(&self, registry: &str, vanilla_id: u32) -> u32;
+    /// Whether this remapper has the server's ids for the given registry.
+    /// Remappers that translate ids on top of another one use this to know
+    /// which registries they should leave to it.
+    fn has_registry(&self, _registry: &str) -> bool {
+        true
+    }
+}
+
+/// What a raw id from the server turned out to be.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RemappedId {
+    /// A vanilla entry, with its vanilla id.
+    Vanilla(u32),
+    /// An entry that vanilla doesn't have.
+    Modded(ModdedEntry),
+}
+
+/// A registry entry that vanilla doesn't have, usually because a mod added
+/// it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModdedEntry {
+    /// The raw id the server used.
+    pub id: u32,
+    /// The name of the entry, like `examplemod:ruby`, if the server told us.
+    pub name: Option<String>,
+}
+
+thread_local! {
+    static REMAPPER: RefCell<Option<Arc<dyn IdRemapper>>> = RefCell::new(None);
+}
+
+/// Puts the previous remapper back when dropped, so it's restored even if
+/// decoding panics.
+struct RestoreRemapper(Option<Arc<dyn IdRemapper>>);
+
+impl Drop for RestoreRemapper {
+    fn drop(&mut self) {
+        REMAPPER.with(|remapper| *remapper.borrow_mut() = self.0.take());
+    }
+}
+
+/// Run `f` with ids being remapped by `remapper`. If it's `None`, ids are read
+/// and written as vanilla ones.
+pub fn with_remapper<T>(remapper: Option<Arc<dyn IdRemapper>>, f: impl FnOnce() -> T) -> T {
+    let previous = REMAPPER.with(|current| current.replace(remapper));
+    let _restore = RestoreRemapper(previous);
+    f()
+}
+
+/// The remapper for the current thread, as set by [`with_remapper`].
+pub fn current_remapper() -> Option<Arc<dyn IdRemapper>> {
+    REMAPPER.with(|remapper| remapper.borrow().clone())
+}
+
+/// Translate a raw id with the remapper for the current thread.
+pub fn remap(registry: &str, id: u32) -> RemappedId {
+    REMAPPER.with(|remapper| match &*remapper.borrow() {
+        Some(remapper) => remapper.remap(registry, id),
+        None => RemappedId::Vanilla(id),
+    })
+}
+
+/// Translate a vanilla id into the server's raw id with the remapper for the
+/// current thread.
+pub fn server_id(registry: &str, vanilla_id: u32) -> u32 {
+    REMAPPER.with(|remapper| match &*remapper.borrow() {
+        Some(remapper) => remapper.server_id(registry, vanilla_id),
+        None => vanilla_id,
+    })
+}
+
+/// Something with a vanilla id that servers can remap.
+pub trait Remappable: Sized {
+    /// The registry that the ids are from, like `minecraft:item`.
+    const REGISTRY: &'static str;
+
+    fn from_vanilla_id(id: u32) -> Option<Self>;
+    fn vanilla_id(&self) -> u32;
+}
+
+/// A value that's either one that vanilla has or a modded one we don't know
+/// about.
+///
+/// This is read as a varint id that goes through [`remap`]. Ids that aren't
+/// valid vanilla ones are read as [`MaybeModded::Modded`] instead of failing.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaybeModded<T> {
+    Vanilla(T),
+    Modded(ModdedEntry),
+}
+
+impl<T> MaybeModded<T> {
+    /// The vanilla value, or `None` if it's modded.
+    pub fn vanilla(&self) -> Option<&T> {
+        match self {
+            MaybeModded::Vanilla(value) => Some(value),
+            MaybeModded::Modded(_) => None,
+        }
+    }
+}
+
+impl<T> From<T> for MaybeModded<T> {
+    fn from(value: T) -> Self {
+        MaybeModded::Vanilla(value)
+    }
+}
+
+impl<T: Remappable> McBufReadable for MaybeModded<T> {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        let id = u32::var_read_from(buf)?;
+        Ok(match remap(T::REGISTRY, id) {
+            RemappedId::Vanilla(vanilla_id) => match T::from_vanilla_id(vanilla_id) {
+                Some(value) => MaybeModded::Vanilla(value),
+                None => MaybeModded::Modded(ModdedEntry { id, name: None }),
+            },
+            RemappedId::Modded(entry) => MaybeModded::Modded(entry),
+        })
+    }
+}
+
+impl<T: Remappable> McBufWritable for MaybeModded<T> {
+    /// Vanilla values are written with the id the server uses for them, and
+    /// modded ones with the id the server sent.
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        match self {
+            MaybeModded::Vanilla(value) => {
+                server_id(T::REGISTRY, value.vanilla_id()).var_write_into(buf)
+            }
+            MaybeModded::Modded(entry) => entry.id.var_write_into(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Color(u32);
+    impl Remappable for Color {
+        const REGISTRY: &'static str = "test:color";
+        fn from_vanilla_id(id: u32) -> Option<Self> {
+            (id < 16).then_some(Color(id))
+        }
+        fn vanilla_id(&self) -> u32 {
+            self.0
+        }
+    }
+
+    /// Swaps ids 0 and 1 and says 100 is modded.
+    struct TestRemapper;
+    impl IdRemapper for TestRemapper {
+        fn remap(&self, _registry: &str, id: u32) -> RemappedId {
+            match id {
+                0 => RemappedId::Vanilla(1),
+                1 => RemappedId::Vanilla(0),
+                100 => RemappedId::Modded(ModdedEntry {
+                    id,
+                    name: Some("test:octarine".to_string()),
+                }),
+                id => RemappedId::Vanilla(id),
+            }
+        }
+        fn server_id(&self, _registry: &str, vanilla_id: u32) -> u32 {
+            match vanilla_id {
+                0 => 1,
+                1 => 0,
+                id => id,
+            }
+        }
+    }
+
+    fn read(id: u32) -> MaybeModded<Color> {
+        let mut data = Vec::new();
+        id.var_write_into(&mut data).unwrap();
+        MaybeModded::read_from(&mut Cursor::new(&data[..])).unwrap()
+    }
+
+    #[test]
+    fn test_remap() {
+        assert_eq!(read(0), MaybeModded::Vanilla(Color(0)));
+        assert!(matches!(
+            read(100),
+            MaybeModded::Modded(ModdedEntry { name: None, .. })
+        ));
+
+        with_remapper(Some(Arc::new(TestRemapper)), || {
+            assert_eq!(read(0), MaybeModded::Vanilla(Color(1)));
+            assert_eq!(
+                read(100),
+                MaybeModded::Modded(ModdedEntry {
+                    id: 100,
+                    name: Some("test:octarine".to_string())
+                })
+            );
+        });
+        // the remapper is only used inside with_remapper
+        assert_eq!(read(1), MaybeModded::Vanilla(Color(1)));
+    }
+
+    fn write(value: &MaybeModded<Color>) -> Vec<u8> {
+        let mut data = Vec::new();
+        value.write_into(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_remap_round_trip() {
+        with_remapper(Some(Arc::new(TestRemapper)), || {
+            for id in [0, 1, 5, 100] {
+                let value = read(id);
+                let mut data = Vec::new();
+                id.var_write_into(&mut data).unwrap();
+                assert_eq!(write(&value), data);
+            }
+            // color 1 is 0 on the server
+            assert_eq!(write(&MaybeModded::Vanilla(Color(1))), vec![0]);
+        });
+        assert_eq!(write(&MaybeModded::Vanilla(Color(1))), vec![1]);
+    }
+}
@@ -3,7 +3,7 @@ use std::{collections::HashMap, fmt};
 #[cfg(feature = "azalea-buf")]
 use azalea_buf::McBuf;
 use once_cell::sync::Lazy;
-use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 use serde_json::Value;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -87,7 +87,7 @@ impl Ansi {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "azalea-buf", derive(McBuf))]
 pub enum ChatFormatting {
     Black,
@@ -2,7 +2,13 @@
 
 use std::sync::Arc;
 
-use crate::get_mc_dir;
+use crate::{
+    get_mc_dir,
+    login::{LoginQueryHandler, LoginQueryHandlers},
+};
+use azalea_auth::game_profile::GameProfile;
+use azalea_core::ResourceLocation;
+use azalea_protocol::{forwarding::Forwarding, proxy::Proxy};
 use parking_lot::Mutex;
 use uuid::Uuid;
 
@@ -38,6 +44,23 @@ pub struct Account {
     /// "Invalid Session" errors. If you don't need that feature (like in
     /// offline mode), then you can set this to `AuthOpts::default()`.
     pub auth_opts: AuthOpts,
+
+    /// The proxy that this account connects to servers through, or `None` to
+    /// connect directly.
+    pub proxy: Option<Proxy>,
+
+    /// Player information to forward to the server like a BungeeCord or
+    /// Velocity proxy would, for joining servers that are normally behind
+    /// one.
+    pub forwarding: Option<Forwarding>,
+
+    /// The brand we tell servers we are, like `vanilla` or `fabric`. This is
+    /// `vanilla` if it's `None`.
+    pub brand: Option<String>,
+
+    /// What to answer the custom queries that servers send while we're
+    /// logging in with. See [`login`](crate::login).
+    pub login_query_handlers: LoginQueryHandlers,
 }
 
 /// The parameters that were passed for creating the associated [`Account`].
@@ -60,6 +83,10 @@ impl Account {
             auth_opts: AuthOpts::Offline {
                 username: username.to_string(),
             },
+            proxy: None,
+            forwarding: None,
+            brand: None,
+            login_query_handlers: LoginQueryHandlers::default(),
         }
     }
 
@@ -89,9 +116,73 @@ impl Account {
             auth_opts: AuthOpts::Microsoft {
                 email: email.to_string(),
             },
+            proxy: None,
+            forwarding: None,
+            brand: None,
+            login_query_handlers: LoginQueryHandlers::default(),
         })
     }
 
+    /// Make this account connect through the given proxy.
+    ///
+    /// ```rust,no_run
+    /// use azalea_client::Account;
+    ///
+    /// let account = Account::offline("bot").with_proxy("socks5://127.0.0.1:1080".parse().unwrap());
+    /// ```
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Forward our player information to the server as if we were connecting
+    /// through BungeeCord or Velocity. Only use this with servers you're
+    /// allowed to join this way.
+    ///
+    /// ```rust,no_run
+    /// use azalea_client::Account;
+    /// use azalea_protocol::forwarding::Forwarding;
+    ///
+    /// let account = Account::offline("bot")
+    ///     .with_forwarding(Forwarding::velocity("secret", "127.0.0.1".parse().unwrap()));
+    /// ```
+    pub fn with_forwarding(mut self, forwarding: Forwarding) -> Self {
+        self.forwarding = Some(forwarding);
+        self
+    }
+
+    /// Tell servers that we're the given brand instead of `vanilla`.
+    ///
+    /// ```rust,no_run
+    /// use azalea_client::Account;
+    ///
+    /// let account = Account::offline("bot").with_brand("fabric");
+    /// ```
+    pub fn with_brand(mut self, brand: &str) -> Self {
+        self.brand = Some(brand.to_string());
+        self
+    }
+
+    /// Answer custom login queries with the given identifier with
+    /// `handler`. See [`login`](crate::login) for an example.
+    pub fn with_login_query_handler(
+        mut self,
+        identifier: ResourceLocation,
+        handler: impl LoginQueryHandler + 'static,
+    ) -> Self {
+        self.login_query_handlers.insert(identifier, handler);
+        self
+    }
+
+    /// The profile that's forwarded to the server. This uses the offline-mode
+    /// UUID if the account doesn't have one.
+    pub fn forwarded_profile(&self) -> GameProfile {
+        let uuid = self
+            .uuid
+            .unwrap_or_else(|| azalea_auth::offline::generate_uuid(&self.username));
+        GameProfile::new(uuid, self.username.clone())
+    }
+
     /// Refresh the access_token for this account to be valid again.
     ///
     /// This requires the `auth_opts` field to be set correctly (which is done
@@ -0,0 +1,301 @@
+//! Plugin channels, which are how servers and clients send each other custom
+//! data that vanilla doesn't know about.
+//!
+//! Both sides tell each other which channels they can receive with
+//! [`REGISTER_CHANNEL`] and [`UNREGISTER_CHANNEL`]. The client and server
+//! also tell each other their brand (like `vanilla` or `fabric`) on
+//! [`BRAND_CHANNEL`] when the client logs in.
+//!
+//! ```rust,no_run
+//! # use azalea_client::Client;
+//! # use azalea_buf::McBuf;
+//! # use azalea_core::ResourceLocation;
+//! #[derive(Debug, McBuf)]
+//! struct Balance {
+//!     player: String,
+//!     #[var]
+//!     coins: u32,
+//! }
+//!
+//! # async fn example(client: Client) -> Result<(), std::io::Error> {
+//! client
+//!     .register_channel(
+//!         ResourceLocation::new("myplugin:balance").unwrap(),
+//!         |_client, balance: Balance| println!("{balance:?}"),
+//!     )
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::Client;
+use azalea_buf::{BufReadError, McBufReadable, McBufWritable};
+use azalea_core::ResourceLocation;
+use azalea_protocol::packets::game::{
+    clientbound_custom_payload_packet::ClientboundCustomPayloadPacket,
+    serverbound_custom_payload_packet::ServerboundCustomPayloadPacket,
+};
+use log::{debug, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Cursor,
+    sync::Arc,
+};
+
+/// The channel that the channels a side can receive are sent on, separated
+/// by null bytes.
+pub const REGISTER_CHANNEL: &str = "minecraft:register";
+/// The channel that the channels a side can't receive anymore are sent on.
+pub const UNREGISTER_CHANNEL: &str = "minecraft:unregister";
+/// The channel that the client and server send their brand on.
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+
+/// The brand we tell servers if the account doesn't have one set.
+pub const DEFAULT_BRAND: &str = "vanilla";
+
+type Handler = Arc<dyn Fn(&Client, &mut Cursor<&[u8]>) -> Result<(), BufReadError> + Send + Sync>;
+
+/// The channels that we and the server registered.
+pub(crate) struct Channels {
+    /// The brand we send to the server when we log in.
+    pub brand: String,
+    /// The brand the server sent us.
+    pub server_brand: Option<String>,
+    /// The channels the server said it can receive.
+    pub server_channels: HashSet<ResourceLocation>,
+    /// The channels we registered, and what to do with their payloads.
+    handlers: HashMap<ResourceLocation, Handler>,
+}
+
+impl Default for Channels {
+    fn default() -> Self {
+        Self {
+            brand: DEFAULT_BRAND.to_string(),
+            server_brand: None,
+            server_channels: HashSet::new(),
+            handlers: HashMap::new(),
+        }
+    }
+}
+
+/// Channel names separated by null bytes, like in [`REGISTER_CHANNEL`].
+fn channel_list<'a>(channels: impl IntoIterator<Item = &'a ResourceLocation>) -> Vec<u8> {
+    channels
+        .into_iter()
+        .map(|channel| channel.to_string())
+        .collect::<Vec<_>>()
+        .join("\0")
+        .into_bytes()
+}
+
+fn parse_channel_list(data: &[u8]) -> Vec<ResourceLocation> {
+    String::from_utf8_lossy(data)
+        .split('\0')
+        .filter(|channel| !channel.is_empty())
+        .filter_map(|channel| ResourceLocation::new(channel).ok())
+        .collect()
+}
+
+impl Client {
+    /// Start receiving payloads on a plugin channel and tell the server that
+    /// we can receive them. The payloads are decoded as `T` and passed to
+    /// `handler`, replacing the handler that was there before.
+    ///
+    /// The handler is called on the task that handles packets, so it
+    /// shouldn't block. Payloads that can't be decoded are logged and
+    /// ignored.
+    pub async fn register_channel<T: McBufReadable>(
+        &self,
+        channel: ResourceLocation,
+        handler: impl Fn(&Client, T) + Send + Sync + 'static,
+    ) -> Result<(), std::io::Error> {
+        let handler: Handler = Arc::new(move |client, buf| {
+            handler(client, T::read_from(buf)?);
+            Ok(())
+        });
+        self.channels
+            .write()
+            .handlers
+            .insert(channel.clone(), handler);
+        self.write_packet(
+            ServerboundCustomPayloadPacket {
+                identifier: ResourceLocation::new(REGISTER_CHANNEL).unwrap(),
+                data: channel_list([&channel]).into(),
+            }
+            .get(),
+        )
+        .await
+    }
+
+    /// Stop receiving payloads on a plugin channel and tell the server.
+    pub async fn unregister_channel(
+        &self,
+        channel: &ResourceLocation,
+    ) -> Result<(), std::io::Error> {
+        self.channels.write().handlers.remove(channel);
+        self.write_packet(
+            ServerboundCustomPayloadPacket {
+                identifier: ResourceLocation::new(UNREGISTER_CHANNEL).unwrap(),
+                data: channel_list([channel]).into(),
+            }
+            .get(),
+        )
+        .await
+    }
+
+    /// The plugin channels we registered with [`Client::register_channel`].
+    pub fn registered_channels(&self) -> Vec<ResourceLocation> {
+        self.channels.read().handlers.keys().cloned().collect()
+    }
+
+    /// The plugin channels that the server said it can receive.
+    pub fn server_channels(&self) -> HashSet<ResourceLocation> {
+        self.channels.read().server_channels.clone()
+    }
+
+    /// The brand of the server, like `vanilla` or `Paper`, if it sent one.
+    pub fn server_brand(&self) -> Option<String> {
+        self.channels.read().server_brand.clone()
+    }
+
+    /// Set the brand we tell the server we are. This is sent when we log in,
+    /// so it's usually set with [`Account::with_brand`] instead.
+    ///
+    /// [`Account::with_brand`]: crate::Account::with_brand
+    pub fn set_brand(&self, brand: impl Into<String>) {
+        self.channels.write().brand = brand.into();
+    }
+
+    /// Send a payload to the server on a plugin channel.
+    pub async fn send_plugin_message(
+        &self,
+        channel: ResourceLocation,
+        payload: &impl McBufWritable,
+    ) -> Result<(), std::io::Error> {
+        let mut data = Vec::new();
+        payload.write_into(&mut data)?;
+        self.write_packet(
+            ServerboundCustomPayloadPacket {
+                identifier: channel,
+                data: data.into(),
+            }
+            .get(),
+        )
+        .await
+    }
+
+    /// Tell the server our brand. This is done when we log in.
+    pub(crate) async fn send_brand(&self) -> Result<(), std::io::Error> {
+        let brand = self.channels.read().brand.clone();
+        self.send_plugin_message(ResourceLocation::new(BRAND_CHANNEL).unwrap(), &brand)
+            .await
+    }
+
+    /// Keep track of the channels and brand the server sends, and call the
+    /// handler for the payload's channel if we registered it.
+    pub(crate) fn handle_plugin_message(&self, packet: &ClientboundCustomPayloadPacket) {
+        match packet.identifier.to_string().as_str() {
+            REGISTER_CHANNEL => {
+                let channels = parse_channel_list(&packet.data);
+                debug!("Server registered channels {channels:?}");
+                self.channels.write().server_channels.extend(channels);
+            }
+            UNREGISTER_CHANNEL => {
+                let mut state = self.channels.write();
+                for channel in parse_channel_list(&packet.data) {
+                    state.server_channels.remove(&channel);
+                }
+            }
+            BRAND_CHANNEL => match String::read_from(&mut Cursor::new(&packet.data)) {
+                Ok(brand) => self.channels.write().server_brand = Some(brand),
+                Err(e) => warn!("Couldn't read server brand: {e}"),
+            },
+            _ => {
+                // clone the handler so it can register channels without
+                // deadlocking
+                let handler = self
+                    .channels
+                    .read()
+                    .handlers
+                    .get(&packet.identifier)
+                    .cloned();
+                if let Some(handler) = handler {
+                    if let Err(e) = handler(self, &mut Cursor::new(&packet.data)) {
+                        warn!(
+                            "Couldn't read payload on channel {}: {e}",
+                            packet.identifier
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::client_and_server;
+    use azalea_buf::McBuf;
+    use azalea_protocol::packets::game::ServerboundGamePacket;
+    use parking_lot::Mutex;
+
+    #[derive(Debug, Clone, PartialEq, McBuf)]
+    struct Balance {
+        player: String,
+        #[var]
+        coins: u32,
+    }
+
+    fn payload(channel: &str, data: Vec<u8>) -> ClientboundCustomPayloadPacket {
+        ClientboundCustomPayloadPacket {
+            identifier: ResourceLocation::new(channel).unwrap(),
+            data: data.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_channel() {
+        let (client, mut server) = client_and_server();
+
+        let channel = ResourceLocation::new("myplugin:balance").unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        client
+            .register_channel(channel.clone(), move |_, balance: Balance| {
+                received_clone.lock().push(balance)
+            })
+            .await
+            .unwrap();
+        match server.read().await.unwrap() {
+            ServerboundGamePacket::CustomPayload(p) => {
+                assert_eq!(p.identifier.to_string(), REGISTER_CHANNEL);
+                assert_eq!(&p.data[..], b"myplugin:balance");
+            }
+            p => panic!("Expected a custom payload, got {p:?}"),
+        }
+
+        let balance = Balance {
+            player: "bot".to_string(),
+            coins: 300,
+        };
+        let mut data = Vec::new();
+        balance.write_into(&mut data).unwrap();
+        client.handle_plugin_message(&payload("myplugin:balance", data));
+        // payloads that can't be decoded are ignored
+        client.handle_plugin_message(&payload("myplugin:balance", vec![]));
+        assert_eq!(*received.lock(), vec![balance]);
+
+        client.handle_plugin_message(&payload(
+            REGISTER_CHANNEL,
+            b"myplugin:balance\0myplugin:shop".to_vec(),
+        ));
+        client.handle_plugin_message(&payload(UNREGISTER_CHANNEL, b"myplugin:shop".to_vec()));
+        assert_eq!(client.server_channels(), HashSet::from([channel]));
+
+        let mut data = Vec::new();
+        "Paper".write_into(&mut data).unwrap();
+        client.handle_plugin_message(&payload(BRAND_CHANNEL, data));
+        assert_eq!(client.server_brand().as_deref(), Some("Paper"));
+    }
+}
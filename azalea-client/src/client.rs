@@ -1,14 +1,34 @@
 pub use crate::chat::ChatPacket;
-use crate::{movement::WalkDirection, plugins::PluginStates, Account, PlayerInfo};
+use crate::{
+    channels::Channels,
+    filter::{PacketAction, PacketFilter, RawPacket},
+    middleware::PacketMiddleware,
+    mining::{BlockPrediction, BlockTags},
+    movement::WalkDirection,
+    plugins::PluginStates,
+    replay::Recorder,
+    Account, PlayerInfo,
+};
 use azalea_auth::{game_profile::GameProfile, sessionserver::ClientSessionServerError};
-use azalea_buf::McBufReadable;
+use azalea_block::BlockState;
+use azalea_buf::{remap::with_remapper, BufReadError, McBufReadable, McBufWritable};
 use azalea_chat::Component;
 use azalea_core::{ChunkPos, ResourceLocation, Vec3};
+use azalea_inventory::Container;
 use azalea_protocol::{
-    connect::{Connection, ConnectionError, ReadConnection, WriteConnection},
+    connect::{Connection, ConnectionError, ReadConnection, SharedRemapper, WriteConnection},
+    forwarding::{self, BungeeCordForwarding, Forwarding, ForwardingMode},
     packets::{
-        fabric::ClientboundFabricRegistryPacket,
+        fabric::{
+            self, ClientboundFabricRegistryPacket, FabricRegistries, FabricRegistrySyncBuffer,
+            ServerboundFabricRegistryPacket,
+        },
         forge::{
+            fml2::{
+                serverbound_acknowledge_packet::ServerboundAcknowledgePacket as Fml2AcknowledgePacket,
+                serverbound_mod_list_reply_packet::ServerboundModListReplyPacket as Fml2ModListReplyPacket,
+                ClientboundFml2Packet,
+            },
             serverbound_acknowledge_packet::ServerboundAcknowledgePacket,
             serverbound_mod_list_reply_packet::ServerboundModListReplyPacket,
             ClientboundForgePacket,
@@ -17,26 +37,32 @@ use azalea_protocol::{
             clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket,
             serverbound_accept_teleportation_packet::ServerboundAcceptTeleportationPacket,
             serverbound_client_information_packet::ServerboundClientInformationPacket,
-            serverbound_custom_payload_packet::ServerboundCustomPayloadPacket,
             serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
             serverbound_move_player_pos_rot_packet::ServerboundMovePlayerPosRotPacket,
-            ClientboundGamePacket, ServerboundGamePacket,
+            ClientboundGamePacket, ClientboundGamePacketKind, ServerboundGamePacket,
+            ServerboundGamePacketKind,
         },
         handshake::{
             client_intention_packet::ClientIntentionPacket, ClientIdentifier,
             ClientboundHandshakePacket, ServerboundHandshakePacket,
         },
         login::{
+            clientbound_custom_query_packet::ClientboundCustomQueryPacket,
             serverbound_custom_query_packet::{CustomQuery, ServerboundCustomQueryPacket},
             serverbound_hello_packet::ServerboundHelloPacket,
             serverbound_key_packet::ServerboundKeyPacket,
             ClientboundLoginPacket,
         },
-        ConnectionProtocol, PROTOCOL_VERSION,
+        ConnectionProtocol, ProtocolPacket, ProtocolVersion,
     },
-    read::ReadPacketError,
-    resolver, ServerAddress,
+    proxy::Proxy,
+    read::{deserialize_packet, ReadPacketError},
+    remap::{remapper_for_version, RegistryRemapper},
+    resolver::{self, Resolver},
+    stats::SharedTrafficStats,
+    ServerAddress,
 };
+use azalea_registry::MobEffect;
 use azalea_world::{
     entity::{metadata, Entity, EntityData, EntityMetadata},
     PartialWorld, WeakWorld, WeakWorldContainer,
@@ -46,14 +72,18 @@ use parking_lot::{Mutex, RwLock};
 use std::{
     any,
     backtrace::Backtrace,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     io::{self, Cursor},
     sync::Arc,
+    time::Instant,
 };
 use thiserror::Error;
 use tokio::{
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Notify,
+    },
     task::JoinHandle,
     time::{self},
 };
@@ -80,6 +110,9 @@ pub enum Event {
     /// Happens 20 times per second, but only when the world is loaded.
     Tick,
     Packet(Arc<ClientboundGamePacket>),
+    /// A packet that the [`PacketFilter`] said not to decode. It wasn't
+    /// handled by the client, but you can still decode it yourself.
+    RawPacket(Arc<RawPacket>),
     /// A player joined the game (or more specifically, was added to the tab
     /// list).
     AddPlayer(PlayerInfo),
@@ -91,6 +124,9 @@ pub enum Event {
     UpdatePlayer(PlayerInfo),
     /// The client player died in-game.
     Death(Option<Arc<ClientboundPlayerCombatKillPacket>>),
+    /// A Fabric server told us the raw ids of its registry entries. You can
+    /// also get these later with [`Client::fabric_registries`].
+    FabricRegistries(Arc<FabricRegistries>),
 }
 
 /// A player that you control that is currently in a Minecraft server.
@@ -116,7 +152,47 @@ pub struct Client {
     pub plugins: Arc<PluginStates>,
     /// A map of player uuids to their information in the tab list
     pub players: Arc<RwLock<HashMap<Uuid, PlayerInfo>>>,
-    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    /// The protocol version we're connected with.
+    pub version: ProtocolVersion,
+    /// Which packets we decode. Change it with [`Client::set_packet_filter`].
+    pub packet_filter: Arc<RwLock<PacketFilter>>,
+    /// The chain of middleware that packets go through, in order. Add to it
+    /// with [`Client::add_middleware`].
+    pub middleware: Arc<RwLock<Vec<Arc<dyn PacketMiddleware>>>>,
+    /// Where the packets we receive are written to, if we're recording. See
+    /// [`Client::start_recording`].
+    pub(crate) recorder: Arc<Mutex<Option<Recorder>>>,
+    /// The same statistics as the read and write connections have, so we
+    /// don't have to lock the connections to get them.
+    pub(crate) received_stats: SharedTrafficStats<ClientboundGamePacketKind>,
+    pub(crate) sent_stats: SharedTrafficStats<ServerboundGamePacketKind>,
+    pub(crate) tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    /// The registries a Fabric server synced, and the parts of the sync that
+    /// we're still waiting for the rest of.
+    pub(crate) fabric_registries: Arc<RwLock<Option<Arc<FabricRegistries>>>>,
+    pub(crate) fabric_registry_sync: Arc<Mutex<FabricRegistrySyncBuffer>>,
+    /// The same remapper as the connection has, for decoding packets without
+    /// locking it and for replacing it when a Fabric server syncs its
+    /// registries.
+    pub(crate) remapper: SharedRemapper,
+    /// Our brand and plugin channels, and the server's. See
+    /// [`channels`](crate::channels).
+    pub(crate) channels: Arc<RwLock<Channels>>,
+    /// The player's inventory, which is always open.
+    pub(crate) inventory: Arc<RwLock<Container>>,
+    /// The container the player has open, if it's not their own inventory.
+    pub(crate) open_container: Arc<RwLock<Option<Container>>>,
+    /// Notified when the server sends the contents of a container it opened.
+    pub(crate) container_opened: Arc<Notify>,
+    /// Which slot of the hotbar the player is holding.
+    pub(crate) selected_hotbar_slot: Arc<RwLock<u8>>,
+    /// The effects the player has, and their amplifiers.
+    pub(crate) effects: Arc<RwLock<HashMap<MobEffect, u8>>>,
+    /// The sequence numbers of our block changes that the server handled.
+    pub(crate) block_prediction: Arc<BlockPrediction>,
+    /// The block tags the server sent, which we need to know which tools
+    /// break which blocks.
+    pub(crate) block_tags: Arc<RwLock<BlockTags>>,
 }
 
 #[derive(Default)]
@@ -156,6 +232,10 @@ pub enum JoinError {
     Auth(#[from] azalea_auth::AuthError),
     #[error("Disconnected: {reason}")]
     Disconnect { reason: Component },
+    #[error("Couldn't answer login query: {0}")]
+    LoginQuery(anyhow::Error),
+    #[error("Couldn't read the channel of a Forge login packet: {0}")]
+    ForgeChannel(BufReadError),
 }
 
 #[derive(Error, Debug)]
@@ -170,6 +250,33 @@ pub enum HandleError {
     Send(#[from] mpsc::error::SendError<Event>),
 }
 
+/// How [`Client::join`] connects to a server. The defaults join like a
+/// vanilla client on the latest version.
+///
+/// ```rust
+/// # use azalea_client::JoinOptions;
+/// # use azalea_protocol::packets::ProtocolVersion;
+/// let options = JoinOptions {
+///     version: ProtocolVersion::V760,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct JoinOptions {
+    /// Marks the client as either vanilla, forge, or fabric.
+    pub identifier: ClientIdentifier,
+    /// The protocol version to connect with.
+    pub version: ProtocolVersion,
+    /// How the server's address is resolved. If this is `None`, a
+    /// [`Resolver`] with the default options is used.
+    pub resolver: Option<Resolver>,
+    /// Which packets the client decodes, starting from the first game packet.
+    pub packet_filter: PacketFilter,
+    /// The proxy to connect through. If this is `None`, the account's
+    /// [`proxy`](Account::proxy) is used, if it has one.
+    pub proxy: Option<Proxy>,
+}
+
 impl Client {
     /// Create a new client from the given GameProfile, Connection, and World.
     /// You should only use this if you want to change these fields from the
@@ -179,7 +286,11 @@ impl Client {
         conn: Connection<ClientboundGamePacket, ServerboundGamePacket>,
         world_container: Option<Arc<RwLock<WeakWorldContainer>>>,
     ) -> Self {
+        let version = conn.version();
         let (read_conn, write_conn) = conn.into_split();
+        let received_stats = read_conn.stats.clone();
+        let sent_stats = write_conn.stats.clone();
+        let remapper = read_conn.remapper.clone();
         let (read_conn, write_conn) = (
             Arc::new(tokio::sync::Mutex::new(read_conn)),
             Arc::new(tokio::sync::Mutex::new(write_conn)),
@@ -202,11 +313,30 @@ impl Client {
             // field right after this. No Mutex so the user doesn't need to .lock().
             plugins: Arc::new(PluginStates::default()),
             players: Arc::new(RwLock::new(HashMap::new())),
+            version,
+            packet_filter: Arc::new(RwLock::new(PacketFilter::default())),
+            middleware: Arc::new(RwLock::new(Vec::new())),
+            recorder: Arc::new(Mutex::new(None)),
+            received_stats,
+            sent_stats,
             tasks: Arc::new(Mutex::new(Vec::new())),
+            fabric_registries: Arc::new(RwLock::new(None)),
+            fabric_registry_sync: Arc::new(Mutex::new(FabricRegistrySyncBuffer::default())),
+            remapper,
+            channels: Arc::new(RwLock::new(Channels::default())),
+            inventory: Arc::new(RwLock::new(Container::player_inventory())),
+            open_container: Arc::new(RwLock::new(None)),
+            container_opened: Arc::new(Notify::new()),
+            selected_hotbar_slot: Arc::new(RwLock::new(0)),
+            effects: Arc::new(RwLock::new(HashMap::new())),
+            block_prediction: Arc::new(BlockPrediction::default()),
+            block_tags: Arc::new(RwLock::new(BlockTags::new())),
         }
     }
 
-    /// Connect to a Minecraft server.
+    /// Connect to a Minecraft server. The connection goes through the
+    /// options' [`proxy`](JoinOptions::proxy), or the account's if the
+    /// options don't have one.
     ///
     /// To change the render distance and other settings, use
     /// [`Client::set_client_information`]. To watch for events like packets
@@ -215,12 +345,12 @@ impl Client {
     /// # Examples
     ///
     /// ```rust,no_run
-    /// use azalea_client::{Client, Account};
+    /// use azalea_client::{Account, Client, JoinOptions};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let account = Account::offline("bot");
-    ///     let (client, rx) = Client::join(&account, "localhost").await?;
+    ///     let (client, rx) = Client::join(&account, "localhost", JoinOptions::default()).await?;
     ///     client.chat("Hello, world!").await?;
     ///     client.disconnect().await?;
     ///     Ok(())
@@ -229,13 +359,20 @@ impl Client {
     pub async fn join(
         account: &Account,
         address: impl TryInto<ServerAddress>,
-        identifier: ClientIdentifier,
+        options: JoinOptions,
     ) -> Result<(Self, Receiver<Event>), JoinError> {
         let address: ServerAddress = address.try_into().map_err(|_| JoinError::InvalidAddress)?;
-        let resolved_address = resolver::resolve_address(&address).await?;
 
-        let conn = Connection::new(&resolved_address).await?;
-        let (conn, game_profile) = Self::handshake(conn, identifier, account, &address).await?;
+        let resolver = options.resolver.unwrap_or_default();
+        let conn = match options.proxy.as_ref().or(account.proxy.as_ref()) {
+            Some(proxy) => {
+                Connection::new_with_proxy(&proxy.resolve(&resolver, &address).await?, proxy)
+                    .await?
+            }
+            None => Connection::new(&resolver.resolve_address(&address).await?).await?,
+        };
+        let (conn, game_profile) =
+            Self::handshake(conn, options.identifier, options.version, account, &address).await?;
 
         // The buffer has to be 1 to avoid a bug where if it lags events are
         // received a bit later instead of the instant they were fired.
@@ -244,6 +381,10 @@ impl Client {
 
         // we got the GameConnection, so the server is now connected :)
         let client = Client::new(game_profile, conn, None);
+        client.set_packet_filter(options.packet_filter);
+        if let Some(brand) = &account.brand {
+            client.set_brand(brand);
+        }
 
         tx.send(Event::Init).await.expect("Failed to send event");
 
@@ -255,13 +396,15 @@ impl Client {
     }
 
     /// Do a handshake with the server and get to the game state from the
-    /// initial handshake state.
+    /// initial handshake state. The connection will use the given protocol
+    /// version from now on.
     ///
     /// This will also automatically refresh the account's access token if
     /// it's expired.
     pub async fn handshake(
         mut conn: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket>,
         identifier: ClientIdentifier,
+        version: ProtocolVersion,
         account: &Account,
         address: &ServerAddress,
     ) -> Result<
@@ -272,12 +415,24 @@ impl Client {
         JoinError,
     > {
         // handshake
+        conn.set_version(version);
+        let bungeecord = match &account.forwarding {
+            Some(Forwarding {
+                mode: ForwardingMode::BungeeCord,
+                address,
+            }) => Some(BungeeCordForwarding::new(
+                *address,
+                &account.forwarded_profile(),
+            )),
+            _ => None,
+        };
         conn.write(
             ClientIntentionPacket {
-                protocol_version: PROTOCOL_VERSION,
+                protocol_version: version.id(),
                 hostname: address.host.clone(),
                 port: address.port,
                 intention: ConnectionProtocol::Login,
+                forwarding: bungeecord,
                 identifier,
             }
             .get(),
@@ -296,6 +451,9 @@ impl Client {
         )
         .await?;
 
+        // the ids of modded registry entries, if it's a Forge server
+        let mut remapper = RegistryRemapper::default();
+
         let (conn, profile) = loop {
             let packet = conn.read().await?;
             match packet {
@@ -357,123 +515,23 @@ impl Client {
                 }
                 ClientboundLoginPacket::GameProfile(p) => {
                     debug!("Got profile {:?}", p.game_profile);
-                    break (conn.game(), p.game_profile);
+                    let conn = conn.game();
+                    if !remapper.is_empty() {
+                        conn.set_remapper(Some(Arc::new(remapper)));
+                    }
+                    break (conn, p.game_profile);
                 }
                 ClientboundLoginPacket::LoginDisconnect(p) => {
                     debug!("Got disconnect {:?}", p);
                     return Err(JoinError::Disconnect { reason: p.reason });
                 }
-                ClientboundLoginPacket::CustomQuery(p) => match identifier {
-                    ClientIdentifier::Vanilla => {
-                        debug!("Got custom query {:?}", p);
-                        conn.write(
-                            ServerboundCustomQueryPacket {
-                                transaction_id: p.transaction_id,
-                                query: None,
-                            }
-                            .get(),
-                        )
-                        .await?;
-                    }
-                    ClientIdentifier::Forge => {
-                        if p.identifier.to_string() == "fml:loginwrapper" {
-                            let buf_data = p.data.to_vec();
-                            let mut buf: Cursor<&[u8]> = Cursor::new(&buf_data);
-
-                            let channel = ResourceLocation::read_from(&mut buf)
-                                .expect("Error reading fml channel");
-                            if let Ok(packet) =
-                                ClientboundForgePacket::read_from_buf(&mut buf).await
-                            {
-                                debug!("Got forge packet {:?}", packet);
-                                match packet {
-                                    ClientboundForgePacket::ModData(_) => {}
-                                    ClientboundForgePacket::ChannelMismatchData(_) => {}
-                                    ClientboundForgePacket::ModList(m) => {
-                                        conn.write(
-                                            ServerboundCustomQueryPacket {
-                                                transaction_id: p.transaction_id,
-                                                query: Some(CustomQuery {
-                                                    identifier: Some(channel),
-                                                    data: ServerboundModListReplyPacket::from(m)
-                                                        .get()
-                                                        .write_to_vec()
-                                                        .await?
-                                                        .into(),
-                                                }),
-                                            }
-                                            .get(),
-                                        )
-                                        .await?;
-                                    }
-                                    _ => {
-                                        conn.write(
-                                            ServerboundCustomQueryPacket {
-                                                transaction_id: p.transaction_id,
-                                                query: Some(CustomQuery {
-                                                    identifier: Some(channel),
-                                                    data: ServerboundAcknowledgePacket::new()
-                                                        .get()
-                                                        .write_to_vec()
-                                                        .await?
-                                                        .into(),
-                                                }),
-                                            }
-                                            .get(),
-                                        )
-                                        .await?;
-                                    }
-                                }
-                            } else {
-                                debug!("Unable to decode Forge packet {:?}", buf_data);
-                            }
-                        } else {
-                            debug!("Got custom query {:?}", p);
-                            conn.write(
-                                ServerboundCustomQueryPacket {
-                                    transaction_id: p.transaction_id,
-                                    query: None,
-                                }
-                                .get(),
-                            )
-                            .await?;
-                        }
+                ClientboundLoginPacket::CustomQuery(p) => {
+                    if let Some(reply) =
+                        Self::handle_custom_query(account, identifier, &mut remapper, &p).await?
+                    {
+                        conn.write(reply.get()).await?;
                     }
-                    ClientIdentifier::Fabric => {
-                        let buf_data = p.data.to_vec();
-                        let mut buf: Cursor<&[u8]> = Cursor::new(&buf_data);
-
-                        match p.identifier.to_string().as_str() {
-                            "fabric-networking-api-v1:early_registration" => {
-                                if let Ok(p) = ClientboundFabricRegistryPacket::read_from(&mut buf)
-                                {
-                                    debug!("{p:?}");
-                                } else {
-                                    debug!("Unknown packet sent to fabric-networking-api-v1:early_registration: {p:?}")
-                                }
-                                conn.write(
-                                    ServerboundCustomQueryPacket {
-                                        transaction_id: p.transaction_id,
-                                        query: None,
-                                    }
-                                    .get(),
-                                )
-                                .await?;
-                            }
-                            _ => {
-                                debug!("Got custom query {:?}", p);
-                                conn.write(
-                                    ServerboundCustomQueryPacket {
-                                        transaction_id: p.transaction_id,
-                                        query: None,
-                                    }
-                                    .get(),
-                                )
-                                .await?;
-                            }
-                        }
-                    }
-                },
+                }
                 ClientboundLoginPacket::LoginError(p) => {
                     debug!("Recieved Login Error {p:?}");
                 }
@@ -483,12 +541,183 @@ impl Client {
         Ok((conn, profile))
     }
 
-    /// Write a packet directly to the server.
+    /// Work out our reply to a login plugin request, or `None` if it's one
+    /// that isn't answered.
+    async fn handle_custom_query(
+        account: &Account,
+        identifier: ClientIdentifier,
+        remapper: &mut RegistryRemapper,
+        p: &ClientboundCustomQueryPacket,
+    ) -> Result<Option<ServerboundCustomQueryPacket>, JoinError> {
+        let reply = |query| {
+            Some(ServerboundCustomQueryPacket {
+                transaction_id: p.transaction_id,
+                query,
+            })
+        };
+
+        if let Some(handler) = account.login_query_handlers.get(&p.identifier) {
+            debug!("Handling custom query {:?}", p);
+            let data = handler.handle(p).await.map_err(JoinError::LoginQuery)?;
+            return Ok(reply(data.map(|data| CustomQuery {
+                identifier: None,
+                data: data.into(),
+            })));
+        }
+
+        let channel = p.identifier.to_string();
+        if channel == forwarding::VELOCITY_CHANNEL {
+            return Ok(reply(Self::velocity_query(account, p)));
+        }
+        let query = match (identifier, channel.as_str()) {
+            (ClientIdentifier::Forge, "fml:loginwrapper") => {
+                match Self::fml3_query(remapper, p).await? {
+                    Some(query) => query,
+                    None => return Ok(None),
+                }
+            }
+            (ClientIdentifier::ForgeFml2, "fml:loginwrapper") => {
+                Self::fml2_query(remapper, p).await?
+            }
+            (ClientIdentifier::Fabric, fabric::EARLY_REGISTRATION_CHANNEL) => {
+                Some(Self::fabric_early_registration_query(p)?)
+            }
+            _ => {
+                debug!("Got custom query {:?}", p);
+                None
+            }
+        };
+        Ok(reply(query))
+    }
+
+    /// Answer Velocity's player info request. Servers only ask this if they
+    /// expect a proxy, so we answer it even if we aren't forwarding to say we
+    /// don't understand it.
+    fn velocity_query(account: &Account, p: &ClientboundCustomQueryPacket) -> Option<CustomQuery> {
+        match &account.forwarding {
+            Some(Forwarding {
+                mode: ForwardingMode::Velocity { secret },
+                address,
+            }) => Some(CustomQuery {
+                identifier: None,
+                data: forwarding::write_velocity_response(
+                    secret,
+                    &p.data,
+                    *address,
+                    &account.forwarded_profile(),
+                )
+                .into(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Answer a Forge (FML3) handshake packet, keeping track of any registry
+    /// it syncs. The outer `None` means the packet isn't answered at all, like
+    /// the mod data that Forge sends without waiting for a reply.
+    async fn fml3_query(
+        remapper: &mut RegistryRemapper,
+        p: &ClientboundCustomQueryPacket,
+    ) -> Result<Option<Option<CustomQuery>>, JoinError> {
+        let mut buf = Cursor::new(&p.data[..]);
+        let channel = ResourceLocation::read_from(&mut buf).map_err(JoinError::ForgeChannel)?;
+        let Ok(packet) = ClientboundForgePacket::read_from_buf(&mut buf).await else {
+            debug!("Unable to decode Forge packet {:?}", p.data);
+            return Ok(None);
+        };
+        debug!("Got forge packet {:?}", packet);
+        if let ClientboundForgePacket::Registry(registry) = &packet {
+            if let Some(data) = &registry.data {
+                remapper.add_forge_registry(&registry.registry, data);
+            }
+        }
+        let reply = match packet {
+            ClientboundForgePacket::ModData(_) | ClientboundForgePacket::ChannelMismatchData(_) => {
+                return Ok(None)
+            }
+            ClientboundForgePacket::ModList(m) => ServerboundModListReplyPacket::from(m).get(),
+            _ => ServerboundAcknowledgePacket::new().get(),
+        };
+        Ok(Some(Some(CustomQuery {
+            identifier: Some(channel),
+            data: reply.write_to_vec().await?.into(),
+        })))
+    }
+
+    /// Answer a legacy Forge (FML2) handshake packet, keeping track of any
+    /// registry it syncs.
+    async fn fml2_query(
+        remapper: &mut RegistryRemapper,
+        p: &ClientboundCustomQueryPacket,
+    ) -> Result<Option<CustomQuery>, JoinError> {
+        let mut buf = Cursor::new(&p.data[..]);
+        let channel = ResourceLocation::read_from(&mut buf).map_err(JoinError::ForgeChannel)?;
+        let Ok(packet) = ClientboundFml2Packet::read_from_buf(&mut buf).await else {
+            debug!("Unable to decode FML2 packet {:?}", p.data);
+            return Ok(None);
+        };
+        debug!("Got FML2 packet {:?}", packet);
+        if let ClientboundFml2Packet::Registry(registry) = &packet {
+            if let Some(data) = &registry.data {
+                remapper.add_forge_registry(&registry.registry, data);
+            }
+        }
+        // every FML2 packet has to be answered, and everything but the mod
+        // list is just acknowledged
+        let reply = match packet {
+            ClientboundFml2Packet::ModList(m) => Fml2ModListReplyPacket::from(m).get(),
+            _ => Fml2AcknowledgePacket {}.get(),
+        };
+        Ok(Some(CustomQuery {
+            identifier: Some(channel),
+            data: reply.write_to_vec().await?.into(),
+        }))
+    }
+
+    /// Say we can receive Fabric registry syncs, otherwise servers with modded
+    /// registries kick us.
+    fn fabric_early_registration_query(
+        p: &ClientboundCustomQueryPacket,
+    ) -> Result<CustomQuery, JoinError> {
+        match ClientboundFabricRegistryPacket::read_from(&mut Cursor::new(&p.data[..])) {
+            Ok(packet) => debug!("{packet:?}"),
+            Err(_) => debug!("Unknown packet sent to {}: {p:?}", p.identifier),
+        }
+        let mut data = Vec::new();
+        ServerboundFabricRegistryPacket::registry_sync().write_into(&mut data)?;
+        Ok(CustomQuery {
+            identifier: None,
+            data: data.into(),
+        })
+    }
+
+    /// Write a packet directly to the server. It goes through the
+    /// [middleware](Client::add_middleware) first, so it might be changed or
+    /// not sent at all.
     pub async fn write_packet(&self, packet: ServerboundGamePacket) -> Result<(), std::io::Error> {
-        self.write_conn.lock().await.write(packet).await?;
+        let packets = self.intercept_outbound(packet);
+        let mut write_conn = self.write_conn.lock().await;
+        for packet in packets {
+            write_conn.write(packet).await?;
+        }
         Ok(())
     }
 
+    /// Enable or disable write batching. While it's enabled, the packets
+    /// written in a tick are sent together at the end of the tick instead of
+    /// one at a time, which is much cheaper when there are lots of bots.
+    ///
+    /// Use [`Client::flush`] if a packet has to be sent right away.
+    pub async fn set_write_batching(&self, batching: bool) -> Result<(), std::io::Error> {
+        self.write_conn.lock().await.set_batching(batching).await
+    }
+
+    /// Send the packets that are waiting to be sent because of write
+    /// batching. This does nothing if batching is disabled.
+    pub async fn flush(&self) -> Result<(), std::io::Error> {
+        self.write_conn.lock().await.flush().await
+    }
+
     /// Disconnect this client from the server, ending all tasks.
     pub async fn disconnect(&self) -> Result<(), std::io::Error> {
         if let Err(e) = self.write_conn.lock().await.shutdown().await {
@@ -520,18 +749,54 @@ impl Client {
     }
 
     async fn protocol_loop(client: Client, tx: Sender<Event>) {
+        let mut skipped_ids = HashSet::new();
         loop {
-            let r = client.read_conn.lock().await.read().await;
+            let r = client.read_conn.lock().await.read_raw().await;
+            let r = match r {
+                Ok(data) => {
+                    client.record(&data);
+                    match client.packet_action(&data) {
+                        (_, PacketAction::Skip) => continue,
+                        (Some(kind), PacketAction::Lazy) => {
+                            let packet = RawPacket::new(kind, client.version, data)
+                                .with_remapper(client.remapper.read().clone());
+                            if tx.send(Event::RawPacket(Arc::new(packet))).await.is_err() {
+                                // nobody is listening for events anymore
+                                break;
+                            }
+                            continue;
+                        }
+                        _ => {
+                            let start = Instant::now();
+                            let packet = with_remapper(client.remapper.read().clone(), || {
+                                deserialize_packet::<ClientboundGamePacket>(
+                                    &mut Cursor::new(&data[..]),
+                                    client.version,
+                                )
+                            });
+                            if let Ok(packet) = &packet {
+                                client
+                                    .received_stats
+                                    .lock()
+                                    .record_decode(packet.kind(), start.elapsed());
+                            }
+                            packet
+                        }
+                    }
+                }
+                Err(e) => Err(e),
+            };
             match r {
-                Ok(packet) => match Self::handle(&packet, &client, &tx).await {
-                    Ok(_) => {}
-                    Err(e) => {
-                        error!("Error handling packet: {}", e);
-                        if !IGNORE_ERRORS {
-                            panic!("Error handling packet: {e}");
+                Ok(packet) => {
+                    for packet in client.intercept_inbound(packet) {
+                        if let Err(e) = Self::handle(&packet, &client, &tx).await {
+                            error!("Error handling packet: {}", e);
+                            if !IGNORE_ERRORS {
+                                panic!("Error handling packet: {e}");
+                            }
                         }
                     }
-                },
+                }
                 Err(e) => {
                     let e = *e;
                     if let ReadPacketError::ConnectionClosed = e {
@@ -541,6 +806,19 @@ impl Client {
                         }
                         break;
                     }
+                    // older versions have packets that we can't convert, skip them
+                    if let ReadPacketError::UnknownPacketId { id, .. } = e {
+                        if client.version != ProtocolVersion::LATEST {
+                            // only warn the first time so it doesn't flood the logs
+                            if skipped_ids.insert(id) {
+                                warn!(
+                                    "Skipping packet {id:#04x} since it can't be converted from {}",
+                                    client.version
+                                );
+                            }
+                            continue;
+                        }
+                    }
                     let default_backtrace = Backtrace::capture();
                     if IGNORE_ERRORS {
                         let backtrace =
@@ -560,7 +838,28 @@ impl Client {
         }
     }
 
-    async fn handle(
+    /// Whether the chunk is in the shared world but wasn't sent to this
+    /// client, meaning another client already parsed it.
+    pub(crate) fn is_chunk_known_elsewhere(&self, pos: &ChunkPos) -> bool {
+        let world = self.world.read();
+        let shared_has_chunk = world.get_chunk(pos).is_some();
+        let this_client_has_chunk = world.chunk_storage.limited_get(pos).is_some();
+        shared_has_chunk && !this_client_has_chunk
+    }
+
+    /// Write the packet to the recording if we're recording. If writing fails
+    /// the recording is stopped.
+    fn record(&self, data: &[u8]) {
+        let mut recorder = self.recorder.lock();
+        if let Some(r) = recorder.as_mut() {
+            if let Err(e) = r.record(ConnectionProtocol::Game, data) {
+                error!("Error writing to recording, stopping it: {e}");
+                *recorder = None;
+            }
+        }
+    }
+
+    pub(crate) async fn handle(
         packet: &ClientboundGamePacket,
         client: &Client,
         tx: &Sender<Event>,
@@ -669,17 +968,7 @@ impl Client {
                 );
                 client.write_packet(client_information_packet.get()).await?;
 
-                // brand
-                client
-                    .write_packet(
-                        ServerboundCustomPayloadPacket {
-                            identifier: ResourceLocation::new("brand").unwrap(),
-                            // they don't have to know :)
-                            data: "vanilla".into(),
-                        }
-                        .get(),
-                    )
-                    .await?;
+                client.send_brand().await?;
 
                 tx.send(Event::Login).await?;
             }
@@ -688,6 +977,10 @@ impl Client {
             }
             ClientboundGamePacket::CustomPayload(p) => {
                 debug!("Got custom payload packet {:?}", p);
+                client.handle_plugin_message(p);
+                if let Some(registries) = client.handle_fabric_registry_sync(p) {
+                    tx.send(Event::FabricRegistries(registries)).await?;
+                }
             }
             ClientboundGamePacket::ChangeDifficulty(p) => {
                 debug!("Got difficulty packet {:?}", p);
@@ -700,9 +993,11 @@ impl Client {
             }
             ClientboundGamePacket::SetCarriedItem(p) => {
                 debug!("Got set carried item packet {:?}", p);
+                client.handle_set_carried_item(p);
             }
-            ClientboundGamePacket::UpdateTags(_p) => {
+            ClientboundGamePacket::UpdateTags(p) => {
                 debug!("Got update tags packet");
+                client.handle_update_tags(p);
             }
             ClientboundGamePacket::Disconnect(p) => {
                 debug!("Got disconnect packet {:?}", p);
@@ -876,14 +1171,7 @@ impl Client {
                 // parse it again. This is only used when we have a shared
                 // world, since we check that the chunk isn't currently owned
                 // by this client.
-                let shared_has_chunk = client.world.read().get_chunk(&pos).is_some();
-                let this_client_has_chunk = client
-                    .world
-                    .read()
-                    .chunk_storage
-                    .limited_get(&pos)
-                    .is_some();
-                if shared_has_chunk && !this_client_has_chunk {
+                if client.is_chunk_known_elsewhere(&pos) {
                     trace!(
                         "Skipping parsing chunk {:?} because we already know about it",
                         pos
@@ -893,11 +1181,15 @@ impl Client {
 
                 // let chunk = Chunk::read_with_world_height(&mut p.chunk_data);
                 // debug("chunk {:?}")
-                if let Err(e) = client
-                    .world
-                    .write()
-                    .replace_with_packet_data(&pos, &mut Cursor::new(&p.chunk_data.data))
-                {
+                // the chunk data is only parsed now, so the block states in it
+                // have to be remapped here instead of when the packet was read
+                let remapper = remapper_for_version(client.version, client.remapper.read().clone());
+                if let Err(e) = with_remapper(remapper, || {
+                    client
+                        .world
+                        .write()
+                        .replace_with_packet_data(&pos, &mut Cursor::new(&p.chunk_data.data))
+                }) {
                     error!("Couldn't set chunk data: {}", e);
                 }
             }
@@ -906,8 +1198,11 @@ impl Client {
             }
             ClientboundGamePacket::AddEntity(p) => {
                 debug!("Got add entity packet {:?}", p);
-                let entity = EntityData::from(p);
-                client.world.write().add_entity(p.id, entity);
+                if let Ok(entity) = EntityData::try_from(p) {
+                    client.world.write().add_entity(p.id, entity);
+                } else {
+                    debug!("Not adding modded entity {:?}", p.entity_type);
+                }
             }
             ClientboundGamePacket::SetEntityData(p) => {
                 debug!("Got set entity data packet {:?}", p);
@@ -944,6 +1239,7 @@ impl Client {
             }
             ClientboundGamePacket::ContainerSetContent(p) => {
                 debug!("Got container set content packet {:?}", p);
+                client.handle_container_set_content(p);
             }
             ClientboundGamePacket::SetHealth(p) => {
                 debug!("Got set health packet {:?}", p);
@@ -1017,7 +1313,10 @@ impl Client {
             ClientboundGamePacket::BlockUpdate(p) => {
                 debug!("Got block update packet {:?}", p);
                 let mut world = client.world.write();
-                world.set_block_state(&p.pos, p.block_state);
+                // we don't know anything about modded blocks, so they're
+                // treated like unknown block states in chunks
+                let state = p.block_state.vanilla().copied().unwrap_or(BlockState::Air);
+                world.set_block_state(&p.pos, state);
             }
             ClientboundGamePacket::Animate(p) => {
                 debug!("Got animate packet {:?}", p);
@@ -1026,7 +1325,10 @@ impl Client {
                 debug!("Got section blocks update packet {:?}", p);
                 let mut world = client.world.write();
                 for state in &p.states {
-                    world.set_block_state(&(p.section_pos + state.pos.clone()), state.state);
+                    world.set_block_state(
+                        &(p.section_pos + state.pos.clone()),
+                        state.state.vanilla().copied().unwrap_or(BlockState::Air),
+                    );
                 }
             }
             ClientboundGamePacket::GameEvent(p) => {
@@ -1043,17 +1345,18 @@ impl Client {
             }
             ClientboundGamePacket::UpdateMobEffect(p) => {
                 debug!("Got update mob effect packet {:?}", p);
+                client.handle_update_mob_effect(p);
             }
             ClientboundGamePacket::AddExperienceOrb(_) => {}
             ClientboundGamePacket::AwardStats(_) => {}
-            ClientboundGamePacket::BlockChangedAck(_) => {}
+            ClientboundGamePacket::BlockChangedAck(p) => client.handle_block_changed_ack(p),
             ClientboundGamePacket::BlockDestruction(_) => {}
             ClientboundGamePacket::BlockEntityData(_) => {}
             ClientboundGamePacket::BlockEvent(_) => {}
             ClientboundGamePacket::BossEvent(_) => {}
             ClientboundGamePacket::CommandSuggestions(_) => {}
-            ClientboundGamePacket::ContainerSetData(_) => {}
-            ClientboundGamePacket::ContainerSetSlot(_) => {}
+            ClientboundGamePacket::ContainerSetData(p) => client.handle_container_set_data(p),
+            ClientboundGamePacket::ContainerSetSlot(p) => client.handle_container_set_slot(p),
             ClientboundGamePacket::Cooldown(_) => {}
             ClientboundGamePacket::CustomChatCompletions(_) => {}
             ClientboundGamePacket::DeleteChat(_) => {}
@@ -1064,7 +1367,10 @@ impl Client {
             ClientboundGamePacket::MerchantOffers(_) => {}
             ClientboundGamePacket::MoveVehicle(_) => {}
             ClientboundGamePacket::OpenBook(_) => {}
-            ClientboundGamePacket::OpenScreen(_) => {}
+            ClientboundGamePacket::OpenScreen(p) => {
+                debug!("Got open screen packet {:?}", p);
+                client.handle_open_screen(p);
+            }
             ClientboundGamePacket::OpenSignEditor(_) => {}
             ClientboundGamePacket::Ping(_) => {}
             ClientboundGamePacket::PlaceGhostRecipe(_) => {}
@@ -1082,13 +1388,15 @@ impl Client {
                 }
             }
             ClientboundGamePacket::PlayerLookAt(_) => {}
-            ClientboundGamePacket::RemoveMobEffect(_) => {}
+            ClientboundGamePacket::RemoveMobEffect(p) => client.handle_remove_mob_effect(p),
             ClientboundGamePacket::ResourcePack(_) => {}
             ClientboundGamePacket::Respawn(p) => {
                 debug!("Got respawn packet {:?}", p);
                 // Sets clients dead state to false.
                 let mut dead_lock = client.dead.lock();
                 *dead_lock = false;
+                // respawning gives us a new player with no effects
+                client.effects.write().clear();
             }
             ClientboundGamePacket::SelectAdvancementsTab(_) => {}
             ClientboundGamePacket::SetActionBarText(_) => {}
@@ -1114,7 +1422,7 @@ impl Client {
             ClientboundGamePacket::TakeItemEntity(_) => {}
             ClientboundGamePacket::DisguisedChat(_) => {}
             ClientboundGamePacket::UpdateEnabledFeatures(_) => {}
-            ClientboundGamePacket::ContainerClose(_) => {}
+            ClientboundGamePacket::ContainerClose(p) => client.handle_container_close(p),
         }
 
         Ok(())
@@ -1127,12 +1435,19 @@ impl Client {
         game_tick_interval.set_missed_tick_behavior(time::MissedTickBehavior::Burst);
         loop {
             game_tick_interval.tick().await;
+            if let Some(recorder) = client.recorder.lock().as_mut() {
+                recorder.tick();
+            }
             Self::game_tick(&mut client, &tx).await;
+            // send everything that was batched during the tick
+            if let Err(e) = client.flush().await {
+                warn!("Error flushing packets: {e}");
+            }
         }
     }
 
     /// Runs every 50 milliseconds.
-    async fn game_tick(client: &mut Client, tx: &Sender<Event>) {
+    pub(crate) async fn game_tick(client: &mut Client, tx: &Sender<Event>) {
         // return if there's no chunk at the player's position
 
         {
@@ -1243,3 +1558,373 @@ impl<T> From<std::sync::PoisonError<T>> for HandleError {
         HandleError::Poison(e.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_buf::remap::{ModdedEntry, RemappedId};
+    use azalea_protocol::{
+        forwarding::ForwardingError,
+        packets::{
+            forge::{
+                clientbound_registry_packet::ForgeRegistryData,
+                fml2::{
+                    clientbound_config_data_packet::ClientboundConfigDataPacket,
+                    clientbound_mod_list_packet::ClientboundModListPacket,
+                    clientbound_registry_packet::ClientboundRegistryPacket, ServerboundFml2Packet,
+                },
+            },
+            handshake::ServerboundHandshakePacket as ServerHandshakePacket,
+            login::{
+                clientbound_custom_query_packet::ClientboundCustomQueryPacket,
+                clientbound_game_profile_packet::ClientboundGameProfilePacket,
+                ServerboundLoginPacket,
+            },
+        },
+        server::{self, LoginOptions},
+    };
+
+    #[tokio::test]
+    async fn test_handshake_in_memory() {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+
+        let server = tokio::spawn(async move {
+            let mut conn: Connection<ServerHandshakePacket, ClientboundHandshakePacket> =
+                Connection::from_stream(server_stream);
+            let ServerHandshakePacket::ClientIntention(intent) = conn.read().await.unwrap();
+            assert_eq!(intent.intention, ConnectionProtocol::Login);
+            let options = LoginOptions {
+                online_mode: false,
+                ..Default::default()
+            };
+            let (_conn, profile, _) =
+                server::login(Connection::from(conn), &options, None, None, None)
+                    .await
+                    .unwrap();
+            profile
+        });
+
+        let address = ServerAddress::try_from("localhost").unwrap();
+        let (_conn, profile) = Client::handshake(
+            Connection::from_stream(client_stream),
+            ClientIdentifier::Vanilla,
+            ProtocolVersion::LATEST,
+            &Account::offline("bot"),
+            &address,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(profile.name, "bot");
+        assert_eq!(server.await.unwrap().uuid, profile.uuid);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_malformed_forge_channel() {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+
+        tokio::spawn(async move {
+            let mut conn: Connection<ServerHandshakePacket, ClientboundHandshakePacket> =
+                Connection::from_stream(server_stream);
+            conn.read().await.unwrap();
+            let mut conn: Connection<ServerboundLoginPacket, ClientboundLoginPacket> =
+                Connection::from(conn);
+            conn.read().await.unwrap();
+            // a string length that's longer than the rest of the data
+            conn.write(
+                ClientboundCustomQueryPacket {
+                    transaction_id: 0,
+                    identifier: ResourceLocation::new("fml:loginwrapper").unwrap(),
+                    data: vec![0x7f].into(),
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+            // keep the connection open until the client gives up
+            let _ = conn.read().await;
+        });
+
+        let address = ServerAddress::try_from("localhost").unwrap();
+        let result = Client::handshake(
+            Connection::from_stream(client_stream),
+            ClientIdentifier::Forge,
+            ProtocolVersion::LATEST,
+            &Account::offline("bot"),
+            &address,
+        )
+        .await;
+
+        assert!(matches!(result, Err(JoinError::ForgeChannel(_))));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_fml2() {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+
+        let server = tokio::spawn(async move {
+            let mut conn: Connection<ServerHandshakePacket, ClientboundHandshakePacket> =
+                Connection::from_stream(server_stream);
+            let ServerHandshakePacket::ClientIntention(intent) = conn.read().await.unwrap();
+            assert_eq!(intent.identifier, ClientIdentifier::ForgeFml2);
+            let mut conn: Connection<ServerboundLoginPacket, ClientboundLoginPacket> =
+                Connection::from(conn);
+            let ServerboundLoginPacket::Hello(hello) = conn.read().await.unwrap() else {
+                panic!("Expected hello");
+            };
+
+            let channel = ResourceLocation::new("fml:handshake").unwrap();
+            let queries = [
+                ClientboundModListPacket {
+                    mods: vec!["forge".to_string()],
+                    channels: HashMap::new(),
+                    registries: vec![ResourceLocation::new("minecraft:item").unwrap()],
+                }
+                .get(),
+                ClientboundRegistryPacket {
+                    registry: ResourceLocation::new("minecraft:item").unwrap(),
+                    data: Some(ForgeRegistryData {
+                        ids: HashMap::from([
+                            (ResourceLocation::new("minecraft:stone").unwrap(), 1),
+                            (ResourceLocation::new("examplemod:ruby").unwrap(), 5000),
+                        ]),
+                        aliases: HashMap::new(),
+                        overrides: HashMap::new(),
+                        blocked: Vec::new(),
+                        dummied: Vec::new(),
+                    }),
+                }
+                .get(),
+                ClientboundConfigDataPacket {
+                    filename: "forge-server.toml".to_string(),
+                    filedata: Vec::new(),
+                }
+                .get(),
+            ];
+            for (transaction_id, packet) in queries.into_iter().enumerate() {
+                let mut data = Vec::new();
+                channel.write_into(&mut data).unwrap();
+                data.extend(packet.write_to_vec().await.unwrap());
+                conn.write(
+                    ClientboundCustomQueryPacket {
+                        transaction_id: transaction_id as u32,
+                        identifier: ResourceLocation::new("fml:loginwrapper").unwrap(),
+                        data: data.into(),
+                    }
+                    .get(),
+                )
+                .await
+                .unwrap();
+
+                let ServerboundLoginPacket::CustomQuery(reply) = conn.read().await.unwrap() else {
+                    panic!("Expected a custom query reply");
+                };
+                assert_eq!(reply.transaction_id, transaction_id as u32);
+                let query = reply.query.expect("Every FML2 packet should be answered");
+                assert_eq!(query.identifier, Some(channel.clone()));
+                let reply = ServerboundFml2Packet::read_from_buf(&mut &query.data[..])
+                    .await
+                    .unwrap();
+                // the mod list is answered with ours, and the rest are acked
+                match (transaction_id, reply) {
+                    (0, ServerboundFml2Packet::ModListReply(reply)) => {
+                        assert_eq!(reply.mods, ["forge"]);
+                    }
+                    (1 | 2, ServerboundFml2Packet::Acknowledge(_)) => {}
+                    (_, reply) => panic!("Unexpected reply {reply:?}"),
+                }
+            }
+
+            conn.write(
+                ClientboundGameProfilePacket {
+                    game_profile: GameProfile::new(Uuid::nil(), hello.name),
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let address = ServerAddress::try_from("localhost").unwrap();
+        let (conn, profile) = Client::handshake(
+            Connection::from_stream(client_stream),
+            ClientIdentifier::ForgeFml2,
+            ProtocolVersion::LATEST,
+            &Account::offline("bot"),
+            &address,
+        )
+        .await
+        .unwrap();
+        server.await.unwrap();
+
+        assert_eq!(profile.name, "bot");
+        // the ids from the registry packet are used for the rest of the
+        // connection
+        let remapper = conn
+            .reader
+            .remapper
+            .read()
+            .clone()
+            .expect("The client should remap ids");
+        assert_eq!(
+            remapper.remap("minecraft:item", 1),
+            RemappedId::Vanilla(azalea_registry::Item::Stone as u32)
+        );
+        assert_eq!(
+            remapper.remap("minecraft:item", 5000),
+            RemappedId::Modded(ModdedEntry {
+                id: 5000,
+                name: Some("examplemod:ruby".to_string())
+            })
+        );
+    }
+
+    /// Log in to a server that expects forwarding, returning the profile and
+    /// forwarded address that the server got.
+    async fn login_forwarded(
+        server_mode: ForwardingMode,
+        account: Account,
+    ) -> Result<(GameProfile, Option<std::net::IpAddr>), server::ServerError> {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+
+        let server = tokio::spawn(async move {
+            let mut conn: Connection<ServerHandshakePacket, ClientboundHandshakePacket> =
+                Connection::from_stream(server_stream);
+            let ServerHandshakePacket::ClientIntention(intent) = conn.read().await.unwrap();
+            let options = LoginOptions {
+                online_mode: false,
+                forwarding: Some(server_mode),
+                ..Default::default()
+            };
+            server::login(
+                Connection::from(conn),
+                &options,
+                None,
+                None,
+                intent.forwarding.as_ref(),
+            )
+            .await
+            .map(|(_conn, profile, address)| (profile, address))
+        });
+
+        let address = ServerAddress::try_from("localhost").unwrap();
+        // this fails if the server rejects us, which the caller checks for
+        let _ = Client::handshake(
+            Connection::from_stream(client_stream),
+            ClientIdentifier::Vanilla,
+            ProtocolVersion::LATEST,
+            &account,
+            &address,
+        )
+        .await;
+        server.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_join_with_proxy_option() {
+        use tokio::{
+            io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+            net::TcpListener,
+        };
+
+        // an HTTP proxy that's also the server
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Proxy::new(
+            azalea_protocol::proxy::ProxyKind::Http,
+            listener.local_addr().unwrap().into(),
+        );
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            while !request.ends_with("\r\n\r\n") {
+                reader.read_line(&mut request).await.unwrap();
+            }
+            assert!(request.starts_with("CONNECT 10.0.0.1:25565 HTTP/1.1\r\n"));
+            let mut stream = reader.into_inner();
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut conn: Connection<ServerHandshakePacket, ClientboundHandshakePacket> =
+                Connection::from_stream(stream);
+            conn.read().await.unwrap();
+            let options = LoginOptions {
+                online_mode: false,
+                ..Default::default()
+            };
+            let (conn, profile, _) =
+                server::login(Connection::from(conn), &options, None, None, None)
+                    .await
+                    .unwrap();
+            (conn, profile)
+        });
+
+        // the account doesn't have a proxy, so the one in the options is used
+        let (client, _rx) = Client::join(
+            &Account::offline("bot"),
+            "10.0.0.1",
+            JoinOptions {
+                proxy: Some(proxy),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let (_conn, profile) = server.await.unwrap();
+        assert_eq!(client.profile.name, "bot");
+        assert_eq!(profile.uuid, client.profile.uuid);
+        client.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_handshake_with_forwarding() {
+        let address = "10.1.2.3".parse().unwrap();
+
+        let account = Account::offline("bot").with_forwarding(Forwarding::bungeecord(address));
+        let (profile, forwarded) = login_forwarded(ForwardingMode::BungeeCord, account.clone())
+            .await
+            .unwrap();
+        assert_eq!(profile, account.forwarded_profile());
+        assert_eq!(forwarded, Some(address));
+
+        let account =
+            Account::offline("bot").with_forwarding(Forwarding::velocity("secret", address));
+        let secret = ForwardingMode::Velocity {
+            secret: b"secret".to_vec(),
+        };
+        let (profile, forwarded) = login_forwarded(secret, account).await.unwrap();
+        assert_eq!(profile.name, "bot");
+        assert_eq!(forwarded, Some(address));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_with_wrong_forwarding() {
+        let address = "10.1.2.3".parse().unwrap();
+        let wrong_secret = ForwardingMode::Velocity {
+            secret: b"other".to_vec(),
+        };
+
+        let account =
+            Account::offline("bot").with_forwarding(Forwarding::velocity("secret", address));
+        assert!(matches!(
+            login_forwarded(wrong_secret.clone(), account).await,
+            Err(server::ServerError::Forwarding(
+                ForwardingError::InvalidSignature
+            ))
+        ));
+        assert!(matches!(
+            login_forwarded(wrong_secret, Account::offline("bot")).await,
+            Err(server::ServerError::Forwarding(
+                ForwardingError::NotForwarded
+            ))
+        ));
+        assert!(matches!(
+            login_forwarded(ForwardingMode::BungeeCord, Account::offline("bot")).await,
+            Err(server::ServerError::Forwarding(
+                ForwardingError::NotForwarded
+            ))
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+//! Keep track of the potion effects the player has.
+
+use crate::Client;
+use azalea_protocol::packets::game::{
+    clientbound_remove_mob_effect_packet::ClientboundRemoveMobEffectPacket,
+    clientbound_update_mob_effect_packet::ClientboundUpdateMobEffectPacket,
+};
+use azalea_registry::MobEffect;
+use std::collections::HashMap;
+
+impl Client {
+    /// The effects the player has and their amplifiers, which are their
+    /// levels minus one.
+    pub fn effects(&self) -> HashMap<MobEffect, u8> {
+        self.effects.read().clone()
+    }
+
+    /// The amplifier of an effect the player has, or `None` if they don't
+    /// have it.
+    pub fn effect_amplifier(&self, effect: MobEffect) -> Option<u8> {
+        self.effects.read().get(&effect).copied()
+    }
+
+    pub(crate) fn handle_update_mob_effect(&self, packet: &ClientboundUpdateMobEffectPacket) {
+        if packet.entity_id != *self.entity_id.read() {
+            return;
+        }
+        self.effects
+            .write()
+            .insert(packet.effect, packet.effect_amplifier);
+    }
+
+    pub(crate) fn handle_remove_mob_effect(&self, packet: &ClientboundRemoveMobEffectPacket) {
+        if packet.entity_id != *self.entity_id.read() {
+            return;
+        }
+        self.effects.write().remove(&packet.effect);
+    }
+}
@@ -0,0 +1,112 @@
+//! Keep track of the registries that Fabric servers sync.
+
+use crate::Client;
+use azalea_protocol::{
+    packets::{
+        fabric::{self, FabricRegistries},
+        game::clientbound_custom_payload_packet::ClientboundCustomPayloadPacket,
+    },
+    remap::RegistryRemapper,
+};
+use log::{debug, warn};
+use std::sync::Arc;
+
+impl Client {
+    /// The raw ids of registry entries that the server synced, if it's a
+    /// Fabric server that did that. Entries added by mods have these ids
+    /// instead of the ones in `azalea-registry`.
+    pub fn fabric_registries(&self) -> Option<Arc<FabricRegistries>> {
+        self.fabric_registries.read().clone()
+    }
+
+    /// Handle a custom payload if it's part of a Fabric registry sync,
+    /// returning the registries if the sync is finished.
+    pub(crate) fn handle_fabric_registry_sync(
+        &self,
+        packet: &ClientboundCustomPayloadPacket,
+    ) -> Option<Arc<FabricRegistries>> {
+        let result = match packet.identifier.to_string().as_str() {
+            fabric::REGISTRY_SYNC_DIRECT_CHANNEL => {
+                self.fabric_registry_sync.lock().push(&packet.data)?
+            }
+            fabric::REGISTRY_SYNC_NBT_CHANNEL => FabricRegistries::read_nbt(&packet.data),
+            _ => return None,
+        };
+        match result {
+            Ok(registries) => {
+                debug!("Got {} Fabric registries", registries.registries().len());
+                // the packets after the sync use the synced ids
+                let mut remapper = RegistryRemapper::default();
+                remapper.add_fabric_registries(&registries);
+                if !remapper.is_empty() {
+                    *self.remapper.write() = Some(Arc::new(remapper));
+                }
+                let registries = Arc::new(registries);
+                *self.fabric_registries.write() = Some(registries.clone());
+                Some(registries)
+            }
+            Err(e) => {
+                warn!("Couldn't read Fabric registry sync: {e}");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::client_and_server;
+    use azalea_buf::{
+        remap::{with_remapper, MaybeModded, ModdedEntry},
+        McBufReadable, McBufVarWritable,
+    };
+    use azalea_core::{ResourceLocation, SlotData};
+    use std::{collections::HashMap, io::Cursor};
+
+    #[tokio::test]
+    async fn test_direct_sync() {
+        let (client, _server) = client_and_server();
+
+        let item = ResourceLocation::new("minecraft:item").unwrap();
+        let ruby = ResourceLocation::new("examplemod:ruby").unwrap();
+        let registries = FabricRegistries::from(HashMap::from([(
+            item.clone(),
+            HashMap::from([(ruby.clone(), 1200)]),
+        )]));
+        let mut data = Vec::new();
+        registries.write_direct(&mut data).unwrap();
+
+        let payload = |data: &[u8]| ClientboundCustomPayloadPacket {
+            identifier: ResourceLocation::new(fabric::REGISTRY_SYNC_DIRECT_CHANNEL).unwrap(),
+            data: data.to_vec().into(),
+        };
+        assert!(client
+            .handle_fabric_registry_sync(&payload(&data))
+            .is_none());
+        assert!(client.fabric_registries().is_none());
+        let synced = client.handle_fabric_registry_sync(&payload(&[])).unwrap();
+        assert_eq!(synced.id(&item, &ruby), Some(1200));
+        assert_eq!(
+            client.fabric_registries().unwrap().id(&item, &ruby),
+            Some(1200)
+        );
+
+        // the connection reads the synced ids now
+        let mut data = Vec::new();
+        1200u32.var_write_into(&mut data).unwrap();
+        // one of the item with no nbt
+        data.extend([1, 0]);
+        let remapper = client.read_conn.lock().await.remapper.read().clone();
+        let slot = with_remapper(remapper, || {
+            SlotData::read_from(&mut Cursor::new(&data[..])).unwrap()
+        });
+        assert_eq!(
+            slot.item,
+            MaybeModded::Modded(ModdedEntry {
+                id: 1200,
+                name: Some("examplemod:ruby".to_string())
+            })
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Choose which game packets a client bothers decoding.
+//!
+//! Decoding every packet is wasteful when you have lots of bots and most of
+//! them don't care about most packets. A [`PacketFilter`] lets a client look
+//! at a packet's id and either decode it like normal, skip it entirely, or
+//! hand it to you as a [`RawPacket`] that's only decoded if you ask for it.
+
+use crate::Client;
+use azalea_buf::{
+    remap::{with_remapper, IdRemapper},
+    McBufVarReadable,
+};
+use azalea_core::ChunkPos;
+use azalea_protocol::{
+    packets::{
+        game::{ClientboundGamePacket, ClientboundGamePacketKind},
+        ProtocolPacket, ProtocolVersion,
+    },
+    read::{deserialize_packet, ReadPacketError},
+};
+use once_cell::sync::OnceCell;
+use std::{collections::HashSet, fmt::Debug, io::Cursor, sync::Arc};
+
+/// Packets the client needs to stay connected and keep track of itself, so
+/// they're always decoded and handled no matter what the filter says.
+pub const REQUIRED_PACKETS: &[ClientboundGamePacketKind] = &[
+    ClientboundGamePacketKind::KeepAlive,
+    ClientboundGamePacketKind::Login,
+    ClientboundGamePacketKind::Disconnect,
+    ClientboundGamePacketKind::PlayerPosition,
+    ClientboundGamePacketKind::Respawn,
+];
+
+/// What the client should do with a packet it received.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketAction {
+    /// Decode the packet, handle it, and send
+    /// [`Event::Packet`](crate::Event::Packet).
+    Decode,
+    /// Don't decode or handle the packet, but send it as
+    /// [`Event::RawPacket`](crate::Event::RawPacket).
+    Lazy,
+    /// Ignore the packet.
+    Skip,
+}
+
+/// Decides which packets a client decodes. By default everything is decoded.
+///
+/// Packets that are skipped or lazy aren't handled by the client, so for
+/// example skipping `SetEntityData` means the entities in the world won't
+/// have up-to-date metadata. The packets in [`REQUIRED_PACKETS`] are always
+/// decoded.
+///
+/// ```
+/// # use azalea_client::filter::PacketFilter;
+/// # use azalea_protocol::packets::game::ClientboundGamePacketKind;
+/// let filter = PacketFilter::default()
+///     .skip(ClientboundGamePacketKind::LevelParticles)
+///     .lazy(ClientboundGamePacketKind::SetEntityData)
+///     .skip_known_chunks(true);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PacketFilter {
+    skip: HashSet<ClientboundGamePacketKind>,
+    lazy: HashSet<ClientboundGamePacketKind>,
+    skip_known_chunks: bool,
+}
+
+impl PacketFilter {
+    /// Don't decode or handle this packet at all.
+    pub fn skip(mut self, kind: ClientboundGamePacketKind) -> Self {
+        self.lazy.remove(&kind);
+        self.skip.insert(kind);
+        self
+    }
+
+    /// Send this packet as an [`Event::RawPacket`](crate::Event::RawPacket)
+    /// instead of decoding it.
+    pub fn lazy(mut self, kind: ClientboundGamePacketKind) -> Self {
+        self.skip.remove(&kind);
+        self.lazy.insert(kind);
+        self
+    }
+
+    /// Don't decode chunks that are already in the shared world because
+    /// another client received them. This is what you want for swarms, where
+    /// every bot would otherwise decode the same chunks.
+    ///
+    /// Skipped chunks don't send [`Event::Packet`](crate::Event::Packet).
+    pub fn skip_known_chunks(mut self, skip_known_chunks: bool) -> Self {
+        self.skip_known_chunks = skip_known_chunks;
+        self
+    }
+
+    /// Whether chunks that are already in the shared world are skipped.
+    pub fn skips_known_chunks(&self) -> bool {
+        self.skip_known_chunks
+    }
+
+    /// What should be done with a packet of the given kind.
+    pub fn action(&self, kind: ClientboundGamePacketKind) -> PacketAction {
+        if REQUIRED_PACKETS.contains(&kind) {
+            PacketAction::Decode
+        } else if self.skip.contains(&kind) {
+            PacketAction::Skip
+        } else if self.lazy.contains(&kind) {
+            PacketAction::Lazy
+        } else {
+            PacketAction::Decode
+        }
+    }
+}
+
+/// A packet that hasn't been decoded yet. It's decoded the first time
+/// [`RawPacket::decode`] is called, and the result is reused after that.
+pub struct RawPacket {
+    pub kind: ClientboundGamePacketKind,
+    /// The protocol version the packet was sent with.
+    pub version: ProtocolVersion,
+    /// The packet id and data.
+    pub data: Vec<u8>,
+    /// The remapper of the connection the packet came from.
+    remapper: Option<Arc<dyn IdRemapper>>,
+    decoded: OnceCell<Arc<ClientboundGamePacket>>,
+}
+
+impl RawPacket {
+    pub fn new(kind: ClientboundGamePacketKind, version: ProtocolVersion, data: Vec<u8>) -> Self {
+        Self {
+            kind,
+            version,
+            data,
+            remapper: None,
+            decoded: OnceCell::new(),
+        }
+    }
+
+    /// Decode the packet with the given remapper. See
+    /// [`ReadConnection::remapper`](azalea_protocol::connect::ReadConnection::remapper).
+    pub fn with_remapper(mut self, remapper: Option<Arc<dyn IdRemapper>>) -> Self {
+        self.remapper = remapper;
+        self
+    }
+
+    /// Decode the packet, or get it if it was already decoded.
+    pub fn decode(&self) -> Result<Arc<ClientboundGamePacket>, Box<ReadPacketError>> {
+        self.decoded
+            .get_or_try_init(|| {
+                with_remapper(self.remapper.clone(), || {
+                    deserialize_packet(&mut Cursor::new(&self.data[..]), self.version)
+                })
+                .map(Arc::new)
+            })
+            .cloned()
+    }
+}
+
+impl Debug for RawPacket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawPacket")
+            .field("kind", &self.kind)
+            .field("version", &self.version)
+            .field("data", &self.data)
+            .field("remapped", &self.remapper.is_some())
+            .field("decoded", &self.decoded)
+            .finish()
+    }
+}
+
+/// Read the position of a `LevelChunkWithLight` packet without decoding the
+/// rest of it. The data starts with the packet id.
+pub(crate) fn chunk_packet_pos(data: &[u8]) -> Option<(i32, i32)> {
+    let mut buf = Cursor::new(data);
+    u32::var_read_from(&mut buf).ok()?;
+    let start = buf.position() as usize;
+    let x = data.get(start..start + 4)?;
+    let z = data.get(start + 4..start + 8)?;
+    Some((
+        i32::from_be_bytes(x.try_into().ok()?),
+        i32::from_be_bytes(z.try_into().ok()?),
+    ))
+}
+
+impl Client {
+    /// Change which packets this client decodes. See [`PacketFilter`].
+    pub fn set_packet_filter(&self, filter: PacketFilter) {
+        *self.packet_filter.write() = filter;
+    }
+
+    /// Decide what to do with the packet in `data` based on our
+    /// [`PacketFilter`]. Unknown packets are always decoded so the error is
+    /// reported like normal.
+    pub(crate) fn packet_action(
+        &self,
+        data: &[u8],
+    ) -> (Option<ClientboundGamePacketKind>, PacketAction) {
+        let mut buf = Cursor::new(data);
+        let Ok(id) = u32::var_read_from(&mut buf) else {
+            return (None, PacketAction::Decode);
+        };
+        let Some(kind) = ClientboundGamePacket::kind_for_id(self.version, id) else {
+            return (None, PacketAction::Decode);
+        };
+
+        let filter = self.packet_filter.read();
+        if kind == ClientboundGamePacketKind::LevelChunkWithLight && filter.skip_known_chunks {
+            if let Some((x, z)) = chunk_packet_pos(data) {
+                if self.is_chunk_known_elsewhere(&ChunkPos::new(x, z)) {
+                    return (Some(kind), PacketAction::Skip);
+                }
+            }
+        }
+        (Some(kind), filter.action(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_buf::McBufVarWritable;
+    use azalea_protocol::packets::game::clientbound_set_time_packet::ClientboundSetTimePacket;
+
+    #[test]
+    fn test_action() {
+        let filter = PacketFilter::default()
+            .skip(ClientboundGamePacketKind::SetTime)
+            .lazy(ClientboundGamePacketKind::SetEntityData)
+            .skip(ClientboundGamePacketKind::KeepAlive);
+        assert_eq!(
+            filter.action(ClientboundGamePacketKind::SetTime),
+            PacketAction::Skip
+        );
+        assert_eq!(
+            filter.action(ClientboundGamePacketKind::SetEntityData),
+            PacketAction::Lazy
+        );
+        assert_eq!(
+            filter.action(ClientboundGamePacketKind::AddEntity),
+            PacketAction::Decode
+        );
+        // required packets can't be skipped
+        assert_eq!(
+            filter.action(ClientboundGamePacketKind::KeepAlive),
+            PacketAction::Decode
+        );
+    }
+
+    #[test]
+    fn test_raw_packet() {
+        let packet = ClientboundSetTimePacket {
+            game_time: 1,
+            day_time: 2,
+        }
+        .get();
+        let mut data = Vec::new();
+        packet.id().var_write_into(&mut data).unwrap();
+        packet.write(&mut data).unwrap();
+
+        let raw = RawPacket::new(packet.kind(), ProtocolVersion::LATEST, data);
+        let decoded = raw.decode().unwrap();
+        assert!(matches!(*decoded, ClientboundGamePacket::SetTime(ref p) if p.day_time == 2));
+        assert!(Arc::ptr_eq(&decoded, &raw.decode().unwrap()));
+    }
+
+    #[test]
+    fn test_chunk_packet_pos() {
+        let mut data = Vec::new();
+        0x21u32.var_write_into(&mut data).unwrap();
+        data.extend(5i32.to_be_bytes());
+        data.extend((-3i32).to_be_bytes());
+        data.extend([1, 2, 3]);
+        assert_eq!(chunk_packet_pos(&data), Some((5, -3)));
+        assert_eq!(chunk_packet_pos(&data[..4]), None);
+    }
+}
@@ -0,0 +1,311 @@
+//! Right click blocks to use them or place blocks against them.
+
+use crate::Client;
+use azalea_core::{BlockPos, Direction, Slot, Vec3};
+use azalea_inventory::{item::is_block_item_of, MenuLayout};
+use azalea_protocol::packets::game::{
+    serverbound_interact_packet::InteractionHand,
+    serverbound_swing_packet::ServerboundSwingPacket,
+    serverbound_use_item_on_packet::{BlockHitResult, ServerboundUseItemOnPacket},
+};
+use azalea_world::WeakWorld;
+use std::io;
+use thiserror::Error;
+
+/// How far away a survival player can reach blocks, from their eyes.
+pub const BLOCK_REACH: f64 = 4.5;
+
+#[derive(Error, Debug)]
+pub enum InteractError {
+    #[error("The block isn't in a loaded chunk")]
+    NotLoaded,
+    #[error("The block is too far away to reach")]
+    OutOfReach,
+    #[error("None of the block's faces can be seen")]
+    NoVisibleFace,
+    #[error("There's already a block there")]
+    Occupied,
+    #[error("There's no block next to it to place against")]
+    NothingToPlaceAgainst,
+    #[error("There's no block in either hand to place")]
+    NoItem,
+    #[error("The server didn't place the block")]
+    Rejected,
+    #[error("The server didn't acknowledge the interaction in time")]
+    Timeout,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The middle of a face of a block.
+fn face_center(pos: &BlockPos, face: Direction) -> Vec3 {
+    let center = pos.center();
+    let normal = face.normal();
+    Vec3 {
+        x: center.x + normal.x as f64 * 0.5,
+        y: center.y + normal.y as f64 * 0.5,
+        z: center.z + normal.z as f64 * 0.5,
+    }
+}
+
+/// Whether a face of the block at `pos` points towards `eye` and isn't
+/// covered by the block next to it.
+fn is_face_visible(world: &WeakWorld, pos: &BlockPos, face: Direction, eye: &Vec3) -> bool {
+    let normal = face.normal();
+    let delta = eye - &face_center(pos, face);
+    let facing = delta.x * normal.x as f64 + delta.y * normal.y as f64 + delta.z * normal.z as f64;
+    if facing <= 0. {
+        return false;
+    }
+    world
+        .get_block_state(&(pos + &normal))
+        .map_or(true, |neighbor| neighbor.is_replaceable())
+}
+
+/// The distance from `eye` to the middle of a face of a block.
+fn face_distance(pos: &BlockPos, face: Direction, eye: &Vec3) -> f64 {
+    (eye - &face_center(pos, face)).length_sqr().sqrt()
+}
+
+impl Client {
+    /// Right click a block with the item in `hand`, like to open a door or
+    /// till dirt with a hoe. We click the middle of the visible face that's
+    /// closest to the player.
+    ///
+    /// This finishes when the server acknowledges the click, but that
+    /// doesn't mean it did anything.
+    pub async fn use_block(
+        &self,
+        pos: &BlockPos,
+        hand: InteractionHand,
+    ) -> Result<(), InteractError> {
+        let world = self.world();
+        world.get_block_state(pos).ok_or(InteractError::NotLoaded)?;
+        let eye = self.eye_position();
+        let face = Direction::iter()
+            .filter(|&face| is_face_visible(&world, pos, face, &eye))
+            .min_by(|&a, &b| face_distance(pos, a, &eye).total_cmp(&face_distance(pos, b, &eye)))
+            .ok_or(InteractError::NoVisibleFace)?;
+        self.use_item_on(
+            hand,
+            BlockHitResult {
+                block_pos: *pos,
+                direction: face,
+                location: face_center(pos, face),
+                inside: BlockPos::from(&eye) == *pos,
+            },
+        )
+        .await
+    }
+
+    /// Place the block in `hand` against a face of the block at `against`,
+    /// so it ends up next to that face. Fails with
+    /// [`InteractError::Rejected`] if the block wasn't placed.
+    pub async fn place_block_against(
+        &self,
+        against: &BlockPos,
+        face: Direction,
+        hand: InteractionHand,
+    ) -> Result<(), InteractError> {
+        let world = self.world();
+        world
+            .get_block_state(against)
+            .ok_or(InteractError::NotLoaded)?;
+        let target = against + &face.normal();
+        let old_state = world
+            .get_block_state(&target)
+            .ok_or(InteractError::NotLoaded)?;
+        if !old_state.is_replaceable() {
+            return Err(InteractError::Occupied);
+        }
+        let eye = self.eye_position();
+        self.use_item_on(
+            hand,
+            BlockHitResult {
+                block_pos: *against,
+                direction: face,
+                location: face_center(against, face),
+                inside: BlockPos::from(&eye) == *against,
+            },
+        )
+        .await?;
+        // the server sends the new block before it acknowledges the click
+        if world.get_block_state(&target) == Some(old_state) {
+            return Err(InteractError::Rejected);
+        }
+        Ok(())
+    }
+
+    /// Place the block the player is holding at `pos`, against whichever
+    /// neighboring block has the closest visible face. The main hand is used
+    /// if it has a block, and the off hand otherwise.
+    pub async fn place_block(&self, pos: &BlockPos) -> Result<(), InteractError> {
+        let is_block = |slot: Slot| matches!(slot, Slot::Present(item) if is_block_item_of(&item));
+        let offhand = MenuLayout::PLAYER.range("offhand").unwrap().start;
+        let hand = if is_block(self.held_item()) {
+            InteractionHand::MainHand
+        } else if is_block(self.inventory.read().slots[offhand].clone()) {
+            InteractionHand::OffHand
+        } else {
+            return Err(InteractError::NoItem);
+        };
+
+        let world = self.world();
+        let state = world.get_block_state(pos).ok_or(InteractError::NotLoaded)?;
+        if !state.is_replaceable() {
+            return Err(InteractError::Occupied);
+        }
+        let eye = self.eye_position();
+        let (against, face) = Direction::iter()
+            .filter_map(|direction| {
+                let against = pos + &direction.normal();
+                let face = direction.opposite();
+                let against_state = world.get_block_state(&against)?;
+                (!against_state.is_replaceable() && is_face_visible(&world, &against, face, &eye))
+                    .then_some((against, face))
+            })
+            .min_by(|(a, a_face), (b, b_face)| {
+                face_distance(a, *a_face, &eye).total_cmp(&face_distance(b, *b_face, &eye))
+            })
+            .ok_or(InteractError::NothingToPlaceAgainst)?;
+        self.place_block_against(&against, face, hand).await
+    }
+
+    /// Send a [`ServerboundUseItemOnPacket`], wait for the server to
+    /// acknowledge it, and then swing `hand`. Fails with
+    /// [`InteractError::OutOfReach`] without sending anything if the clicked
+    /// point is farther than [`BLOCK_REACH`] from the player's eyes.
+    pub async fn use_item_on(
+        &self,
+        hand: InteractionHand,
+        block_hit: BlockHitResult,
+    ) -> Result<(), InteractError> {
+        let distance = (self.eye_position() - block_hit.location).length_sqr();
+        if distance > BLOCK_REACH * BLOCK_REACH {
+            return Err(InteractError::OutOfReach);
+        }
+        let sequence = self.block_prediction.next_sequence();
+        self.write_packet(
+            ServerboundUseItemOnPacket {
+                hand,
+                block_hit,
+                sequence,
+            }
+            .get(),
+        )
+        .await?;
+        if !self.block_prediction.wait_for_ack(sequence).await {
+            return Err(InteractError::Timeout);
+        }
+        self.write_packet(ServerboundSwingPacket { hand }.get())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{client_and_server, empty_world};
+    use azalea_block::BlockState;
+    use azalea_core::SlotData;
+    use azalea_protocol::packets::game::{
+        clientbound_block_changed_ack_packet::ClientboundBlockChangedAckPacket,
+        ServerboundGamePacket,
+    };
+    use azalea_registry::Item;
+
+    #[tokio::test]
+    async fn test_place_and_use() {
+        let (client, mut server) = client_and_server();
+
+        let stone = BlockPos::new(2, 64, 0);
+        let target = BlockPos::new(1, 64, 0);
+        empty_world(&client, Vec3::new(0.5, 64., 0.5));
+        client
+            .world
+            .write()
+            .set_block_state(&stone, BlockState::Stone);
+
+        assert!(matches!(
+            client.place_block(&target).await,
+            Err(InteractError::NoItem)
+        ));
+        // the block is in the off hand, so that's the hand we place with
+        client.inventory.write().slots[36] = Slot::Present(SlotData::new(Item::DiamondSword, 1));
+        assert!(matches!(
+            client.place_block(&target).await,
+            Err(InteractError::NoItem)
+        ));
+        client.inventory.write().slots[45] = Slot::Present(SlotData::new(Item::Stone, 1));
+
+        let server_client = client.clone();
+        let server = tokio::spawn(async move {
+            let ServerboundGamePacket::UseItemOn(p) = server.read().await.unwrap() else {
+                panic!("Expected use item on");
+            };
+            assert!(matches!(p.hand, InteractionHand::OffHand));
+            // the only block to place against is the stone
+            assert_eq!(p.block_hit.block_pos, stone);
+            assert_eq!(p.block_hit.direction, Direction::West);
+            assert_eq!(p.block_hit.location, Vec3::new(2., 64.5, 0.5));
+            assert!(!p.block_hit.inside);
+            server_client
+                .world
+                .write()
+                .set_block_state(&target, BlockState::Stone);
+            server_client.handle_block_changed_ack(&ClientboundBlockChangedAckPacket {
+                sequence: p.sequence as i32,
+            });
+            let ServerboundGamePacket::Swing(swing) = server.read().await.unwrap() else {
+                panic!("Expected swing");
+            };
+            assert!(matches!(swing.hand, InteractionHand::OffHand));
+
+            // using the new block clicks its top since the player's eyes are
+            // above it
+            let ServerboundGamePacket::UseItemOn(p) = server.read().await.unwrap() else {
+                panic!("Expected use item on");
+            };
+            assert_eq!(p.block_hit.block_pos, target);
+            assert_eq!(p.block_hit.direction, Direction::Up);
+            server_client.handle_block_changed_ack(&ClientboundBlockChangedAckPacket {
+                sequence: p.sequence as i32,
+            });
+            let ServerboundGamePacket::Swing(swing) = server.read().await.unwrap() else {
+                panic!("Expected swing");
+            };
+            assert!(matches!(swing.hand, InteractionHand::MainHand));
+        });
+
+        client.place_block(&target).await.unwrap();
+        assert_eq!(
+            client.world().get_block_state(&target),
+            Some(BlockState::Stone)
+        );
+        client
+            .use_block(&target, InteractionHand::MainHand)
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        assert!(matches!(
+            client.place_block(&target).await,
+            Err(InteractError::Occupied)
+        ));
+        assert!(matches!(
+            client
+                .use_item_on(
+                    InteractionHand::MainHand,
+                    BlockHitResult {
+                        block_pos: BlockPos::new(8, 64, 0),
+                        direction: Direction::West,
+                        location: Vec3::new(8., 64.5, 0.5),
+                        inside: false,
+                    },
+                )
+                .await,
+            Err(InteractError::OutOfReach)
+        ));
+    }
+}
@@ -0,0 +1,632 @@
+//! Keep track of the player's inventory and the container they have open.
+
+use crate::Client;
+use azalea_core::{BlockPos, Direction, Slot, SlotData, Vec3};
+use azalea_inventory::{
+    item::{is_same_item, max_stack_size_of},
+    ClickOperation, Container, MenuLayout, MouseButton, PLAYER_CONTAINER_ID,
+};
+use azalea_protocol::packets::game::{
+    clientbound_container_close_packet::ClientboundContainerClosePacket,
+    clientbound_container_set_content_packet::ClientboundContainerSetContentPacket,
+    clientbound_container_set_data_packet::ClientboundContainerSetDataPacket,
+    clientbound_container_set_slot_packet::ClientboundContainerSetSlotPacket,
+    clientbound_open_screen_packet::ClientboundOpenScreenPacket,
+    clientbound_set_carried_item_packet::ClientboundSetCarriedItemPacket,
+    serverbound_container_close_packet::ServerboundContainerClosePacket,
+    serverbound_interact_packet::InteractionHand,
+    serverbound_set_carried_item_packet::ServerboundSetCarriedItemPacket,
+    serverbound_use_item_on_packet::{BlockHitResult, ServerboundUseItemOnPacket},
+};
+use log::warn;
+use std::{io, time::Duration};
+use thiserror::Error;
+
+/// The container id the server uses in `ContainerSetSlot` to set the item
+/// held by the cursor (-1).
+const CARRIED_ITEM_CONTAINER_ID: u8 = 255;
+/// The container id the server uses in `ContainerSetSlot` to set a slot in
+/// the player's inventory no matter what's open (-2).
+const INVENTORY_CONTAINER_ID: u8 = 254;
+
+/// How long [`Client::open_container`] waits for the server to open the
+/// container.
+const OPEN_CONTAINER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum HotbarSlotError {
+    #[error("Hotbar slot {0} isn't from 0 to 8")]
+    OutOfRange(u8),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A container that we opened with [`Client::open_container`].
+#[derive(Clone)]
+pub struct ContainerHandle {
+    client: Client,
+    id: u8,
+}
+
+impl ContainerHandle {
+    /// The id the server gave the container.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Whether the container is still open. The server can close containers
+    /// by itself, like when the player walks too far away from them.
+    pub fn is_open(&self) -> bool {
+        self.client.open_container_id() == Some(self.id)
+    }
+
+    /// The container's current state, or `None` if it was closed.
+    pub fn state(&self) -> Option<Container> {
+        self.client
+            .open_container
+            .read()
+            .clone()
+            .filter(|container| container.id == self.id)
+    }
+
+    /// The slots that belong to the container instead of the player, or
+    /// `None` if it was closed.
+    pub fn contents(&self) -> Option<Vec<Slot>> {
+        self.state().map(|container| container.contents().to_vec())
+    }
+
+    /// Close the container, if it's still open.
+    pub async fn close(self) {
+        if !self.is_open() {
+            return;
+        }
+        if let Err(e) = self.client.close_container().await {
+            warn!("Couldn't close container: {e}");
+        }
+    }
+}
+
+impl Client {
+    /// The player's inventory, as the server last told us. This has the
+    /// layout of [`MenuLayout::PLAYER`].
+    pub fn inventory(&self) -> Container {
+        self.inventory.read().clone()
+    }
+
+    /// The container the player has open, if it's not their own inventory.
+    pub fn open_container_state(&self) -> Option<Container> {
+        self.open_container.read().clone()
+    }
+
+    /// Which slot of the hotbar the player is holding, from 0 to 8.
+    pub fn selected_hotbar_slot(&self) -> u8 {
+        *self.selected_hotbar_slot.read()
+    }
+
+    /// Hold a different slot of the hotbar. Fails with
+    /// [`HotbarSlotError::OutOfRange`] if `slot` isn't from 0 to 8.
+    pub async fn set_selected_hotbar_slot(&self, slot: u8) -> Result<(), HotbarSlotError> {
+        if slot >= 9 {
+            return Err(HotbarSlotError::OutOfRange(slot));
+        }
+        *self.selected_hotbar_slot.write() = slot;
+        self.write_packet(ServerboundSetCarriedItemPacket { slot: slot as u16 }.get())
+            .await?;
+        Ok(())
+    }
+
+    /// The item in the player's main hand.
+    pub fn held_item(&self) -> Slot {
+        let index = MenuLayout::PLAYER.range("hotbar").unwrap().start
+            + self.selected_hotbar_slot() as usize;
+        self.inventory.read().slots[index].clone()
+    }
+
+    fn open_container_id(&self) -> Option<u8> {
+        self.open_container.read().as_ref().map(|c| c.id)
+    }
+
+    /// Right click a block like a chest or a furnace and wait for the server
+    /// to open it. Returns `None` if the server didn't open a container in
+    /// time, like if the block isn't a container or it's too far away.
+    pub async fn open_container(&self, pos: &BlockPos) -> Option<ContainerHandle> {
+        // the server sends the contents right after it opens the container,
+        // so that's what we wait for
+        let opened = self.container_opened.notified();
+        let packet = ServerboundUseItemOnPacket {
+            hand: InteractionHand::MainHand,
+            block_hit: BlockHitResult {
+                block_pos: *pos,
+                direction: Direction::Up,
+                location: Vec3 {
+                    x: pos.x as f64 + 0.5,
+                    y: pos.y as f64 + 1.,
+                    z: pos.z as f64 + 0.5,
+                },
+                inside: false,
+            },
+            sequence: self.block_prediction.next_sequence(),
+        };
+        if let Err(e) = self.write_packet(packet.get()).await {
+            warn!("Couldn't open container: {e}");
+            return None;
+        }
+        tokio::time::timeout(OPEN_CONTAINER_TIMEOUT, opened)
+            .await
+            .ok()?;
+        Some(ContainerHandle {
+            client: self.clone(),
+            id: self.open_container_id()?,
+        })
+    }
+
+    /// Close the container the player has open, if there is one.
+    pub async fn close_container(&self) -> Result<(), std::io::Error> {
+        let Some(id) = self.open_container.write().take().map(|c| c.id) else {
+            return Ok(());
+        };
+        self.write_packet(ServerboundContainerClosePacket { container_id: id }.get())
+            .await
+    }
+
+    /// Click slots in the open container, or in the player's inventory if no
+    /// container is open. We predict what the click does right away, and the
+    /// server corrects us if we were wrong.
+    pub async fn click(&self, operation: &ClickOperation) -> Result<(), std::io::Error> {
+        let packets = {
+            let mut inventory = self.inventory.write();
+            let mut open_container = self.open_container.write();
+            match open_container.as_mut() {
+                Some(container) => {
+                    let packets = container.click(operation);
+                    copy_player_slots(container, &mut inventory);
+                    packets
+                }
+                None => inventory.click(operation),
+            }
+        };
+        for packet in packets {
+            self.write_packet(packet.get()).await?;
+        }
+        Ok(())
+    }
+
+    /// Move up to `amount` items that match `predicate` from a container to
+    /// the player's inventory. Returns how many were moved, which is less
+    /// than `amount` if the container doesn't have enough or the inventory
+    /// is full.
+    pub async fn take_amount(
+        &self,
+        container: &ContainerHandle,
+        amount: u32,
+        predicate: impl Fn(&SlotData) -> bool,
+    ) -> Result<u32, std::io::Error> {
+        let mut remaining = amount;
+        let Some(state) = container.state() else {
+            return Ok(0);
+        };
+        for slot in state.layout().container_range() {
+            if remaining == 0 || !container.is_open() {
+                break;
+            }
+            let Some(Slot::Present(item)) = container.state().map(|c| c.slots[slot].clone()) else {
+                continue;
+            };
+            if !predicate(&item) {
+                continue;
+            }
+
+            if item.count as u32 <= remaining {
+                self.click(&ClickOperation::QuickMove { slot }).await?;
+                let left = match container.state().map(|c| c.slots[slot].clone()) {
+                    Some(Slot::Present(left)) => left.count,
+                    _ => 0,
+                };
+                // the stack might have grown since we looked at it
+                remaining -= item.count.saturating_sub(left) as u32;
+                if left > 0 {
+                    // the inventory is full
+                    break;
+                }
+                continue;
+            }
+
+            // pick up the stack, put down what we want one at a time, and
+            // put the rest back
+            self.click(&ClickOperation::Pickup {
+                slot: Some(slot),
+                button: MouseButton::Left,
+            })
+            .await?;
+            while remaining > 0 {
+                let Some(target) = container
+                    .state()
+                    .and_then(|c| inventory_slot_for(&c, &item))
+                else {
+                    break;
+                };
+                self.click(&ClickOperation::Pickup {
+                    slot: Some(target),
+                    button: MouseButton::Right,
+                })
+                .await?;
+                remaining -= 1;
+            }
+            self.click(&ClickOperation::Pickup {
+                slot: Some(slot),
+                button: MouseButton::Left,
+            })
+            .await?;
+            break;
+        }
+        Ok(amount - remaining)
+    }
+
+    pub(crate) fn handle_set_carried_item(&self, packet: &ClientboundSetCarriedItemPacket) {
+        *self.selected_hotbar_slot.write() = packet.slot;
+    }
+
+    pub(crate) fn handle_open_screen(&self, packet: &ClientboundOpenScreenPacket) {
+        *self.open_container.write() = Some(Container::new(
+            packet.container_id as u8,
+            packet.menu_type,
+            packet.title.clone(),
+        ));
+    }
+
+    pub(crate) fn handle_container_close(&self, packet: &ClientboundContainerClosePacket) {
+        let mut open_container = self.open_container.write();
+        if open_container.as_ref().map(|c| c.id) == Some(packet.container_id) {
+            *open_container = None;
+        }
+    }
+
+    pub(crate) fn handle_container_set_content(
+        &self,
+        packet: &ClientboundContainerSetContentPacket,
+    ) {
+        let mut inventory = self.inventory.write();
+        let mut open_container = self.open_container.write();
+        if packet.container_id == PLAYER_CONTAINER_ID {
+            inventory.set_content(
+                packet.state_id,
+                packet.items.clone(),
+                packet.carried_item.clone(),
+            );
+            // the open container shows the same inventory, so keep it in sync
+            if let Some(container) = open_container.as_mut() {
+                copy_player_slots(&inventory, container);
+            }
+            return;
+        }
+        let Some(container) = open_container
+            .as_mut()
+            .filter(|c| c.id == packet.container_id)
+        else {
+            warn!(
+                "Got contents for container {} but it's not open",
+                packet.container_id
+            );
+            return;
+        };
+        container.set_content(
+            packet.state_id,
+            packet.items.clone(),
+            packet.carried_item.clone(),
+        );
+        copy_player_slots(container, &mut inventory);
+        drop((inventory, open_container));
+        self.container_opened.notify_waiters();
+    }
+
+    pub(crate) fn handle_container_set_slot(&self, packet: &ClientboundContainerSetSlotPacket) {
+        let mut inventory = self.inventory.write();
+        let mut open_container = self.open_container.write();
+        let index = packet.slot as usize;
+        let item = packet.item_stack.clone();
+        match packet.container_id {
+            CARRIED_ITEM_CONTAINER_ID => {
+                match open_container.as_mut() {
+                    Some(container) => container.carried = item,
+                    None => inventory.carried = item,
+                };
+            }
+            INVENTORY_CONTAINER_ID => {
+                let Some(index) = MenuLayout::from_inventory_index(index) else {
+                    warn!("Got item for inventory slot {index} which doesn't exist");
+                    return;
+                };
+                inventory.slots[index] = item.clone();
+                if let Some(container) = open_container.as_mut() {
+                    if let Some(index) = container.layout().from_player_inventory_index(index) {
+                        container.slots[index] = item;
+                    }
+                }
+            }
+            PLAYER_CONTAINER_ID => {
+                inventory.set_slot(packet.state_id, index, item.clone());
+                if let Some(container) = open_container.as_mut() {
+                    if let Some(index) = container.layout().from_player_inventory_index(index) {
+                        container.slots[index] = item;
+                    }
+                }
+            }
+            id => {
+                let Some(container) = open_container.as_mut().filter(|c| c.id == id) else {
+                    return;
+                };
+                container.set_slot(packet.state_id, index, item.clone());
+                if let Some(index) = container.layout().player_inventory_index(index) {
+                    inventory.slots[index] = item;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn handle_container_set_data(&self, packet: &ClientboundContainerSetDataPacket) {
+        if let Some(container) = self
+            .open_container
+            .write()
+            .as_mut()
+            .filter(|c| c.id == packet.container_id)
+        {
+            container.set_data(packet.id, packet.value);
+        }
+    }
+}
+
+/// A slot in the player's part of a container that one more of `item` can
+/// be put in, preferring slots that already have some of it.
+fn inventory_slot_for(container: &Container, item: &SlotData) -> Option<usize> {
+    let range = container.layout().player_range()?;
+    let max = max_stack_size_of(item);
+    range
+        .clone()
+        .find(|&slot| match &container.slots[slot] {
+            Slot::Present(existing) => is_same_item(existing, item) && existing.count < max,
+            Slot::Empty => false,
+        })
+        .or_else(|| {
+            range
+                .into_iter()
+                .find(|&slot| container.slots[slot] == Slot::Empty)
+        })
+}
+
+/// Copy the player's inventory and hotbar from one container to another.
+fn copy_player_slots(from: &Container, to: &mut Container) {
+    let (Some(from_range), Some(to_range)) =
+        (from.layout().player_range(), to.layout().player_range())
+    else {
+        return;
+    };
+    to.slots[to_range].clone_from_slice(&from.slots[from_range]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::client_and_server;
+    use azalea_chat::Component;
+    use azalea_protocol::packets::game::ServerboundGamePacket;
+    use azalea_registry::{Item, Menu};
+
+    fn set_slot(
+        container_id: u8,
+        slot: u16,
+        item_stack: Slot,
+    ) -> ClientboundContainerSetSlotPacket {
+        ClientboundContainerSetSlotPacket {
+            container_id,
+            state_id: 1,
+            slot,
+            item_stack,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_container() {
+        let (client, mut server) = client_and_server();
+
+        let server_client = client.clone();
+        let server = tokio::spawn(async move {
+            let ServerboundGamePacket::UseItemOn(p) = server.read().await.unwrap() else {
+                panic!("Expected use item on");
+            };
+            assert_eq!(p.block_hit.block_pos, BlockPos::new(1, 2, 3));
+            server_client.handle_open_screen(&ClientboundOpenScreenPacket {
+                container_id: 1,
+                menu_type: Menu::Hopper,
+                title: Component::from("Hopper"),
+            });
+            let mut items = vec![Slot::Present(SlotData::new(Item::Stone, 5))];
+            items.resize(41, Slot::Empty);
+            // the last slot of the hotbar
+            items[40] = Slot::Present(SlotData::new(Item::Granite, 1));
+            server_client.handle_container_set_content(&ClientboundContainerSetContentPacket {
+                container_id: 1,
+                state_id: 3,
+                items,
+                carried_item: Slot::Empty,
+            });
+            server
+        });
+
+        let hopper = client
+            .open_container(&BlockPos::new(1, 2, 3))
+            .await
+            .unwrap();
+        let mut server = server.await.unwrap();
+        assert_eq!(hopper.id(), 1);
+        assert_eq!(hopper.state().unwrap().state_id, 3);
+        assert_eq!(hopper.contents().unwrap().len(), 5);
+        assert_eq!(
+            client.inventory().slots[44],
+            Slot::Present(SlotData::new(Item::Granite, 1))
+        );
+
+        // slots in the player's inventory are mirrored into the open container
+        client.handle_container_set_slot(&set_slot(
+            PLAYER_CONTAINER_ID,
+            9,
+            Slot::Present(SlotData::new(Item::PolishedGranite, 2)),
+        ));
+        client.handle_container_set_slot(&set_slot(
+            INVENTORY_CONTAINER_ID,
+            0,
+            Slot::Present(SlotData::new(Item::Diorite, 1)),
+        ));
+        client.handle_container_set_slot(&set_slot(
+            CARRIED_ITEM_CONTAINER_ID,
+            0,
+            Slot::Present(SlotData::new(Item::PolishedDiorite, 1)),
+        ));
+        let state = hopper.state().unwrap();
+        assert_eq!(
+            state.slots[5],
+            Slot::Present(SlotData::new(Item::PolishedGranite, 2))
+        );
+        assert_eq!(
+            state.slots[32],
+            Slot::Present(SlotData::new(Item::Diorite, 1))
+        );
+        assert_eq!(
+            state.carried,
+            Slot::Present(SlotData::new(Item::PolishedDiorite, 1))
+        );
+        assert_eq!(
+            client.inventory().slots[36],
+            Slot::Present(SlotData::new(Item::Diorite, 1))
+        );
+
+        hopper.clone().close().await;
+        assert!(!hopper.is_open());
+        let ServerboundGamePacket::ContainerClose(p) = server.read().await.unwrap() else {
+            panic!("Expected container close");
+        };
+        assert_eq!(p.container_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_take_amount() {
+        let (client, mut server) = client_and_server();
+
+        client.handle_open_screen(&ClientboundOpenScreenPacket {
+            container_id: 2,
+            menu_type: Menu::Generic9x1,
+            title: Component::from("Chest"),
+        });
+        let mut items = vec![
+            Slot::Present(SlotData::new(Item::Stone, 3)),
+            Slot::Present(SlotData::new(Item::Granite, 64)),
+            Slot::Present(SlotData::new(Item::Stone, 10)),
+        ];
+        items.resize(45, Slot::Empty);
+        client.handle_container_set_content(&ClientboundContainerSetContentPacket {
+            container_id: 2,
+            state_id: 5,
+            items,
+            carried_item: Slot::Empty,
+        });
+        let chest = ContainerHandle {
+            client: client.clone(),
+            id: 2,
+        };
+
+        let taken = client
+            .take_amount(&chest, 5, |item| item.item.vanilla() == Some(&Item::Stone))
+            .await
+            .unwrap();
+        assert_eq!(taken, 5);
+        let state = chest.state().unwrap();
+        assert_eq!(state.slots[0], Slot::Empty);
+        assert_eq!(state.slots[2], Slot::Present(SlotData::new(Item::Stone, 8)));
+        assert_eq!(
+            state.slots[44],
+            Slot::Present(SlotData::new(Item::Stone, 5))
+        );
+        assert_eq!(state.carried, Slot::Empty);
+        assert_eq!(
+            client.inventory().slots[44],
+            Slot::Present(SlotData::new(Item::Stone, 5))
+        );
+
+        // a shift click, picking up the stack, two right clicks, and putting
+        // the stack back
+        for _ in 0..5 {
+            let ServerboundGamePacket::ContainerClick(p) = server.read().await.unwrap() else {
+                panic!("Expected container click");
+            };
+            assert_eq!(p.container_id, 2);
+            assert_eq!(p.state_id, 5);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_selected_hotbar_slot() {
+        let (client, mut server) = client_and_server();
+        client.set_selected_hotbar_slot(8).await.unwrap();
+        let ServerboundGamePacket::SetCarriedItem(p) = server.read().await.unwrap() else {
+            panic!("Expected set carried item");
+        };
+        assert_eq!(p.slot, 8);
+
+        assert!(matches!(
+            client.set_selected_hotbar_slot(9).await,
+            Err(HotbarSlotError::OutOfRange(9))
+        ));
+        assert_eq!(client.selected_hotbar_slot(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_take_amount_grown_stack() {
+        let (client, _server) = client_and_server();
+
+        client.handle_open_screen(&ClientboundOpenScreenPacket {
+            container_id: 2,
+            menu_type: Menu::Generic9x1,
+            title: Component::from("Chest"),
+        });
+        // the inventory only has room for 4 more
+        let mut items = vec![Slot::Present(SlotData::new(Item::Stone, 3))];
+        items.resize(9, Slot::Empty);
+        items.resize(45, Slot::Present(SlotData::new(Item::Granite, 64)));
+        items[9] = Slot::Present(SlotData::new(Item::Stone, 60));
+        client.handle_container_set_content(&ClientboundContainerSetContentPacket {
+            container_id: 2,
+            state_id: 5,
+            items,
+            carried_item: Slot::Empty,
+        });
+        let chest = ContainerHandle {
+            client: client.clone(),
+            id: 2,
+        };
+
+        // the server adds to the stack after we've looked at it
+        let grown = std::cell::Cell::new(false);
+        let taken = client
+            .take_amount(&chest, 5, |stack| {
+                if !grown.replace(true) {
+                    client.handle_container_set_slot(&set_slot(
+                        2,
+                        0,
+                        Slot::Present(SlotData::new(Item::Stone, 64)),
+                    ));
+                }
+                stack.item.vanilla() == Some(&Item::Stone)
+            })
+            .await
+            .unwrap();
+        assert_eq!(taken, 0);
+        let state = chest.state().unwrap();
+        assert_eq!(
+            state.slots[0],
+            Slot::Present(SlotData::new(Item::Stone, 60))
+        );
+        assert_eq!(
+            state.slots[9],
+            Slot::Present(SlotData::new(Item::Stone, 64))
+        );
+    }
+}
@@ -11,19 +11,41 @@
 #![feature(error_generic_member_access)]
 
 mod account;
+pub mod channels;
 mod chat;
 mod client;
+mod effects;
+mod fabric;
+pub mod filter;
 mod get_mc_dir;
+mod interact;
+mod inventory;
+pub mod login;
+pub mod middleware;
+mod mining;
+pub mod mitm;
 mod movement;
 pub mod ping;
 mod player;
 mod plugins;
+pub mod replay;
+mod stats;
+#[cfg(test)]
+mod test_utils;
 
 pub use account::Account;
-pub use client::{ChatPacket, Client, ClientInformation, Event, JoinError, PhysicsState};
+pub use azalea_inventory::{ClickOperation, Container, MenuLayout, MouseButton};
+pub use azalea_protocol::packets::game::serverbound_interact_packet::InteractionHand;
+pub use client::{
+    ChatPacket, Client, ClientInformation, Event, JoinError, JoinOptions, PhysicsState,
+};
+pub use interact::{InteractError, BLOCK_REACH};
+pub use inventory::{ContainerHandle, HotbarSlotError};
+pub use mining::DigError;
 pub use movement::{SprintDirection, WalkDirection};
 pub use player::PlayerInfo;
 pub use plugins::{Plugin, PluginState, PluginStates, Plugins};
+pub use stats::ClientStats;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,188 @@
+//! Answer the custom queries that servers (or the proxies in front of them)
+//! send while we're logging in.
+//!
+//! Handlers are added to an [`Account`] with
+//! [`Account::with_login_query_handler`] and are keyed by the identifier of
+//! the query. They're checked before azalea's own handling of queries like
+//! Velocity forwarding and Forge's handshake, so they can replace it.
+//!
+//! ```rust,no_run
+//! use async_trait::async_trait;
+//! use azalea_client::{login::LoginQueryHandler, Account};
+//! use azalea_core::ResourceLocation;
+//! use azalea_protocol::packets::login::clientbound_custom_query_packet::ClientboundCustomQueryPacket;
+//!
+//! struct SecretHandler;
+//!
+//! #[async_trait]
+//! impl LoginQueryHandler for SecretHandler {
+//!     async fn handle(
+//!         &self,
+//!         query: &ClientboundCustomQueryPacket,
+//!     ) -> anyhow::Result<Option<Vec<u8>>> {
+//!         // answer with the nonce the server sent followed by our secret
+//!         let mut response = query.data.to_vec();
+//!         response.extend_from_slice(b"hunter2");
+//!         Ok(Some(response))
+//!     }
+//! }
+//!
+//! let account = Account::offline("bot")
+//!     .with_login_query_handler(ResourceLocation::new("myproxy:auth").unwrap(), SecretHandler);
+//! ```
+//!
+//! [`Account`]: crate::Account
+//! [`Account::with_login_query_handler`]: crate::Account::with_login_query_handler
+
+use async_trait::async_trait;
+use azalea_core::ResourceLocation;
+use azalea_protocol::packets::login::clientbound_custom_query_packet::ClientboundCustomQueryPacket;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// Something that answers a custom query during login. See the
+/// [module docs](self).
+#[async_trait]
+pub trait LoginQueryHandler: Send + Sync {
+    /// Get the payload to answer the query with, or `None` to tell the server
+    /// we don't understand it. Returning an error stops us from joining.
+    async fn handle(&self, query: &ClientboundCustomQueryPacket)
+        -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+/// The [`LoginQueryHandler`]s for each query identifier.
+#[derive(Clone, Default)]
+pub struct LoginQueryHandlers {
+    handlers: HashMap<ResourceLocation, Arc<dyn LoginQueryHandler>>,
+}
+
+impl LoginQueryHandlers {
+    /// Handle queries with the given identifier with `handler`, replacing
+    /// the handler that was there before.
+    pub fn insert(
+        &mut self,
+        identifier: ResourceLocation,
+        handler: impl LoginQueryHandler + 'static,
+    ) {
+        self.handlers.insert(identifier, Arc::new(handler));
+    }
+
+    /// Stop handling queries with the given identifier.
+    pub fn remove(&mut self, identifier: &ResourceLocation) {
+        self.handlers.remove(identifier);
+    }
+
+    /// The handler for queries with the given identifier.
+    pub fn get(&self, identifier: &ResourceLocation) -> Option<Arc<dyn LoginQueryHandler>> {
+        self.handlers.get(identifier).cloned()
+    }
+}
+
+impl fmt::Debug for LoginQueryHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Account, Client, JoinError};
+    use azalea_auth::game_profile::GameProfile;
+    use azalea_protocol::{
+        connect::Connection,
+        packets::{
+            handshake::{
+                client_intention_packet::ClientIntentionPacket, ClientIdentifier,
+                ClientboundHandshakePacket, ServerboundHandshakePacket,
+            },
+            login::{
+                clientbound_game_profile_packet::ClientboundGameProfilePacket,
+                ClientboundLoginPacket, ServerboundLoginPacket,
+            },
+            ProtocolVersion,
+        },
+        ServerAddress,
+    };
+    use uuid::Uuid;
+
+    struct Reverse;
+
+    #[async_trait]
+    impl LoginQueryHandler for Reverse {
+        async fn handle(
+            &self,
+            query: &ClientboundCustomQueryPacket,
+        ) -> anyhow::Result<Option<Vec<u8>>> {
+            if query.data.is_empty() {
+                anyhow::bail!("Empty query");
+            }
+            Ok(Some(query.data.iter().rev().copied().collect()))
+        }
+    }
+
+    /// Log in to a server that sends one query, returning what we answered
+    /// with.
+    async fn login(query: &[u8]) -> (Result<(), JoinError>, Option<Option<Vec<u8>>>) {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+        let query = query.to_vec();
+
+        let server = tokio::spawn(async move {
+            let mut conn: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> =
+                Connection::from_stream(server_stream);
+            let ServerboundHandshakePacket::ClientIntention(ClientIntentionPacket { .. }) =
+                conn.read().await.unwrap();
+            let mut conn: Connection<ServerboundLoginPacket, ClientboundLoginPacket> =
+                Connection::from(conn);
+            let ServerboundLoginPacket::Hello(_) = conn.read().await.unwrap() else {
+                panic!("Expected hello");
+            };
+            conn.write(
+                ClientboundCustomQueryPacket {
+                    transaction_id: 7,
+                    identifier: ResourceLocation::new("myproxy:auth").unwrap(),
+                    data: query.into(),
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+            let Ok(ServerboundLoginPacket::CustomQuery(answer)) = conn.read().await else {
+                return None;
+            };
+            assert_eq!(answer.transaction_id, 7);
+            conn.write(
+                ClientboundGameProfilePacket {
+                    game_profile: GameProfile::new(Uuid::nil(), "bot".to_string()),
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+            Some(answer.query.map(|query| query.data.to_vec()))
+        });
+
+        let account = Account::offline("bot")
+            .with_login_query_handler(ResourceLocation::new("myproxy:auth").unwrap(), Reverse);
+        let result = Client::handshake(
+            Connection::from_stream(client_stream),
+            ClientIdentifier::Vanilla,
+            ProtocolVersion::LATEST,
+            &account,
+            &ServerAddress::try_from("localhost").unwrap(),
+        )
+        .await
+        .map(|_| ());
+        (result, server.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_login_query_handler() {
+        let (result, answer) = login(b"abc").await;
+        assert!(result.is_ok());
+        assert_eq!(answer, Some(Some(b"cba".to_vec())));
+
+        let (result, answer) = login(b"").await;
+        assert!(matches!(result, Err(JoinError::LoginQuery(_))));
+        assert_eq!(answer, None);
+    }
+}
@@ -0,0 +1,222 @@
+//! Intercept packets as they're received and sent.
+//!
+//! Middleware runs in the order it was added. Inbound packets go through it
+//! right after they're decoded and before the client handles them, and
+//! outbound packets go through it in [`Client::write_packet`] before they're
+//! written. Each middleware can let a packet through (possibly modified),
+//! drop it, or turn it into several packets.
+
+use crate::Client;
+use azalea_protocol::packets::game::{ClientboundGamePacket, ServerboundGamePacket};
+use std::sync::Arc;
+
+/// What to do with a packet that went through a [`PacketMiddleware`].
+#[derive(Clone, Debug)]
+pub enum Intercept<P> {
+    /// Keep going with this packet. It doesn't have to be the same packet
+    /// that was passed in.
+    Pass(P),
+    /// Throw the packet away. Middleware after this one won't see it.
+    Drop,
+    /// Replace the packet with any number of packets, in order. This is how
+    /// you inject packets.
+    Replace(Vec<P>),
+}
+
+/// Something that can look at and change packets before they're handled or
+/// sent. Add it to a client with [`Client::add_middleware`].
+///
+/// These are called synchronously on the task that reads or writes packets,
+/// so they should be fast and must not block.
+///
+/// ```
+/// # use azalea_client::{Client, middleware::{Intercept, PacketMiddleware}};
+/// # use azalea_protocol::packets::game::ServerboundGamePacket;
+/// /// Don't tell the server when we start or stop sneaking.
+/// struct NoSneakPackets;
+///
+/// impl PacketMiddleware for NoSneakPackets {
+///     fn outbound(
+///         &self,
+///         _client: &Client,
+///         packet: ServerboundGamePacket,
+///     ) -> Intercept<ServerboundGamePacket> {
+///         match packet {
+///             ServerboundGamePacket::PlayerCommand(_) => Intercept::Drop,
+///             packet => Intercept::Pass(packet),
+///         }
+///     }
+/// }
+/// ```
+pub trait PacketMiddleware: Send + Sync {
+    /// Called for every packet we receive, before the client handles it.
+    fn inbound(
+        &self,
+        _client: &Client,
+        packet: ClientboundGamePacket,
+    ) -> Intercept<ClientboundGamePacket> {
+        Intercept::Pass(packet)
+    }
+
+    /// Called for every packet we send, before it's written.
+    fn outbound(
+        &self,
+        _client: &Client,
+        packet: ServerboundGamePacket,
+    ) -> Intercept<ServerboundGamePacket> {
+        Intercept::Pass(packet)
+    }
+}
+
+/// Send a packet through every middleware in order and get the packets that
+/// came out the other side.
+fn run_chain<P>(
+    chain: &[Arc<dyn PacketMiddleware>],
+    packet: P,
+    mut intercept: impl FnMut(&dyn PacketMiddleware, P) -> Intercept<P>,
+) -> Vec<P> {
+    let mut packets = vec![packet];
+    for middleware in chain {
+        let mut next = Vec::with_capacity(packets.len());
+        for packet in packets {
+            match intercept(middleware.as_ref(), packet) {
+                Intercept::Pass(packet) => next.push(packet),
+                Intercept::Drop => {}
+                Intercept::Replace(packets) => next.extend(packets),
+            }
+        }
+        if next.is_empty() {
+            return next;
+        }
+        packets = next;
+    }
+    packets
+}
+
+impl Client {
+    /// Add a middleware to the end of this client's chain. See
+    /// [`PacketMiddleware`].
+    pub fn add_middleware(&self, middleware: impl PacketMiddleware + 'static) {
+        self.middleware.write().push(Arc::new(middleware));
+    }
+
+    /// Remove all the middleware from this client.
+    pub fn clear_middleware(&self) {
+        self.middleware.write().clear();
+    }
+
+    /// Send a received packet through the middleware, returning the packets
+    /// that should be handled.
+    pub(crate) fn intercept_inbound(
+        &self,
+        packet: ClientboundGamePacket,
+    ) -> Vec<ClientboundGamePacket> {
+        // clone the list so middleware can add more middleware without
+        // deadlocking
+        let chain = self.middleware.read().clone();
+        run_chain(&chain, packet, |m, p| m.inbound(self, p))
+    }
+
+    /// Send a packet we're about to write through the middleware, returning
+    /// the packets that should actually be written.
+    pub(crate) fn intercept_outbound(
+        &self,
+        packet: ServerboundGamePacket,
+    ) -> Vec<ServerboundGamePacket> {
+        let chain = self.middleware.read().clone();
+        run_chain(&chain, packet, |m, p| m.outbound(self, p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::client_and_server;
+    use azalea_protocol::packets::game::{
+        clientbound_set_time_packet::ClientboundSetTimePacket,
+        serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
+    };
+
+    struct DoubleKeepAlive;
+    impl PacketMiddleware for DoubleKeepAlive {
+        fn outbound(
+            &self,
+            _client: &Client,
+            packet: ServerboundGamePacket,
+        ) -> Intercept<ServerboundGamePacket> {
+            match packet {
+                ServerboundGamePacket::KeepAlive(p) => Intercept::Replace(vec![
+                    ServerboundKeepAlivePacket { id: p.id }.get(),
+                    ServerboundKeepAlivePacket { id: p.id + 1 }.get(),
+                ]),
+                packet => Intercept::Pass(packet),
+            }
+        }
+    }
+
+    struct DropOddKeepAlive;
+    impl PacketMiddleware for DropOddKeepAlive {
+        fn outbound(
+            &self,
+            _client: &Client,
+            packet: ServerboundGamePacket,
+        ) -> Intercept<ServerboundGamePacket> {
+            match packet {
+                ServerboundGamePacket::KeepAlive(p) if p.id % 2 == 1 => Intercept::Drop,
+                packet => Intercept::Pass(packet),
+            }
+        }
+    }
+
+    struct SetNight;
+    impl PacketMiddleware for SetNight {
+        fn inbound(
+            &self,
+            _client: &Client,
+            packet: ClientboundGamePacket,
+        ) -> Intercept<ClientboundGamePacket> {
+            match packet {
+                ClientboundGamePacket::SetTime(p) => Intercept::Pass(
+                    ClientboundSetTimePacket {
+                        day_time: 18000,
+                        ..p
+                    }
+                    .get(),
+                ),
+                packet => Intercept::Pass(packet),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outbound_chain() {
+        let (client, _server) = client_and_server();
+        client.add_middleware(DoubleKeepAlive);
+        client.add_middleware(DropOddKeepAlive);
+
+        let packets = client.intercept_outbound(ServerboundKeepAlivePacket { id: 2 }.get());
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(&packets[0], ServerboundGamePacket::KeepAlive(p) if p.id == 2));
+
+        client.clear_middleware();
+        let packets = client.intercept_outbound(ServerboundKeepAlivePacket { id: 3 }.get());
+        assert_eq!(packets.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_inbound_modify() {
+        let (client, _server) = client_and_server();
+        client.add_middleware(SetNight);
+        let packets = client.intercept_inbound(
+            ClientboundSetTimePacket {
+                game_time: 1,
+                day_time: 2,
+            }
+            .get(),
+        );
+        assert!(matches!(
+            &packets[..],
+            [ClientboundGamePacket::SetTime(p)] if p.day_time == 18000 && p.game_time == 1
+        ));
+    }
+}
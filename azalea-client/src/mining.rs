@@ -0,0 +1,701 @@
+//! Break blocks the same way the vanilla client does.
+//!
+//! Every packet that changes a block has a sequence number, and the server
+//! acknowledges the sequence once it's done handling the packet. By then it
+//! has also sent us the block's new state, so that's how we know whether it
+//! let us break the block.
+
+use crate::Client;
+use azalea_block::{Block, BlockState};
+use azalea_core::{BlockPos, Direction, ResourceLocation, Slot, SlotData, Vec3};
+use azalea_inventory::{ItemStack, MenuLayout};
+use azalea_protocol::packets::game::{
+    clientbound_block_changed_ack_packet::ClientboundBlockChangedAckPacket,
+    clientbound_update_tags_packet::ClientboundUpdateTagsPacket,
+    serverbound_interact_packet::InteractionHand,
+    serverbound_player_action_packet::{Action, ServerboundPlayerActionPacket},
+    serverbound_swing_packet::ServerboundSwingPacket,
+};
+use azalea_registry::{Enchantment, Item, MobEffect};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::sync::watch;
+
+/// How long we wait for the server to acknowledge a block change.
+const BLOCK_CHANGE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a game tick is.
+const TICK: Duration = Duration::from_millis(50);
+/// How high the player's eyes are above their feet when they're standing.
+const EYE_HEIGHT: f64 = 1.62;
+
+#[derive(Error, Debug)]
+pub enum DigError {
+    #[error("The block isn't in a loaded chunk")]
+    NotLoaded,
+    #[error("There's no block to break")]
+    Air,
+    #[error("The block can't be broken")]
+    Unbreakable,
+    #[error("The block changed before we finished breaking it")]
+    BlockChanged,
+    #[error("The server didn't let us break the block")]
+    Rejected,
+    #[error("The server didn't acknowledge breaking the block in time")]
+    Timeout,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The sequence numbers of the packets we sent that change blocks, and the
+/// last one the server acknowledged.
+pub(crate) struct BlockPrediction {
+    sequence: AtomicU32,
+    acked: watch::Sender<u32>,
+}
+
+impl Default for BlockPrediction {
+    fn default() -> Self {
+        Self {
+            sequence: AtomicU32::new(0),
+            acked: watch::channel(0).0,
+        }
+    }
+}
+
+impl BlockPrediction {
+    /// The sequence number for the next packet that changes a block.
+    pub fn next_sequence(&self) -> u32 {
+        self.sequence.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// The server acknowledged every sequence up to and including this one.
+    pub fn ack(&self, sequence: u32) {
+        self.acked.send_if_modified(|acked| {
+            if sequence > *acked {
+                *acked = sequence;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Wait until the server acknowledges the sequence. Returns false if it
+    /// didn't in time.
+    pub async fn wait_for_ack(&self, sequence: u32) -> bool {
+        let mut acked = self.acked.subscribe();
+        tokio::time::timeout(BLOCK_CHANGE_TIMEOUT, async {
+            while *acked.borrow_and_update() < sequence {
+                // the sender lives as long as we do, so this can't fail
+                let _ = acked.changed().await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+/// The block tags the server sent us, as tag names to the ids of the blocks
+/// in them.
+pub(crate) type BlockTags = HashMap<ResourceLocation, HashSet<u32>>;
+
+/// Everything about the player that changes how fast they break blocks.
+pub(crate) struct Miner<'a> {
+    pub tool: Option<&'a SlotData>,
+    pub helmet: Option<&'a SlotData>,
+    pub effects: &'a HashMap<MobEffect, u8>,
+    pub tags: &'a BlockTags,
+    pub eyes_in_water: bool,
+    pub on_ground: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Hoe,
+    Sword,
+    Shears,
+}
+
+/// The mining level and speed of a tool's material.
+#[derive(Clone, Copy)]
+struct Tier {
+    level: u8,
+    speed: f32,
+}
+
+fn tool_of(item: Item) -> Option<(ToolKind, Tier)> {
+    if item == Item::Shears {
+        return Some((
+            ToolKind::Shears,
+            Tier {
+                level: 0,
+                speed: 1.,
+            },
+        ));
+    }
+    let name = item.to_string();
+    let (tier, kind) = name.trim_start_matches("minecraft:").split_once('_')?;
+    let kind = match kind {
+        "pickaxe" => ToolKind::Pickaxe,
+        "axe" => ToolKind::Axe,
+        "shovel" => ToolKind::Shovel,
+        "hoe" => ToolKind::Hoe,
+        "sword" => ToolKind::Sword,
+        _ => return None,
+    };
+    let (level, speed) = match tier {
+        "wooden" => (0, 2.),
+        "stone" => (1, 4.),
+        "iron" => (2, 6.),
+        "diamond" => (3, 8.),
+        "netherite" => (4, 9.),
+        "golden" => (0, 12.),
+        _ => return None,
+    };
+    Some((kind, Tier { level, speed }))
+}
+
+fn block_name(state: BlockState) -> &'static str {
+    Box::<dyn Block>::from(state).id()
+}
+
+impl Miner<'_> {
+    fn has_tag(&self, state: BlockState, tag: &str) -> bool {
+        let Ok(block) =
+            azalea_registry::Block::from_str(&format!("minecraft:{}", block_name(state)))
+        else {
+            return false;
+        };
+        let Ok(tag) = ResourceLocation::new(tag) else {
+            return false;
+        };
+        self.tags
+            .get(&tag)
+            .map_or(false, |blocks| blocks.contains(&(block as u32)))
+    }
+
+    fn tool(&self) -> Option<(ToolKind, Tier)> {
+        let tool = self.tool?;
+        tool_of(ItemStack::new(tool).item()?)
+    }
+
+    /// How fast the tool breaks the block, ignoring enchantments and effects.
+    fn tool_speed(&self, state: BlockState) -> f32 {
+        let Some((kind, tier)) = self.tool() else {
+            return 1.;
+        };
+        let name = block_name(state);
+        match kind {
+            ToolKind::Sword => {
+                if name == "cobweb" {
+                    15.
+                } else if self.has_tag(state, "minecraft:leaves")
+                    || matches!(
+                        name,
+                        "pumpkin" | "carved_pumpkin" | "jack_o_lantern" | "melon"
+                    )
+                {
+                    1.5
+                } else {
+                    1.
+                }
+            }
+            ToolKind::Shears => {
+                if name == "cobweb" || self.has_tag(state, "minecraft:leaves") {
+                    15.
+                } else if self.has_tag(state, "minecraft:wool") {
+                    5.
+                } else if name == "vine" || name == "glow_lichen" {
+                    2.
+                } else {
+                    1.
+                }
+            }
+            _ if self.has_tag(state, mineable_tag(kind)) => tier.speed,
+            _ => 1.,
+        }
+    }
+
+    /// Whether the block drops anything when it's broken with this tool.
+    fn has_correct_tool(&self, state: BlockState) -> bool {
+        if !state.requires_correct_tool_for_drops() {
+            return true;
+        }
+        let Some((kind, tier)) = self.tool() else {
+            return false;
+        };
+        let name = block_name(state);
+        match kind {
+            ToolKind::Sword => name == "cobweb",
+            ToolKind::Shears => matches!(name, "cobweb" | "redstone_wire" | "tripwire"),
+            _ => {
+                if (tier.level < 3 && self.has_tag(state, "minecraft:needs_diamond_tool"))
+                    || (tier.level < 2 && self.has_tag(state, "minecraft:needs_iron_tool"))
+                    || (tier.level < 1 && self.has_tag(state, "minecraft:needs_stone_tool"))
+                {
+                    return false;
+                }
+                self.has_tag(state, mineable_tag(kind))
+            }
+        }
+    }
+
+    fn destroy_speed(&self, state: BlockState) -> f32 {
+        let mut speed = self.tool_speed(state);
+        if speed > 1. {
+            let efficiency = self.tool.map_or(0, |tool| {
+                ItemStack::new(tool).enchantment_level(Enchantment::Efficiency)
+            });
+            if efficiency > 0 {
+                speed += (efficiency * efficiency + 1) as f32;
+            }
+        }
+
+        let haste = [MobEffect::Haste, MobEffect::ConduitPower]
+            .iter()
+            .filter_map(|effect| self.effects.get(effect))
+            .max();
+        if let Some(amplifier) = haste {
+            speed *= 1. + (*amplifier as f32 + 1.) * 0.2;
+        }
+        if let Some(amplifier) = self.effects.get(&MobEffect::MiningFatigue) {
+            speed *= match amplifier {
+                0 => 0.3,
+                1 => 0.09,
+                2 => 0.0027,
+                _ => 8.1e-4,
+            };
+        }
+
+        let aqua_affinity = self.helmet.map_or(false, |helmet| {
+            ItemStack::new(helmet).enchantment_level(Enchantment::AquaAffinity) > 0
+        });
+        if self.eyes_in_water && !aqua_affinity {
+            speed /= 5.;
+        }
+        if !self.on_ground {
+            speed /= 5.;
+        }
+        speed
+    }
+
+    /// How much of the block we break every tick, where 1 is the whole
+    /// block.
+    pub fn destroy_progress(&self, state: BlockState) -> f32 {
+        let destroy_time = state.destroy_time();
+        if destroy_time < 0. {
+            return 0.;
+        }
+        let divisor = if self.has_correct_tool(state) {
+            30.
+        } else {
+            100.
+        };
+        self.destroy_speed(state) / destroy_time / divisor
+    }
+}
+
+fn mineable_tag(kind: ToolKind) -> &'static str {
+    match kind {
+        ToolKind::Pickaxe => "minecraft:mineable/pickaxe",
+        ToolKind::Axe => "minecraft:mineable/axe",
+        ToolKind::Shovel => "minecraft:mineable/shovel",
+        _ => "minecraft:mineable/hoe",
+    }
+}
+
+/// How many ticks it takes to break a block when we break `progress` of it
+/// every tick. 0 means it breaks instantly, and `None` means it never
+/// breaks.
+pub(crate) fn ticks_for_progress(progress: f32) -> Option<u32> {
+    if progress >= 1. {
+        return Some(0);
+    }
+    if progress <= 0. {
+        return None;
+    }
+    // add it up the same way the client does so we round the same way
+    let mut total = 0.;
+    let mut ticks = 0;
+    while total < 1. {
+        total += progress;
+        ticks += 1;
+    }
+    Some(ticks)
+}
+
+/// The face of the block at `pos` that points the most towards `from`.
+pub(crate) fn face_towards(pos: &BlockPos, from: &Vec3) -> Direction {
+    let delta = from - &pos.center();
+    let (x, y, z) = (delta.x.abs(), delta.y.abs(), delta.z.abs());
+    if y >= x && y >= z {
+        if delta.y > 0. {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    } else if x >= z {
+        if delta.x > 0. {
+            Direction::East
+        } else {
+            Direction::West
+        }
+    } else if delta.z > 0. {
+        Direction::South
+    } else {
+        Direction::North
+    }
+}
+
+impl Client {
+    /// Where the player's eyes are.
+    pub fn eye_position(&self) -> Vec3 {
+        self.entity().pos().up(EYE_HEIGHT)
+    }
+
+    /// How much of a block the player breaks every tick with what they're
+    /// holding now.
+    fn destroy_progress(&self, state: BlockState) -> f32 {
+        let held_item = self.held_item();
+        let helmet_index = MenuLayout::PLAYER.range("armor").unwrap().start;
+        let inventory = self.inventory.read();
+        let helmet = match &inventory.slots[helmet_index] {
+            Slot::Present(helmet) => Some(helmet),
+            Slot::Empty => None,
+        };
+        let effects = self.effects.read();
+        let tags = self.block_tags.read();
+        let entity = self.entity();
+        let eye_block = self
+            .world()
+            .get_block_state(&BlockPos::from(&self.eye_position()));
+        let miner = Miner {
+            tool: match &held_item {
+                Slot::Present(tool) => Some(tool),
+                Slot::Empty => None,
+            },
+            helmet,
+            effects: &effects,
+            tags: &tags,
+            // waterlogged blocks count too, but we can't read block
+            // properties yet
+            eyes_in_water: eye_block.map_or(false, |block| {
+                matches!(
+                    block_name(block),
+                    "water"
+                        | "bubble_column"
+                        | "kelp"
+                        | "kelp_plant"
+                        | "seagrass"
+                        | "tall_seagrass"
+                )
+            }),
+            on_ground: entity.on_ground,
+        };
+        miner.destroy_progress(state)
+    }
+
+    /// How many ticks it'd take to break the block with what the player is
+    /// holding now. This is 0 if it breaks instantly, and `None` if it can't
+    /// be broken.
+    pub fn ticks_to_break(&self, state: BlockState) -> Option<u32> {
+        ticks_for_progress(self.destroy_progress(state))
+    }
+
+    /// Break the block at `pos` with whatever the player is holding,
+    /// swinging their arm the whole time. This finishes when the server
+    /// confirms that the block was broken, and fails if it doesn't or the
+    /// block changes while we're breaking it.
+    ///
+    /// The player should already be looking at the block and close enough to
+    /// reach it, or the server won't let them break it.
+    pub async fn dig(&self, pos: &BlockPos) -> Result<(), DigError> {
+        let state = self
+            .world()
+            .get_block_state(pos)
+            .ok_or(DigError::NotLoaded)?;
+        if state.is_air() {
+            return Err(DigError::Air);
+        }
+        if state.is_unbreakable() {
+            return Err(DigError::Unbreakable);
+        }
+        let direction = face_towards(pos, &self.eye_position());
+
+        let start_sequence = self.block_prediction.next_sequence();
+        self.write_packet(
+            ServerboundPlayerActionPacket {
+                action: Action::StartDestroyBlock,
+                pos: *pos,
+                direction,
+                sequence: start_sequence,
+            }
+            .get(),
+        )
+        .await?;
+        self.swing().await?;
+
+        // blocks that break instantly are broken by the start packet
+        let last_sequence = if self.destroy_progress(state) >= 1. {
+            start_sequence
+        } else {
+            let mut progress = 0.;
+            let mut interval = tokio::time::interval(TICK);
+            // the first tick of an interval completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if self.world().get_block_state(pos) != Some(state) {
+                    self.write_packet(
+                        ServerboundPlayerActionPacket {
+                            action: Action::AbortDestroyBlock,
+                            pos: *pos,
+                            direction,
+                            sequence: self.block_prediction.next_sequence(),
+                        }
+                        .get(),
+                    )
+                    .await?;
+                    return Err(DigError::BlockChanged);
+                }
+                progress += self.destroy_progress(state);
+                if progress >= 1. {
+                    break;
+                }
+                self.swing().await?;
+            }
+            let sequence = self.block_prediction.next_sequence();
+            self.write_packet(
+                ServerboundPlayerActionPacket {
+                    action: Action::StopDestroyBlock,
+                    pos: *pos,
+                    direction,
+                    sequence,
+                }
+                .get(),
+            )
+            .await?;
+            sequence
+        };
+
+        // the server sends the block's new state before the ack
+        if !self.block_prediction.wait_for_ack(last_sequence).await {
+            return Err(DigError::Timeout);
+        }
+        if self.world().get_block_state(pos) == Some(state) {
+            return Err(DigError::Rejected);
+        }
+        Ok(())
+    }
+
+    /// Swing the player's main hand.
+    pub async fn swing(&self) -> Result<(), io::Error> {
+        self.write_packet(
+            ServerboundSwingPacket {
+                hand: InteractionHand::MainHand,
+            }
+            .get(),
+        )
+        .await
+    }
+
+    pub(crate) fn handle_block_changed_ack(&self, packet: &ClientboundBlockChangedAckPacket) {
+        self.block_prediction.ack(packet.sequence as u32);
+    }
+
+    pub(crate) fn handle_update_tags(&self, packet: &ClientboundUpdateTagsPacket) {
+        let Some(block_tags) = ResourceLocation::new("minecraft:block")
+            .ok()
+            .and_then(|registry| packet.tags.get(&registry))
+        else {
+            return;
+        };
+        *self.block_tags.write() = block_tags
+            .iter()
+            .map(|tag| {
+                let blocks = tag.elements.iter().map(|&id| id as u32).collect();
+                (tag.name.clone(), blocks)
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{client_and_server, empty_world};
+    use azalea_buf::{McBufReadable, McBufVarWritable, McBufWritable};
+    use azalea_nbt::Tag;
+    use azalea_protocol::packets::game::ServerboundGamePacket;
+    use std::io::Cursor;
+
+    fn tags(tags: &[(&str, &[azalea_registry::Block])]) -> BlockTags {
+        tags.iter()
+            .map(|(name, blocks)| {
+                (
+                    ResourceLocation::new(name).unwrap(),
+                    blocks.iter().map(|&block| block as u32).collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// An item with one enchantment, decoded from the bytes a server would
+    /// send for it.
+    fn enchanted(item: Item, enchantment: &str, level: i16) -> SlotData {
+        let mut bytes = Vec::new();
+        (item as u32).var_write_into(&mut bytes).unwrap();
+        1u8.write_into(&mut bytes).unwrap();
+        // the root compound's type and its empty name
+        bytes.extend([10, 0, 0]);
+        Tag::Compound(
+            [(
+                "Enchantments".to_string(),
+                Tag::List(vec![Tag::Compound(
+                    [
+                        ("id".to_string(), Tag::String(enchantment.to_string())),
+                        ("lvl".to_string(), Tag::Short(level)),
+                    ]
+                    .into_iter()
+                    .collect(),
+                )]),
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .write_without_end(&mut bytes)
+        .unwrap();
+        SlotData::read_from(&mut Cursor::new(&bytes[..])).unwrap()
+    }
+
+    #[test]
+    fn test_break_ticks() {
+        let tags = tags(&[
+            (
+                "minecraft:mineable/pickaxe",
+                &[
+                    azalea_registry::Block::Stone,
+                    azalea_registry::Block::Obsidian,
+                ],
+            ),
+            (
+                "minecraft:needs_diamond_tool",
+                &[azalea_registry::Block::Obsidian],
+            ),
+        ]);
+        let effects = HashMap::new();
+        let pickaxe = SlotData::new(Item::IronPickaxe, 1);
+        let efficiency_pickaxe = enchanted(Item::DiamondPickaxe, "minecraft:efficiency", 5);
+        let aqua_affinity_helmet = enchanted(Item::DiamondHelmet, "minecraft:aqua_affinity", 1);
+        let mut miner = Miner {
+            tool: None,
+            helmet: None,
+            effects: &effects,
+            tags: &tags,
+            eyes_in_water: false,
+            on_ground: true,
+        };
+
+        // adding up the progress as a float is sometimes a tick slower than
+        // the times on the wiki
+        let ticks = |miner: &Miner, state| ticks_for_progress(miner.destroy_progress(state));
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(151));
+        assert_eq!(ticks(&miner, BlockState::Dirt), Some(15));
+        assert_eq!(ticks(&miner, BlockState::Dandelion), Some(0));
+        assert_eq!(ticks(&miner, BlockState::Bedrock), None);
+        miner.tool = Some(&pickaxe);
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(8));
+        // iron pickaxes can't mine obsidian
+        assert_eq!(ticks(&miner, BlockState::Obsidian), Some(834));
+        miner.tool = Some(&efficiency_pickaxe);
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(2));
+        assert_eq!(ticks(&miner, BlockState::Obsidian), Some(45));
+
+        miner.tool = Some(&pickaxe);
+        miner.on_ground = false;
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(38));
+        miner.eyes_in_water = true;
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(188));
+        miner.helmet = Some(&aqua_affinity_helmet);
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(38));
+
+        let effects = HashMap::from([(MobEffect::Haste, 1)]);
+        miner.effects = &effects;
+        miner.eyes_in_water = false;
+        miner.on_ground = true;
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(6));
+        let effects = HashMap::from([(MobEffect::MiningFatigue, 0)]);
+        miner.effects = &effects;
+        assert_eq!(ticks(&miner, BlockState::Stone), Some(25));
+    }
+
+    #[test]
+    fn test_face_towards() {
+        let pos = BlockPos::new(0, 64, 0);
+        assert_eq!(face_towards(&pos, &Vec3::new(0.5, 66., 0.5)), Direction::Up);
+        assert_eq!(
+            face_towards(&pos, &Vec3::new(3., 65., 0.5)),
+            Direction::East
+        );
+        assert_eq!(
+            face_towards(&pos, &Vec3::new(0., 64.5, -4.)),
+            Direction::North
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dig() {
+        let (client, mut server) = client_and_server();
+
+        // level with the player's eyes
+        let pos = BlockPos::new(1, 65, 0);
+        empty_world(&client, Vec3::new(0.5, 64., 0.5));
+        client.world.write().set_block_state(&pos, BlockState::Dirt);
+
+        let server_client = client.clone();
+        let server = tokio::spawn(async move {
+            let mut swings = 0;
+            let sequence = loop {
+                match server.read().await.unwrap() {
+                    ServerboundGamePacket::PlayerAction(p) => {
+                        assert_eq!(p.pos, pos);
+                        assert_eq!(p.direction, Direction::West);
+                        if let Action::StopDestroyBlock = p.action {
+                            break p.sequence;
+                        }
+                        assert!(matches!(p.action, Action::StartDestroyBlock));
+                        assert_eq!(p.sequence, 1);
+                    }
+                    ServerboundGamePacket::Swing(_) => swings += 1,
+                    p => panic!("Unexpected packet {p:?}"),
+                }
+            };
+            // dirt takes 15 ticks to break by hand, and we swing when we
+            // start and on every tick but the last
+            assert_eq!(swings, 15);
+            assert_eq!(sequence, 2);
+            server_client
+                .world
+                .write()
+                .set_block_state(&pos, BlockState::Air);
+            server_client.handle_block_changed_ack(&ClientboundBlockChangedAckPacket {
+                sequence: sequence as i32,
+            });
+        });
+
+        assert_eq!(client.ticks_to_break(BlockState::Dirt), Some(15));
+        client.dig(&pos).await.unwrap();
+        server.await.unwrap();
+        assert_eq!(client.world().get_block_state(&pos), Some(BlockState::Air));
+        assert!(matches!(client.dig(&pos).await, Err(DigError::Air)));
+    }
+}
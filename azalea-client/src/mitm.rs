@@ -0,0 +1,459 @@
+//! A man-in-the-middle proxy that can read and modify the packets sent between
+//! a client and a server.
+//!
+//! The client connects to us and logs in with our own key pair, then we log in
+//! to the target server like a normal azalea [`Client`] would. Since both
+//! connections are ours, we can decrypt and decode every game packet before
+//! passing it along.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use azalea_client::mitm::{Mitm, MitmHandler, MitmOptions};
+//! use azalea_protocol::packets::game::ServerboundGamePacket;
+//! # use azalea_protocol::packets::status::clientbound_status_response_packet::ClientboundStatusResponsePacket;
+//! # fn status(_: &azalea_protocol::packets::handshake::client_intention_packet::ClientIntentionPacket) -> ClientboundStatusResponsePacket { todo!() }
+//!
+//! struct Logger;
+//! impl MitmHandler for Logger {
+//!     fn serverbound(&self, packet: ServerboundGamePacket) -> Option<ServerboundGamePacket> {
+//!         println!("client -> server: {packet:?}");
+//!         Some(packet)
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let mitm = Mitm::bind(
+//!         "127.0.0.1:25566",
+//!         status,
+//!         MitmOptions::new("localhost:25565".try_into().unwrap()),
+//!         Logger,
+//!     )
+//!     .await?;
+//!     mitm.run().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Account, Client, JoinError};
+use azalea_buf::remap::with_remapper;
+use azalea_protocol::{
+    connect::{Connection, ConnectionError, ReadConnection, WriteConnection},
+    packets::{
+        game::{
+            clientbound_disconnect_packet::ClientboundDisconnectPacket, ClientboundGamePacket,
+            ServerboundGamePacket,
+        },
+        handshake::ClientIdentifier,
+        ProtocolPacket,
+    },
+    read::{deserialize_packet, ReadPacketError},
+    resolver,
+    server::{JoinedPlayer, Listener, LoginOptions, ServerError, StatusProvider},
+    ServerAddress,
+};
+use log::{debug, error, info, warn};
+use std::{fmt::Debug, io::Cursor, net::SocketAddr, sync::Arc};
+use thiserror::Error;
+use tokio::net::ToSocketAddrs;
+
+/// Something that gets to see (and change) every game packet that goes through
+/// a [`Mitm`].
+///
+/// Return the packet (or a different one) to send it along, or `None` to drop
+/// it. The default implementations pass every packet through unchanged.
+pub trait MitmHandler: Send + Sync + 'static {
+    /// Called for every packet the server sends to the client.
+    fn clientbound(&self, packet: ClientboundGamePacket) -> Option<ClientboundGamePacket> {
+        Some(packet)
+    }
+    /// Called for every packet the client sends to the server.
+    fn serverbound(&self, packet: ServerboundGamePacket) -> Option<ServerboundGamePacket> {
+        Some(packet)
+    }
+}
+
+/// Where and how a [`Mitm`] should connect to the real server.
+#[derive(Clone, Debug)]
+pub struct MitmOptions {
+    /// The server that players are sent to.
+    pub target: ServerAddress,
+    /// The account we log in to the target server with. If this is `None`, an
+    /// offline account with the same username as the connecting player is
+    /// used.
+    pub account: Option<Account>,
+    pub identifier: ClientIdentifier,
+    /// How players that connect to us are logged in. Online mode is off by
+    /// default, since the player's own session can't be reused for the target
+    /// server anyways.
+    pub login: LoginOptions,
+}
+
+impl MitmOptions {
+    /// Proxy to the given server with offline accounts.
+    pub fn new(target: ServerAddress) -> Self {
+        Self {
+            target,
+            account: None,
+            identifier: ClientIdentifier::Vanilla,
+            login: LoginOptions {
+                online_mode: false,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MitmError {
+    #[error("{0}")]
+    Server(#[from] ServerError),
+    #[error("{0}")]
+    Resolver(#[from] resolver::ResolverError),
+    #[error("{0}")]
+    Connection(#[from] ConnectionError),
+    #[error("{0}")]
+    Join(#[from] JoinError),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A proxy that decrypts and decodes the packets sent between players and a
+/// server.
+pub struct Mitm {
+    listener: Listener,
+    options: Arc<MitmOptions>,
+    handler: Arc<dyn MitmHandler>,
+}
+
+impl Mitm {
+    /// Start listening for players on the given address.
+    pub async fn bind(
+        address: impl ToSocketAddrs,
+        status: impl StatusProvider,
+        options: MitmOptions,
+        handler: impl MitmHandler,
+    ) -> Result<Self, MitmError> {
+        let listener = Listener::bind(address, status, options.login.clone()).await?;
+        Ok(Self {
+            listener,
+            options: Arc::new(options),
+            handler: Arc::new(handler),
+        })
+    }
+
+    /// The address that players should connect to.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept players forever, proxying each one in a new task.
+    pub async fn run(self) -> Result<(), MitmError> {
+        loop {
+            let incoming = self.listener.accept().await?;
+            let options = self.options.clone();
+            let handler = self.handler.clone();
+            tokio::spawn(async move {
+                let address = incoming.address;
+                let player = match incoming.process().await {
+                    Ok(Some(player)) => player,
+                    Ok(None) => return,
+                    Err(e) => {
+                        warn!("Error while {address} was connecting: {e}");
+                        return;
+                    }
+                };
+                if let Err(e) = handle_player(player, &options, handler).await {
+                    error!("Error proxying {address}: {e}");
+                }
+            });
+        }
+    }
+}
+
+/// Log in to the target server for a player that just joined us and relay
+/// packets between them until one side disconnects. We connect to the server
+/// with the same protocol version as the player.
+pub async fn handle_player(
+    player: JoinedPlayer,
+    options: &MitmOptions,
+    handler: Arc<dyn MitmHandler>,
+) -> Result<(), MitmError> {
+    let account = options
+        .account
+        .clone()
+        .unwrap_or_else(|| Account::offline(&player.profile.name));
+
+    let upstream = async {
+        let resolved_address = resolver::resolve_address(&options.target).await?;
+        let conn = Connection::new(&resolved_address).await?;
+        let (upstream, _) = Client::handshake(
+            conn,
+            options.identifier,
+            player.conn.version(),
+            &account,
+            &options.target,
+        )
+        .await?;
+        Ok::<_, MitmError>((upstream, resolved_address))
+    };
+    let (upstream, resolved_address) = match upstream.await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            // tell the player why instead of just closing the connection
+            let reason = match &e {
+                MitmError::Join(JoinError::Disconnect { reason }) => reason.clone(),
+                e => format!("Couldn't connect to the server: {e}").into(),
+            };
+            let mut player_conn = player.conn;
+            let _ = player_conn
+                .write(ClientboundDisconnectPacket { reason }.get())
+                .await;
+            return Err(e);
+        }
+    };
+
+    info!("Proxying {} to {}", player.profile.name, resolved_address);
+    relay(player.conn, upstream, handler).await;
+    info!("{} left the game", player.profile.name);
+
+    Ok(())
+}
+
+/// Pass packets between the player and the server, running them through the
+/// handler. Packets that can't be decoded skip the handler and are passed along
+/// unchanged. This returns when either side closes the connection.
+pub async fn relay(
+    player: Connection<ServerboundGamePacket, ClientboundGamePacket>,
+    server: Connection<ClientboundGamePacket, ServerboundGamePacket>,
+    handler: Arc<dyn MitmHandler>,
+) {
+    let (player_read, mut player_write) = player.into_split();
+    let (server_read, mut server_write) = server.into_split();
+
+    let serverbound_handler = handler.clone();
+    let client_to_server = relay_one_way(player_read, &mut server_write, move |p| {
+        serverbound_handler.serverbound(p)
+    });
+    let server_to_client = relay_one_way(server_read, &mut player_write, move |p| {
+        handler.clientbound(p)
+    });
+
+    // when one direction stops, the other one is useless so we stop both
+    tokio::select! {
+        _ = client_to_server => {}
+        _ = server_to_client => {}
+    }
+
+    let _ = server_write.shutdown().await;
+    let _ = player_write.shutdown().await;
+}
+
+async fn relay_one_way<R, W>(
+    mut reader: ReadConnection<R>,
+    writer: &mut WriteConnection<W>,
+    handle: impl Fn(R) -> Option<W>,
+) where
+    R: ProtocolPacket + Debug,
+    W: ProtocolPacket + Debug,
+{
+    loop {
+        let data = match reader.read_raw().await {
+            Ok(data) => data,
+            Err(e) => match *e {
+                ReadPacketError::ConnectionClosed => return,
+                e => {
+                    error!("Error reading packet: {e}");
+                    return;
+                }
+            },
+        };
+        let packet = with_remapper(reader.remapper.read().clone(), || {
+            deserialize_packet::<R>(&mut Cursor::new(&data[..]), reader.version)
+        });
+        let result = match packet {
+            Ok(packet) => match handle(packet) {
+                Some(packet) => writer.write(packet).await,
+                None => Ok(()),
+            },
+            // the handler can't see it, but the other side might understand it
+            Err(e) => {
+                debug!("Forwarding packet that couldn't be decoded: {e}");
+                writer.write_raw(data).await
+            }
+        };
+        if let Err(e) = result {
+            error!("Error writing packet: {e}");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_protocol::packets::{
+        game::{
+            clientbound_keep_alive_packet::ClientboundKeepAlivePacket,
+            clientbound_set_time_packet::ClientboundSetTimePacket,
+            serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
+        },
+        handshake::client_intention_packet::ClientIntentionPacket,
+        status::clientbound_status_response_packet::{
+            ClientboundStatusResponsePacket, Players, Version,
+        },
+        ProtocolVersion, PROTOCOL_VERSION,
+    };
+
+    fn status(_intent: &ClientIntentionPacket) -> ClientboundStatusResponsePacket {
+        ClientboundStatusResponsePacket {
+            description: "test".into(),
+            favicon: None,
+            players: Players {
+                max: 1,
+                online: 0,
+                sample: Vec::new(),
+            },
+            version: Version {
+                name: "1.19.3".to_string(),
+                protocol: PROTOCOL_VERSION as i32,
+            },
+            previews_chat: None,
+            enforces_secure_chat: None,
+            forge: None,
+        }
+    }
+
+    /// Adds one to every keep alive id and drops time updates.
+    struct TestHandler;
+    impl MitmHandler for TestHandler {
+        fn clientbound(&self, packet: ClientboundGamePacket) -> Option<ClientboundGamePacket> {
+            match packet {
+                ClientboundGamePacket::KeepAlive(p) => {
+                    Some(ClientboundKeepAlivePacket { id: p.id + 1 }.get())
+                }
+                ClientboundGamePacket::SetTime(_) => None,
+                p => Some(p),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mitm() {
+        let login_options = LoginOptions {
+            online_mode: false,
+            ..Default::default()
+        };
+        let server = Listener::bind("127.0.0.1:0", status, login_options)
+            .await
+            .unwrap();
+        let mitm = Mitm::bind(
+            "127.0.0.1:0",
+            status,
+            MitmOptions::new(server.local_addr().unwrap().into()),
+            TestHandler,
+        )
+        .await
+        .unwrap();
+        let mitm_address = mitm.local_addr().unwrap();
+        tokio::spawn(mitm.run());
+
+        let client = tokio::spawn(async move {
+            let conn = Connection::new(&mitm_address).await.unwrap();
+            Client::handshake(
+                conn,
+                ClientIdentifier::Vanilla,
+                ProtocolVersion::LATEST,
+                &Account::offline("bot"),
+                &mitm_address.into(),
+            )
+            .await
+            .unwrap()
+        });
+
+        let mut player = server
+            .accept()
+            .await
+            .unwrap()
+            .process()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(player.profile.name, "bot");
+        let (mut client, _) = client.await.unwrap();
+
+        player
+            .conn
+            .write(
+                ClientboundSetTimePacket {
+                    game_time: 0,
+                    day_time: 0,
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+        player
+            .conn
+            .write(ClientboundKeepAlivePacket { id: 1 }.get())
+            .await
+            .unwrap();
+        match client.read().await.unwrap() {
+            ClientboundGamePacket::KeepAlive(p) => assert_eq!(p.id, 2),
+            p => panic!("unexpected packet {p:?}"),
+        }
+
+        client
+            .write(ServerboundKeepAlivePacket { id: 2 }.get())
+            .await
+            .unwrap();
+        match player.conn.read().await.unwrap() {
+            ServerboundGamePacket::KeepAlive(p) => assert_eq!(p.id, 2),
+            p => panic!("unexpected packet {p:?}"),
+        }
+
+        // packets that can't be decoded are passed along as they are
+        let unknown = vec![0x7f, 1, 2, 3];
+        player.conn.writer.write_raw(unknown.clone()).await.unwrap();
+        assert_eq!(client.reader.read_raw().await.unwrap(), unknown);
+    }
+
+    #[tokio::test]
+    async fn test_mitm_unreachable_server() {
+        // nothing is listening on the port after the listener is dropped
+        let target = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mitm = Mitm::bind(
+            "127.0.0.1:0",
+            status,
+            MitmOptions::new(target.into()),
+            TestHandler,
+        )
+        .await
+        .unwrap();
+        let mitm_address = mitm.local_addr().unwrap();
+        tokio::spawn(mitm.run());
+
+        let conn = Connection::new(&mitm_address).await.unwrap();
+        let (mut client, _) = Client::handshake(
+            conn,
+            ClientIdentifier::Vanilla,
+            ProtocolVersion::LATEST,
+            &Account::offline("bot"),
+            &mitm_address.into(),
+        )
+        .await
+        .unwrap();
+        match client.read().await.unwrap() {
+            ClientboundGamePacket::Disconnect(p) => assert!(p
+                .reason
+                .to_string()
+                .starts_with("Couldn't connect to the server")),
+            p => panic!("unexpected packet {p:?}"),
+        }
+    }
+}
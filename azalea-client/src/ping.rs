@@ -2,19 +2,27 @@
 
 use azalea_protocol::{
     connect::{Connection, ConnectionError},
+    legacy_ping::{self, LegacyPingError, LegacyPingRequest},
     packets::{
         handshake::{client_intention_packet::ClientIntentionPacket, ClientIdentifier},
         status::{
             clientbound_status_response_packet::ClientboundStatusResponsePacket,
+            serverbound_ping_request_packet::ServerboundPingRequestPacket,
             serverbound_status_request_packet::ServerboundStatusRequestPacket,
             ClientboundStatusPacket,
         },
-        ConnectionProtocol, PROTOCOL_VERSION,
+        ConnectionProtocol, ProtocolVersion,
     },
-    resolver, ServerAddress,
+    resolver::{self, Resolver},
+    ServerAddress,
+};
+use futures::{stream, StreamExt};
+use std::{
+    io,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use std::io;
 use thiserror::Error;
+use tokio::{io::AsyncWriteExt, net::TcpStream};
 
 #[derive(Error, Debug)]
 pub enum PingError {
@@ -28,9 +36,51 @@ pub enum PingError {
     WritePacket(#[from] io::Error),
     #[error("The given address could not be parsed into a ServerAddress")]
     InvalidAddress,
+    #[error("The server didn't respond in time")]
+    Timeout,
+    #[error("Unexpected packet {0:?}")]
+    UnexpectedPacket(Box<ClientboundStatusPacket>),
+    #[error("{0}")]
+    LegacyPing(#[from] LegacyPingError),
+}
+
+/// Options for [`ping_server`] and [`ping_servers`].
+#[derive(Clone, Debug)]
+pub struct PingOptions {
+    /// The client we pretend to be in the handshake.
+    pub identifier: ClientIdentifier,
+    /// The protocol version we pretend to have. The server usually puts the
+    /// version it wants in its response.
+    pub version: ProtocolVersion,
+    /// How long the whole ping (including resolving the address) can take
+    /// before we give up. `None` means we wait forever.
+    pub timeout: Option<Duration>,
+    /// How server addresses are resolved. When pinging many servers, the
+    /// cache is shared between them.
+    pub resolver: Resolver,
+}
+
+impl Default for PingOptions {
+    fn default() -> Self {
+        Self {
+            identifier: ClientIdentifier::Vanilla,
+            version: ProtocolVersion::LATEST,
+            timeout: Some(Duration::from_secs(10)),
+            resolver: Resolver::default(),
+        }
+    }
+}
+
+/// What a server responded with when we pinged it.
+#[derive(Clone, Debug)]
+pub struct PingResponse {
+    pub status: ClientboundStatusResponsePacket,
+    /// The time between sending the ping request and receiving the pong, like
+    /// the ping shown in the vanilla server list.
+    pub latency: Duration,
 }
 
-/// Ping a Minecraft server.
+/// Ping a Minecraft server and measure the latency.
 ///
 /// # Examples
 ///
@@ -39,28 +89,119 @@ pub enum PingError {
 ///
 /// #[tokio::main]
 /// async fn main() {
-///     let response = ping::ping_server("play.hypixel.net").await.unwrap();
-///     println!("{}", response.description.to_ansi());
+///     let response = ping::ping_server("play.hypixel.net", &ping::PingOptions::default())
+///         .await
+///         .unwrap();
+///     println!(
+///         "{} ({}ms)",
+///         response.status.description.to_ansi(),
+///         response.latency.as_millis()
+///     );
 /// }
 /// ```
 pub async fn ping_server(
     address: impl TryInto<ServerAddress>,
-    identifier: ClientIdentifier,
-) -> Result<ClientboundStatusResponsePacket, PingError> {
+    options: &PingOptions,
+) -> Result<PingResponse, PingError> {
+    let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
+
+    match options.timeout {
+        Some(timeout) => tokio::time::timeout(timeout, ping_address(&address, options))
+            .await
+            .map_err(|_| PingError::Timeout)?,
+        None => ping_address(&address, options).await,
+    }
+}
+
+/// Ping many servers at once, with at most `concurrency` pings happening at
+/// the same time. The results are in the same order as the addresses.
+///
+/// ```rust,no_run
+/// use azalea_client::ping;
+///
+/// # async fn example() {
+/// let addresses = ["play.hypixel.net", "mc.hypixel.net", "localhost"];
+/// let results = ping::ping_servers(addresses, &ping::PingOptions::default(), 64).await;
+/// for (address, result) in addresses.iter().zip(results) {
+///     match result {
+///         Ok(response) => println!("{address}: {}ms", response.latency.as_millis()),
+///         Err(e) => println!("{address}: {e}"),
+///     }
+/// }
+/// # }
+/// ```
+pub async fn ping_servers<A: TryInto<ServerAddress>>(
+    addresses: impl IntoIterator<Item = A>,
+    options: &PingOptions,
+    concurrency: usize,
+) -> Vec<Result<PingResponse, PingError>> {
+    stream::iter(addresses)
+        .map(|address| ping_server(address, options))
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Ping a server with the ping from before Minecraft 1.7. Use this for
+/// servers that are too old to understand [`ping_server`].
+///
+/// The response is converted into the same shape as a normal status response,
+/// see [`legacy_ping::parse_response`] for what's missing. The latency is the
+/// time it took for the server to reply to the request, and
+/// [`PingOptions::version`] is ignored.
+pub async fn ping_server_legacy(
+    address: impl TryInto<ServerAddress>,
+    options: &PingOptions,
+) -> Result<PingResponse, PingError> {
     let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
 
-    let resolved_address = resolver::resolve_address(&address).await?;
+    match options.timeout {
+        Some(timeout) => tokio::time::timeout(timeout, ping_address_legacy(&address, options))
+            .await
+            .map_err(|_| PingError::Timeout)?,
+        None => ping_address_legacy(&address, options).await,
+    }
+}
+
+async fn ping_address_legacy(
+    address: &ServerAddress,
+    options: &PingOptions,
+) -> Result<PingResponse, PingError> {
+    let resolved_address = options.resolver.resolve_address(address).await?;
+    let mut stream = TcpStream::connect(resolved_address).await?;
+
+    let request = LegacyPingRequest::V1_6 {
+        protocol_version: legacy_ping::LEGACY_PROTOCOL_VERSION,
+        hostname: address.host.clone(),
+        port: address.port,
+    };
+    let start = Instant::now();
+    stream.write_all(&request.serialize()).await?;
+    let status = legacy_ping::read_response(&mut stream).await?;
+    Ok(PingResponse {
+        status,
+        latency: start.elapsed(),
+    })
+}
+
+async fn ping_address(
+    address: &ServerAddress,
+    options: &PingOptions,
+) -> Result<PingResponse, PingError> {
+    let resolved_address = options.resolver.resolve_address(address).await?;
 
     let mut conn = Connection::new(&resolved_address).await?;
+    conn.set_version(options.version);
 
     // send the client intention packet and switch to the status state
     conn.write(
         ClientIntentionPacket {
-            protocol_version: PROTOCOL_VERSION,
+            protocol_version: options.version.id(),
             hostname: address.host.clone(),
             port: address.port,
             intention: ConnectionProtocol::Status,
-            identifier,
+            identifier: options.identifier,
+            forwarding: None,
         }
         .get(),
     )
@@ -69,15 +210,136 @@ pub async fn ping_server(
 
     // send the empty status request packet
     conn.write(ServerboundStatusRequestPacket {}.get()).await?;
+    let status = match conn.read().await? {
+        ClientboundStatusPacket::StatusResponse(p) => p,
+        packet => return Err(PingError::UnexpectedPacket(Box::new(packet))),
+    };
+
+    // the server echoes back whatever number we send, the vanilla client uses
+    // the current time
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let start = Instant::now();
+    conn.write(ServerboundPingRequestPacket { time }.get())
+        .await?;
+    match conn.read().await? {
+        ClientboundStatusPacket::PongResponse(p) if p.time == time => Ok(PingResponse {
+            status,
+            latency: start.elapsed(),
+        }),
+        packet => Err(PingError::UnexpectedPacket(Box::new(packet))),
+    }
+}
 
-    let packet = conn.read().await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_protocol::{
+        packets::status::clientbound_status_response_packet::{Players, Version},
+        server::{Listener, LoginOptions},
+    };
+    use std::net::SocketAddr;
 
-    loop {
-        match packet {
-            ClientboundStatusPacket::StatusResponse(p) => return Ok(p),
-            ClientboundStatusPacket::PongResponse(_) => {
-                // we should never get this packet since we didn't send a ping
+    async fn status_server() -> SocketAddr {
+        let listener = Listener::bind(
+            "127.0.0.1:0",
+            |_intent: &_| ClientboundStatusResponsePacket {
+                description: "test".into(),
+                favicon: None,
+                players: Players {
+                    max: 20,
+                    online: 1,
+                    sample: Vec::new(),
+                },
+                version: Version {
+                    name: ProtocolVersion::LATEST.name().to_string(),
+                    protocol: ProtocolVersion::LATEST.id() as i32,
+                },
+                previews_chat: None,
+                enforces_secure_chat: None,
+                forge: None,
+            },
+            LoginOptions::default(),
+        )
+        .await
+        .unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok(incoming) = listener.accept().await {
+                tokio::spawn(incoming.process());
             }
-        }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let address = status_server().await;
+        let response = ping_server(address, &PingOptions::default()).await.unwrap();
+        assert_eq!(response.status.players.online, 1);
+        assert!(response.latency < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_ping_legacy() {
+        let address = status_server().await;
+        let response = ping_server_legacy(address, &PingOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(response.status.description.to_string(), "test");
+        assert_eq!(response.status.players.max, 20);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        // accepts connections but never responds
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut streams = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                streams.push(stream);
+            }
+        });
+
+        let options = PingOptions {
+            timeout: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ping_server(address, &options).await,
+            Err(PingError::Timeout)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ping_servers() {
+        let server = status_server().await;
+        // bind and immediately drop a listener to get a port nobody is on
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let addresses = vec![
+            server.to_string(),
+            closed.to_string(),
+            String::new(),
+            server.to_string(),
+        ];
+        let results = ping_servers(
+            addresses.iter().map(String::as_str),
+            &PingOptions::default(),
+            2,
+        )
+        .await;
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(PingError::Connection(_))));
+        assert!(matches!(results[2], Err(PingError::InvalidAddress)));
+        assert!(results[3].is_ok());
     }
 }
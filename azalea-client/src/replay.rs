@@ -0,0 +1,347 @@
+//! Record the packets a client receives and replay them later without a
+//! server.
+//!
+//! A recording starts with the magic bytes `AZRP`, a format version byte, and
+//! the protocol version as a varint. The rest of the file is zlib compressed
+//! and is a list of packets, each of which is:
+//!
+//! - the number of game ticks since the previous packet (varint)
+//! - the [`ConnectionProtocol`] the packet was received in (varint)
+//! - the length of the packet (varint)
+//! - the packet id and data, already decrypted and decompressed
+
+use crate::{Client, Event};
+use azalea_auth::game_profile::GameProfile;
+use azalea_buf::{BufReadError, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable};
+use azalea_protocol::{
+    connect::Connection,
+    packets::{
+        game::{ClientboundGamePacket, ServerboundGamePacket},
+        ConnectionProtocol, ProtocolPacket, ProtocolVersion,
+    },
+    read::{deserialize_packet, ReadPacketError},
+};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use log::error;
+use std::{
+    fmt::Debug,
+    io::{self, Cursor, Read, Write},
+};
+use thiserror::Error;
+use tokio::sync::mpsc::{self, Receiver};
+
+const MAGIC: &[u8; 4] = b"AZRP";
+const FORMAT_VERSION: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Read(#[from] BufReadError),
+    #[error("Not a recording (the magic bytes are wrong)")]
+    InvalidMagic,
+    #[error("Unsupported recording format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("The recording uses unsupported protocol version {0}")]
+    UnsupportedProtocolVersion(u32),
+}
+
+/// Writes packets to a recording as they're received. You usually want to
+/// use [`Client::start_recording`] instead of using this directly.
+pub struct Recorder {
+    writer: ZlibEncoder<Box<dyn Write + Send>>,
+    /// The number of ticks that have happened since we started recording.
+    tick: u32,
+    /// The tick that the last packet was recorded in.
+    last_packet_tick: u32,
+}
+
+impl Recorder {
+    /// Start a new recording of packets sent with the given protocol
+    /// version. This writes the header immediately.
+    pub fn new(
+        mut writer: impl Write + Send + 'static,
+        version: ProtocolVersion,
+    ) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        FORMAT_VERSION.write_into(&mut writer)?;
+        version.id().var_write_into(&mut writer)?;
+        Ok(Self {
+            writer: ZlibEncoder::new(Box::new(writer), Compression::default()),
+            tick: 0,
+            last_packet_tick: 0,
+        })
+    }
+
+    /// Mark that a game tick has passed.
+    pub fn tick(&mut self) {
+        self.tick += 1;
+    }
+
+    /// Write the bytes of a packet (including the id) to the recording.
+    pub fn record(&mut self, state: ConnectionProtocol, data: &[u8]) -> io::Result<()> {
+        (self.tick - self.last_packet_tick).var_write_into(&mut self.writer)?;
+        self.last_packet_tick = self.tick;
+        state.write_into(&mut self.writer)?;
+        (data.len() as u32).var_write_into(&mut self.writer)?;
+        self.writer.write_all(data)
+    }
+
+    /// Finish the compressed stream and flush everything to the writer.
+    pub fn finish(self) -> io::Result<()> {
+        self.writer.finish()?.flush()
+    }
+}
+
+/// A packet that was read from a recording.
+#[derive(Clone, Debug)]
+pub struct RecordedPacket {
+    /// The number of game ticks that happened between the previous packet and
+    /// this one.
+    pub ticks: u32,
+    pub state: ConnectionProtocol,
+    /// The packet id and data.
+    pub data: Vec<u8>,
+}
+
+impl RecordedPacket {
+    /// Parse the packet. The type has to match [`Self::state`], and the
+    /// version should be the one from the [`Replay`].
+    pub fn deserialize<P: ProtocolPacket + Debug>(
+        &self,
+        version: ProtocolVersion,
+    ) -> Result<P, Box<ReadPacketError>> {
+        deserialize_packet(&mut Cursor::new(&self.data[..]), version)
+    }
+}
+
+/// A recording that was read into memory.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    /// The protocol version of the server that the packets came from.
+    pub version: ProtocolVersion,
+    pub packets: Vec<RecordedPacket>,
+}
+
+impl Replay {
+    /// Read a whole recording.
+    pub fn read_from(mut reader: impl Read) -> Result<Self, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ReplayError::InvalidMagic);
+        }
+        let mut header = [0; 1];
+        reader.read_exact(&mut header)?;
+        if header[0] != FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header[0]));
+        }
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest)?;
+        let mut rest = Cursor::new(&rest[..]);
+        let protocol_version = u32::var_read_from(&mut rest)?;
+        let version = ProtocolVersion::from_id(protocol_version)
+            .ok_or(ReplayError::UnsupportedProtocolVersion(protocol_version))?;
+
+        let mut data = Vec::new();
+        ZlibDecoder::new(&mut rest).read_to_end(&mut data)?;
+        let mut data = Cursor::new(&data[..]);
+
+        let mut packets = Vec::new();
+        while (data.position() as usize) < data.get_ref().len() {
+            let ticks = u32::var_read_from(&mut data)?;
+            let state = ConnectionProtocol::read_from(&mut data)?;
+            let length = u32::var_read_from(&mut data)? as usize;
+            let mut packet = vec![0; length];
+            data.read_exact(&mut packet)?;
+            packets.push(RecordedPacket {
+                ticks,
+                state,
+                data: packet,
+            });
+        }
+
+        Ok(Self { version, packets })
+    }
+}
+
+impl Client {
+    /// Start writing every packet we receive to the given writer. If we were
+    /// already recording, the old recording is finished first.
+    ///
+    /// ```rust,no_run
+    /// # fn example(bot: azalea_client::Client) -> std::io::Result<()> {
+    /// let file = std::io::BufWriter::new(std::fs::File::create("session.azrp")?);
+    /// bot.start_recording(file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_recording(&self, writer: impl Write + Send + 'static) -> io::Result<()> {
+        let recorder = Recorder::new(writer, self.version)?;
+        if let Some(old_recorder) = self.recorder.lock().replace(recorder) {
+            old_recorder.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Stop recording and flush the recording. This does nothing if we
+    /// weren't recording.
+    pub fn stop_recording(&self) -> io::Result<()> {
+        if let Some(recorder) = self.recorder.lock().take() {
+            recorder.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Create a client that isn't connected to a server and handle all the
+    /// game packets from the recording, running game ticks in between them
+    /// like they happened when it was recorded.
+    ///
+    /// Packets the client sends are thrown away.
+    pub async fn replay(replay: Replay, profile: GameProfile) -> (Client, Receiver<Event>) {
+        let mut conn = null_connection();
+        conn.set_version(replay.version);
+        let (tx, rx) = mpsc::channel(1);
+        let client = Client::new(profile, conn, None);
+
+        tx.send(Event::Init).await.expect("Failed to send event");
+
+        let mut replay_client = client.clone();
+        client.tasks.lock().push(tokio::spawn(async move {
+            for packet in replay.packets {
+                for _ in 0..packet.ticks {
+                    Client::game_tick(&mut replay_client, &tx).await;
+                }
+                if packet.state != ConnectionProtocol::Game {
+                    continue;
+                }
+                match packet.deserialize::<ClientboundGamePacket>(replay.version) {
+                    Ok(packet) => {
+                        for packet in replay_client.intercept_inbound(packet) {
+                            if let Err(e) = Client::handle(&packet, &replay_client, &tx).await {
+                                error!("Error handling replayed packet: {e}");
+                            }
+                        }
+                    }
+                    Err(e) => error!("Error reading replayed packet: {e}"),
+                }
+            }
+        }));
+
+        (client, rx)
+    }
+}
+
+/// A connection to nowhere. Nothing is ever read from it and everything
+/// written to it is discarded.
+fn null_connection() -> Connection<ClientboundGamePacket, ServerboundGamePacket> {
+    let (stream, mut peer) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut peer, &mut tokio::io::sink()).await;
+    });
+    Connection::from_stream(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_protocol::packets::game::{
+        clientbound_keep_alive_packet::ClientboundKeepAlivePacket,
+        clientbound_set_time_packet::ClientboundSetTimePacket,
+    };
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    /// A writer that we can still read from after giving it to a recorder.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn record(packets: &[(u32, ClientboundGamePacket)]) -> Vec<u8> {
+        let buffer = SharedBuffer::default();
+        let mut recorder = Recorder::new(buffer.clone(), ProtocolVersion::LATEST).unwrap();
+        for (ticks, packet) in packets {
+            for _ in 0..*ticks {
+                recorder.tick();
+            }
+            let mut data = Vec::new();
+            packet.id().var_write_into(&mut data).unwrap();
+            packet.write(&mut data).unwrap();
+            recorder.record(ConnectionProtocol::Game, &data).unwrap();
+        }
+        recorder.finish().unwrap();
+        let data = buffer.0.lock().clone();
+        data
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let data = record(&[
+            (0, ClientboundKeepAlivePacket { id: 1 }.get()),
+            (
+                3,
+                ClientboundSetTimePacket {
+                    game_time: 2,
+                    day_time: 3,
+                }
+                .get(),
+            ),
+        ]);
+
+        let replay = Replay::read_from(&data[..]).unwrap();
+        assert_eq!(replay.version, ProtocolVersion::LATEST);
+        assert_eq!(replay.packets.len(), 2);
+        assert_eq!(replay.packets[0].ticks, 0);
+        assert_eq!(replay.packets[1].ticks, 3);
+        match replay.packets[1].deserialize(replay.version).unwrap() {
+            ClientboundGamePacket::SetTime(p) => assert_eq!(p.day_time, 3),
+            p => panic!("unexpected packet {p:?}"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        assert!(matches!(
+            Replay::read_from(&b"nope\x01"[..]),
+            Err(ReplayError::InvalidMagic)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replay_client() {
+        let data = record(&[
+            (0, ClientboundKeepAlivePacket { id: 1 }.get()),
+            (
+                0,
+                ClientboundSetTimePacket {
+                    game_time: 2,
+                    day_time: 3,
+                }
+                .get(),
+            ),
+        ]);
+        let replay = Replay::read_from(&data[..]).unwrap();
+
+        let (_client, mut rx) =
+            Client::replay(replay, GameProfile::new(Uuid::nil(), "bot".to_string())).await;
+
+        assert!(matches!(rx.recv().await, Some(Event::Init)));
+        match rx.recv().await {
+            Some(Event::Packet(p)) => assert!(matches!(*p, ClientboundGamePacket::KeepAlive(_))),
+            e => panic!("unexpected event {e:?}"),
+        }
+        match rx.recv().await {
+            Some(Event::Packet(p)) => assert!(matches!(*p, ClientboundGamePacket::SetTime(_))),
+            e => panic!("unexpected event {e:?}"),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Traffic statistics for clients.
+
+use crate::Client;
+use azalea_protocol::{
+    packets::game::{ClientboundGamePacketKind, ServerboundGamePacketKind},
+    stats::TrafficStats,
+};
+
+/// A snapshot of the packets a client has received and sent while in the
+/// game state. Get it with [`Client::stats`].
+#[derive(Clone, Debug, Default)]
+pub struct ClientStats {
+    pub received: TrafficStats<ClientboundGamePacketKind>,
+    pub sent: TrafficStats<ServerboundGamePacketKind>,
+}
+
+impl ClientStats {
+    /// Add the statistics from another client to these ones. This is how a
+    /// swarm's statistics are calculated.
+    pub fn merge(&mut self, other: &ClientStats) {
+        self.received.merge(&other.received);
+        self.sent.merge(&other.sent);
+    }
+}
+
+impl Client {
+    /// Get the statistics for the packets this client has received and sent.
+    ///
+    /// ```rust,no_run
+    /// # fn example(bot: azalea_client::Client) {
+    /// for (kind, stats) in bot.stats().sent.most_frequent().iter().take(5) {
+    ///     println!("{kind:?}: {} packets", stats.count);
+    /// }
+    /// # }
+    /// ```
+    pub fn stats(&self) -> ClientStats {
+        ClientStats {
+            received: self.received_stats.lock().clone(),
+            sent: self.sent_stats.lock().clone(),
+        }
+    }
+
+    /// Set all the statistics back to zero.
+    pub fn reset_stats(&self) {
+        *self.received_stats.lock() = TrafficStats::default();
+        *self.sent_stats.lock() = TrafficStats::default();
+    }
+}
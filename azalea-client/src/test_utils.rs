@@ -0,0 +1,46 @@
+//! Helpers shared by the tests of different modules.
+
+use crate::Client;
+use azalea_auth::game_profile::GameProfile;
+use azalea_core::{ChunkPos, Vec3};
+use azalea_protocol::{
+    connect::Connection,
+    packets::game::{ClientboundGamePacket, ServerboundGamePacket},
+};
+use azalea_world::{
+    entity::{metadata, EntityData, EntityMetadata},
+    Chunk, PartialWorld,
+};
+use uuid::Uuid;
+
+/// A client named `bot` that's connected to an in-memory server, and the
+/// server's end of the connection.
+pub fn client_and_server() -> (
+    Client,
+    Connection<ServerboundGamePacket, ClientboundGamePacket>,
+) {
+    let (stream, peer) = tokio::io::duplex(64 * 1024);
+    let client = Client::new(
+        GameProfile::new(Uuid::nil(), "bot".to_string()),
+        Connection::from_stream(stream),
+        None,
+    );
+    (client, Connection::from_stream(peer))
+}
+
+/// Replace the client's world with one that only has an empty chunk at 0 0,
+/// and put the player on the ground at `position` in it.
+pub fn empty_world(client: &Client, position: Vec3) {
+    let mut world = client.world.write();
+    *world = PartialWorld::default();
+    world
+        .set_chunk(&ChunkPos::new(0, 0), Some(Chunk::default()))
+        .unwrap();
+    let mut player = EntityData::new(
+        Uuid::nil(),
+        position,
+        EntityMetadata::Player(metadata::Player::default()),
+    );
+    player.on_ground = true;
+    world.add_entity(0, player);
+}
@@ -4,6 +4,7 @@ use azalea_buf::{BufReadError, McBuf, McBufReadable, McBufWritable};
 
 /// Represents Java's BitSet, a list of bits.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitSet {
     data: Vec<u64>,
 }
@@ -190,6 +191,26 @@ where
         Self::new()
     }
 }
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for FixedBitSet<N>
+where
+    [u8; N.div_ceil(8)]: Sized,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        azalea_buf::byte_array::serialize(&self.data, serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for FixedBitSet<N>
+where
+    [u8; N.div_ceil(8)]: Sized,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(FixedBitSet {
+            data: azalea_buf::byte_array::deserialize(deserializer)?,
+        })
+    }
+}
 
 #[cfg(test)]
 mod tests {
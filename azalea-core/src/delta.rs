@@ -9,6 +9,7 @@ pub trait PositionDeltaTrait {
 
 /// Only works for up to 8 blocks
 #[derive(Clone, Debug, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PositionDelta8 {
     pub xa: i16,
     pub ya: i16,
@@ -1,6 +1,8 @@
+use crate::BlockPos;
 use azalea_buf::McBuf;
 
-#[derive(Clone, Copy, Debug, McBuf, Default)]
+#[derive(Clone, Copy, Debug, McBuf, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     #[default]
     Down = 0,
@@ -13,6 +15,7 @@ pub enum Direction {
 
 // TODO: make azalea_block use this instead of FacingCardinal
 #[derive(Clone, Copy, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CardinalDirection {
     North,
     South,
@@ -34,6 +37,48 @@ pub enum AxisCycle {
     Backward = 2,
 }
 
+impl Direction {
+    /// All six directions, in the order of their ids.
+    pub fn iter() -> impl Iterator<Item = Direction> {
+        [
+            Direction::Down,
+            Direction::Up,
+            Direction::North,
+            Direction::South,
+            Direction::West,
+            Direction::East,
+        ]
+        .iter()
+        .copied()
+    }
+
+    /// The block position one step in this direction from the origin.
+    #[inline]
+    pub fn normal(self) -> BlockPos {
+        match self {
+            Direction::Down => BlockPos::new(0, -1, 0),
+            Direction::Up => BlockPos::new(0, 1, 0),
+            Direction::North => BlockPos::new(0, 0, -1),
+            Direction::South => BlockPos::new(0, 0, 1),
+            Direction::West => BlockPos::new(-1, 0, 0),
+            Direction::East => BlockPos::new(1, 0, 0),
+        }
+    }
+
+    /// The direction pointing the other way, like down for up.
+    #[inline]
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Down => Direction::Up,
+            Direction::Up => Direction::Down,
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        }
+    }
+}
+
 impl CardinalDirection {
     #[inline]
     pub fn x(self) -> i32 {
@@ -2,6 +2,7 @@ use azalea_buf::{BufReadError, McBufReadable, McBufWritable};
 use std::io::{Cursor, Write};
 
 #[derive(Hash, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameType {
     #[default]
     Survival,
@@ -97,6 +98,7 @@ impl McBufWritable for GameType {
 /// Rust doesn't let us `impl McBufReadable for Option<GameType>` so we have to
 /// make a new type :(
 #[derive(Hash, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OptionalGameType(Option<GameType>);
 
 impl From<Option<GameType>> for OptionalGameType {
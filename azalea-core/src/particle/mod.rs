@@ -2,6 +2,7 @@ use crate::{BlockPos, Slot};
 use azalea_buf::McBuf;
 
 #[derive(Debug, Clone, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Particle {
     #[var]
     pub id: i32,
@@ -9,6 +10,7 @@ pub struct Particle {
 }
 
 #[derive(Clone, Debug, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParticleData {
     AmbientEntityEffect,
     AngryVillager,
@@ -102,11 +104,13 @@ pub enum ParticleData {
 }
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockParticle {
     #[var]
     pub block_state: i32,
 }
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DustParticle {
     /// Red value, 0-1
     pub red: f32,
@@ -119,6 +123,7 @@ pub struct DustParticle {
 }
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DustColorTransitionParticle {
     /// Red value, 0-1
     pub from_red: f32,
@@ -137,11 +142,13 @@ pub struct DustColorTransitionParticle {
 }
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemParticle {
     pub item: Slot,
 }
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VibrationParticle {
     pub origin: BlockPos,
     pub position_type: String,
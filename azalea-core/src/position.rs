@@ -110,6 +110,7 @@ macro_rules! vec3_impl {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -118,6 +119,7 @@ pub struct Vec3 {
 vec3_impl!(Vec3, f64);
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockPos {
     pub x: i32,
     pub y: i32,
@@ -138,6 +140,7 @@ impl BlockPos {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkPos {
     pub x: i32,
     pub z: i32,
@@ -151,6 +154,7 @@ impl ChunkPos {
 
 /// The coordinates of a chunk section in the world.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkSectionPos {
     pub x: i32,
     pub y: i32,
@@ -180,6 +184,7 @@ impl ChunkBlockPos {
 /// The coordinates of a block inside a chunk section. Each coordinate must be
 /// in the range [0, 15].
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkSectionBlockPos {
     pub x: u8,
     pub y: u8,
@@ -201,6 +206,7 @@ impl Add<ChunkSectionBlockPos> for ChunkSectionPos {
 
 /// A block pos with an attached world
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlobalPos {
     pub pos: BlockPos,
     // this is actually a ResourceKey in Minecraft, but i don't think it matters?
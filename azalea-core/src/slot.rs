@@ -1,24 +1,58 @@
-// TODO: have an azalea-inventory or azalea-container crate and put this there
+// this is here instead of azalea-inventory because particles and entity
+// metadata can have items too. azalea-inventory re-exports it
 
-use azalea_buf::{BufReadError, McBuf, McBufReadable, McBufWritable};
+use azalea_buf::{remap::MaybeModded, BufReadError, McBufReadable, McBufWritable};
 use azalea_nbt::Tag;
+use azalea_registry::Item;
 use std::io::{Cursor, Write};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Slot {
     #[default]
     Empty,
     Present(SlotData),
 }
 
-#[derive(Debug, Clone, McBuf)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SlotData {
-    #[var]
-    pub id: u32,
+    /// The item, or the id the server used for it if it isn't a vanilla one,
+    /// which happens on modded servers.
+    pub item: MaybeModded<Item>,
     pub count: u8,
     pub nbt: Tag,
 }
 
+impl SlotData {
+    /// A stack of a vanilla item with no NBT.
+    pub fn new(item: Item, count: u8) -> Self {
+        Self {
+            item: item.into(),
+            count,
+            nbt: Tag::End,
+        }
+    }
+}
+
+impl McBufReadable for SlotData {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        Ok(Self {
+            item: MaybeModded::read_from(buf)?,
+            count: u8::read_from(buf)?,
+            nbt: Tag::read_from(buf)?,
+        })
+    }
+}
+
+impl McBufWritable for SlotData {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        self.item.write_into(buf)?;
+        self.count.write_into(buf)?;
+        self.nbt.write_into(buf)
+    }
+}
+
 impl McBufReadable for Slot {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
         let slot = Option::<SlotData>::read_from(buf)?;
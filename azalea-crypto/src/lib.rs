@@ -5,8 +5,11 @@ use aes::{
     cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit},
     Aes128,
 };
+use hmac::{Hmac, Mac};
 use rand::{rngs::OsRng, RngCore};
+use rsa::{pkcs8::EncodePublicKey, PaddingScheme, RsaPrivateKey, RsaPublicKey};
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 pub use signing::*;
 
 fn generate_secret_key() -> [u8; 16] {
@@ -60,6 +63,68 @@ pub fn encrypt(public_key: &[u8], nonce: &[u8]) -> Result<EncryptResult, String>
     })
 }
 
+/// The RSA key pair a server uses to receive the shared secret from clients.
+///
+/// Vanilla servers generate a new 1024-bit key pair every time they start.
+pub struct ServerKeyPair {
+    private_key: RsaPrivateKey,
+    /// The public key encoded as an X.509 SubjectPublicKeyInfo, which is the
+    /// format that's sent in `ClientboundHelloPacket`.
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKeyPair {
+    /// Generate a new random key pair.
+    pub fn generate() -> Result<Self, String> {
+        let private_key = RsaPrivateKey::new(&mut OsRng, 1024).map_err(|e| e.to_string())?;
+        let public_key_der = RsaPublicKey::from(&private_key)
+            .to_public_key_der()
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .to_vec();
+        Ok(Self {
+            private_key,
+            public_key_der,
+        })
+    }
+
+    /// The DER-encoded public key that should be sent to clients.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    /// Decrypt some data (the shared secret or the nonce) that the client
+    /// encrypted with our public key.
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        self.private_key
+            .decrypt(PaddingScheme::new_pkcs1v15_encrypt(), data)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Generate the random bytes that a server sends in the encryption request
+/// to make sure the client has the right public key.
+pub fn generate_nonce() -> [u8; 4] {
+    let mut nonce = [0u8; 4];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Sign data with HMAC-SHA256. This is what Velocity uses to prove that
+/// forwarded player information came from the proxy.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Check an HMAC-SHA256 signature in constant time.
+pub fn verify_hmac_sha256(key: &[u8], data: &[u8], signature: &[u8]) -> bool {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.verify_slice(signature).is_ok()
+}
+
 pub type Aes128CfbEnc = cfb8::Encryptor<Aes128>;
 pub type Aes128CfbDec = cfb8::Decryptor<Aes128>;
 
@@ -103,6 +168,31 @@ mod tests {
         assert_eq!(digest, "88e16a1019277b15d58faf0541e11910eb756f6");
     }
 
+    #[test]
+    fn test_server_key_pair() {
+        let key_pair = ServerKeyPair::generate().unwrap();
+        let nonce = generate_nonce();
+        let e = encrypt(key_pair.public_key(), &nonce).unwrap();
+        assert_eq!(
+            key_pair.decrypt(&e.encrypted_public_key).unwrap(),
+            e.secret_key
+        );
+        assert_eq!(key_pair.decrypt(&e.encrypted_nonce).unwrap(), nonce);
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // from RFC 4231, test case 2
+        let signature = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(signature[..4], [0x5b, 0xdc, 0xc1, 0x46],);
+        assert!(verify_hmac_sha256(
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &signature
+        ));
+        assert!(!verify_hmac_sha256(b"Jefe", b"something else", &signature));
+    }
+
     #[test]
     fn encode_packet_twice() {
         let mut packet = vec![0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
@@ -2,17 +2,21 @@ use azalea_buf::McBuf;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SaltSignaturePair {
     pub salt: u64,
     pub signature: Vec<u8>,
 }
 
 #[derive(Clone, Debug, PartialEq, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MessageSignature {
+    #[cfg_attr(feature = "serde", serde(with = "azalea_buf::byte_array"))]
     pub bytes: [u8; 256],
 }
 
 #[derive(Clone, Debug, McBuf, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SignedMessageHeader {
     pub previous_signature: Option<MessageSignature>,
     pub sender: Uuid,
@@ -0,0 +1,721 @@
+//! Clicking slots, and guessing what the server will do when we do.
+//!
+//! The server checks the slots we say a click changed against what it
+//! thinks changed, and if they don't match it sends us the whole container
+//! again. So we do the same thing the vanilla client does and predict the
+//! result of each click. The predictions follow vanilla's rules for normal
+//! slots, but don't know about things like crafting recipes or which items
+//! a furnace accepts; when they're wrong the server corrects us.
+
+use crate::{
+    item::{equipment_slot_of, is_same_item, max_stack_size_of, EquipmentSlot},
+    Container,
+};
+use azalea_core::{Slot, SlotData};
+use azalea_protocol::packets::game::serverbound_container_click_packet::{
+    ClickType, ServerboundContainerClickPacket,
+};
+use std::{collections::HashMap, ops::Range};
+
+/// The slot number that means outside of the window.
+const OUTSIDE_SLOT: i16 = -999;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+}
+
+/// Something the player can do with the slots in a container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClickOperation {
+    /// Pick up, put down, or swap the item in a slot with the one held by
+    /// the cursor. Right clicking picks up half of the stack or puts down
+    /// one item. If `slot` is `None`, the click is outside the window and
+    /// drops the held item (or one of it when right clicking).
+    Pickup {
+        slot: Option<usize>,
+        button: MouseButton,
+    },
+    /// Shift click a slot, which moves its item to the other side of the
+    /// container.
+    QuickMove { slot: usize },
+    /// Swap a slot with a slot in the hotbar, from 0 to 8, like pressing a
+    /// number key while hovering over it.
+    Swap { slot: usize, hotbar_slot: u8 },
+    /// Drop one item from a slot, or all of them, like pressing Q while
+    /// hovering over it.
+    Drop { slot: usize, all: bool },
+    /// Drag the held item over some slots. Left dragging splits it evenly
+    /// between them, and right dragging puts one item in each.
+    Drag {
+        slots: Vec<usize>,
+        button: MouseButton,
+    },
+    /// Double click a slot to pick up every item in the container that's
+    /// the same as the one held by the cursor.
+    PickupAll { slot: usize },
+}
+
+impl ClickOperation {
+    /// The slot number, button, and type of each click packet that vanilla
+    /// sends for this operation. Only dragging takes more than one.
+    fn clicks(&self) -> Vec<(i16, u8, ClickType)> {
+        match self {
+            ClickOperation::Pickup { slot, button } => vec![(
+                slot.map_or(OUTSIDE_SLOT, |slot| slot as i16),
+                *button as u8,
+                ClickType::Pickup,
+            )],
+            ClickOperation::QuickMove { slot } => vec![(*slot as i16, 0, ClickType::QuickMove)],
+            ClickOperation::Swap { slot, hotbar_slot } => {
+                vec![(*slot as i16, *hotbar_slot, ClickType::Swap)]
+            }
+            ClickOperation::Drop { slot, all } => {
+                vec![(*slot as i16, *all as u8, ClickType::Throw)]
+            }
+            ClickOperation::Drag { slots, button } => {
+                // the button says whether the click starts the drag, adds a
+                // slot to it, or ends it, and which mouse button it's with
+                let kind = (*button as u8) << 2;
+                let mut clicks = vec![(OUTSIDE_SLOT, kind, ClickType::QuickCraft)];
+                for slot in slots {
+                    clicks.push((*slot as i16, kind | 1, ClickType::QuickCraft));
+                }
+                clicks.push((OUTSIDE_SLOT, kind | 2, ClickType::QuickCraft));
+                clicks
+            }
+            ClickOperation::PickupAll { slot } => vec![(*slot as i16, 0, ClickType::PickupAll)],
+        }
+    }
+}
+
+impl Container {
+    /// Predict what an operation does to the container, and get the packets
+    /// to send to the server to do it. Every packet has the container's
+    /// current state id and the slots that we think it changed.
+    pub fn click(&mut self, operation: &ClickOperation) -> Vec<ServerboundContainerClickPacket> {
+        let mut clicks = operation.clicks();
+        // only the last click of a drag changes anything
+        let last = clicks.pop().expect("every operation has a click");
+        let mut packets = clicks
+            .into_iter()
+            .map(|click| self.click_packet(click, HashMap::new()))
+            .collect::<Vec<_>>();
+
+        let before = self.slots.clone();
+        self.predict(operation);
+        let changed_slots = before
+            .into_iter()
+            .zip(&self.slots)
+            .enumerate()
+            .filter(|(_, (before, after))| before != *after)
+            .map(|(index, (_, after))| (index as u16, after.clone()))
+            .collect();
+        packets.push(self.click_packet(last, changed_slots));
+        packets
+    }
+
+    fn click_packet(
+        &self,
+        (slot_num, button_num, click_type): (i16, u8, ClickType),
+        changed_slots: HashMap<u16, Slot>,
+    ) -> ServerboundContainerClickPacket {
+        ServerboundContainerClickPacket {
+            container_id: self.id,
+            state_id: self.state_id,
+            slot_num: slot_num as u16,
+            button_num,
+            click_type,
+            changed_slots,
+            carried_item: self.carried.clone(),
+        }
+    }
+
+    fn predict(&mut self, operation: &ClickOperation) {
+        match operation {
+            ClickOperation::Pickup { slot, button } => self.pickup(*slot, *button),
+            ClickOperation::QuickMove { slot } => self.quick_move(*slot),
+            ClickOperation::Swap { slot, hotbar_slot } => self.swap(*slot, *hotbar_slot),
+            ClickOperation::Drop { slot, all } => {
+                if self.carried == Slot::Empty {
+                    let count = if *all { u8::MAX } else { 1 };
+                    self.try_remove(*slot, count, u8::MAX);
+                }
+            }
+            ClickOperation::Drag { slots, button } => self.drag(slots, *button),
+            ClickOperation::PickupAll { slot } => self.pickup_all(*slot),
+        }
+    }
+
+    fn pickup(&mut self, slot: Option<usize>, button: MouseButton) {
+        let Some(slot) = slot else {
+            // clicking outside the window drops what we're holding
+            match button {
+                MouseButton::Left => self.carried = Slot::Empty,
+                MouseButton::Right => self.carried = shrink(self.carried.clone(), 1),
+            }
+            return;
+        };
+        let Some(item) = self.slots.get(slot).cloned() else {
+            return;
+        };
+        let carried = std::mem::take(&mut self.carried);
+        self.carried = match (item, carried) {
+            (_, Slot::Empty) => {
+                let Slot::Present(item) = &self.slots[slot] else {
+                    return;
+                };
+                let count = match button {
+                    MouseButton::Left => item.count,
+                    MouseButton::Right => item.count - item.count / 2,
+                };
+                self.try_remove(slot, count, u8::MAX)
+            }
+            (Slot::Empty, Slot::Present(carried)) => {
+                let count = match button {
+                    MouseButton::Left => carried.count,
+                    MouseButton::Right => 1,
+                };
+                self.safe_insert(slot, carried, count)
+            }
+            (Slot::Present(item), Slot::Present(mut carried)) => {
+                if self.may_place(slot) {
+                    if is_same_item(&item, &carried) {
+                        let count = match button {
+                            MouseButton::Left => carried.count,
+                            MouseButton::Right => 1,
+                        };
+                        self.safe_insert(slot, carried, count)
+                    } else if carried.count <= self.slot_max_stack_size(slot, &carried) {
+                        self.slots[slot] = Slot::Present(carried);
+                        Slot::Present(item)
+                    } else {
+                        Slot::Present(carried)
+                    }
+                } else if is_same_item(&item, &carried) {
+                    // taking from a result slot while holding the same item
+                    let room = max_stack_size_of(&carried).saturating_sub(carried.count);
+                    if let Slot::Present(taken) = self.try_remove(slot, item.count, room) {
+                        carried.count += taken.count;
+                    }
+                    Slot::Present(carried)
+                } else {
+                    Slot::Present(carried)
+                }
+            }
+        };
+    }
+
+    fn quick_move(&mut self, slot: usize) {
+        let Some(Slot::Present(mut item)) = self.slots.get(slot).cloned() else {
+            return;
+        };
+        let layout = self.layout();
+        let (Some(inventory), Some(hotbar), Some(player)) = (
+            layout.range("inventory"),
+            layout.range("hotbar"),
+            layout.player_range(),
+        ) else {
+            return;
+        };
+        let group = layout.group_of(slot);
+        let moved = if self.menu.is_none() {
+            // armor and shields go to their slot first if it's empty
+            let equipment = equipment_slot_of(&item)
+                .and_then(|equipment| match equipment {
+                    EquipmentSlot::Offhand => layout.range("offhand").map(|r| r.start),
+                    armor => layout.range("armor").map(|r| r.start + armor as usize),
+                })
+                .filter(|&target| self.slots[target] == Slot::Empty);
+            match (group, equipment) {
+                (Some("crafting_result"), _) => self.move_item_to(&mut item, player, true),
+                (Some("crafting" | "armor"), _) => self.move_item_to(&mut item, player, false),
+                (_, Some(target)) => self.move_item_to(&mut item, target..target + 1, false),
+                (Some("inventory"), _) => self.move_item_to(&mut item, hotbar, false),
+                (Some("hotbar"), _) => self.move_item_to(&mut item, inventory, false),
+                _ => self.move_item_to(&mut item, player, false),
+            }
+        } else if !player.contains(&slot) {
+            self.move_item_to(&mut item, player, true)
+        } else if let Some(contents) = layout.range("contents") {
+            self.move_item_to(&mut item, contents, false)
+        } else if group == Some("inventory") {
+            self.move_item_to(&mut item, hotbar, false)
+        } else {
+            self.move_item_to(&mut item, inventory, false)
+        };
+        if moved {
+            self.slots[slot] = if item.count == 0 {
+                Slot::Empty
+            } else {
+                Slot::Present(item)
+            };
+        }
+    }
+
+    /// Put as much of `item` as we can in the slots in `range`, first on top
+    /// of the same item and then in an empty slot. Returns whether any of it
+    /// was moved.
+    fn move_item_to(&mut self, item: &mut SlotData, range: Range<usize>, reverse: bool) -> bool {
+        let slots: Vec<usize> = if reverse {
+            range.rev().collect()
+        } else {
+            range.collect()
+        };
+        let count = item.count;
+        if max_stack_size_of(item) > 1 {
+            for &slot in &slots {
+                let max = self.slot_max_stack_size(slot, item);
+                if let Slot::Present(existing) = &mut self.slots[slot] {
+                    if is_same_item(existing, item) && existing.count < max {
+                        let moved = item.count.min(max - existing.count);
+                        existing.count += moved;
+                        item.count -= moved;
+                    }
+                }
+                if item.count == 0 {
+                    return true;
+                }
+            }
+        }
+        for &slot in &slots {
+            if self.slots[slot] == Slot::Empty && self.may_place(slot) {
+                let moved = item.count.min(self.slot_max_stack_size(slot, item));
+                self.slots[slot] = Slot::Present(SlotData {
+                    count: moved,
+                    ..item.clone()
+                });
+                item.count -= moved;
+                break;
+            }
+        }
+        item.count != count
+    }
+
+    fn swap(&mut self, slot: usize, hotbar_slot: u8) {
+        let Some(hotbar) = self.layout().range("hotbar") else {
+            return;
+        };
+        let hotbar_slot = hotbar.start + hotbar_slot as usize;
+        if slot >= self.slots.len() || !hotbar.contains(&hotbar_slot) || slot == hotbar_slot {
+            return;
+        }
+        match (self.slots[slot].clone(), self.slots[hotbar_slot].clone()) {
+            (Slot::Empty, Slot::Empty) => {}
+            (item, Slot::Empty) => {
+                self.slots[hotbar_slot] = item;
+                self.slots[slot] = Slot::Empty;
+            }
+            (item, Slot::Present(hotbar_item)) => {
+                if !self.may_place(slot) {
+                    return;
+                }
+                let max = self.slot_max_stack_size(slot, &hotbar_item);
+                if hotbar_item.count <= max {
+                    self.slots[slot] = Slot::Present(hotbar_item);
+                    self.slots[hotbar_slot] = item;
+                } else if item == Slot::Empty {
+                    self.slots[slot] = Slot::Present(SlotData {
+                        count: max,
+                        ..hotbar_item.clone()
+                    });
+                    self.slots[hotbar_slot] = shrink(Slot::Present(hotbar_item), max);
+                }
+            }
+        }
+    }
+
+    fn drag(&mut self, slots: &[usize], button: MouseButton) {
+        let Slot::Present(carried) = self.carried.clone() else {
+            return;
+        };
+        // like vanilla, slots that can't take the item are skipped, and only
+        // as many slots as there are items are used
+        let mut drag_slots: Vec<usize> = Vec::new();
+        for &slot in slots {
+            if drag_slots.len() >= carried.count as usize {
+                break;
+            }
+            let can_take = match self.slots.get(slot) {
+                Some(Slot::Empty) => true,
+                Some(Slot::Present(existing)) => {
+                    is_same_item(existing, &carried)
+                        && existing.count <= max_stack_size_of(&carried)
+                }
+                None => false,
+            };
+            if can_take && self.may_place(slot) && !drag_slots.contains(&slot) {
+                drag_slots.push(slot);
+            }
+        }
+        if drag_slots.len() == 1 {
+            // dragging over one slot is the same as clicking it
+            self.pickup(Some(drag_slots[0]), button);
+            return;
+        }
+        if drag_slots.is_empty() {
+            return;
+        }
+
+        let per_slot = match button {
+            MouseButton::Left => carried.count / drag_slots.len() as u8,
+            MouseButton::Right => 1,
+        };
+        let mut remaining = carried.count;
+        for slot in drag_slots {
+            let existing = match &self.slots[slot] {
+                Slot::Present(existing) => existing.count,
+                Slot::Empty => 0,
+            };
+            let count = (per_slot + existing).min(self.slot_max_stack_size(slot, &carried));
+            remaining -= count.saturating_sub(existing);
+            self.slots[slot] = Slot::Present(SlotData {
+                count,
+                ..carried.clone()
+            });
+        }
+        self.carried = shrink(Slot::Present(carried.clone()), carried.count - remaining);
+    }
+
+    fn pickup_all(&mut self, slot: usize) {
+        let Slot::Present(mut carried) = self.carried.clone() else {
+            return;
+        };
+        if !matches!(self.slots.get(slot), Some(Slot::Empty)) {
+            return;
+        }
+        let max = max_stack_size_of(&carried);
+        // full stacks are only taken if there's nothing else to take
+        for take_full_stacks in [false, true] {
+            for slot in 0..self.slots.len() {
+                if carried.count >= max {
+                    break;
+                }
+                let Slot::Present(item) = &self.slots[slot] else {
+                    continue;
+                };
+                if !is_same_item(item, &carried)
+                    || self.is_result(slot)
+                    || (!take_full_stacks && item.count == max)
+                {
+                    continue;
+                }
+                if let Slot::Present(taken) = self.try_remove(slot, item.count, max - carried.count)
+                {
+                    carried.count += taken.count;
+                }
+            }
+        }
+        self.carried = Slot::Present(carried);
+    }
+
+    /// Put up to `count` of `item` in a slot, returning what's left of it.
+    fn safe_insert(&mut self, slot: usize, mut item: SlotData, count: u8) -> Slot {
+        if !self.may_place(slot) {
+            return Slot::Present(item);
+        }
+        let max = self.slot_max_stack_size(slot, &item);
+        let count = match &mut self.slots[slot] {
+            Slot::Empty => {
+                let count = count.min(item.count).min(max);
+                if count > 0 {
+                    self.slots[slot] = Slot::Present(SlotData {
+                        count,
+                        ..item.clone()
+                    });
+                }
+                count
+            }
+            Slot::Present(existing) if is_same_item(existing, &item) => {
+                let count = count
+                    .min(item.count)
+                    .min(max.saturating_sub(existing.count));
+                existing.count += count;
+                count
+            }
+            Slot::Present(_) => 0,
+        };
+        item.count -= count;
+        shrink(Slot::Present(item), 0)
+    }
+
+    /// Take up to `count` items from a slot, but no more than `limit`.
+    /// Result slots can only be taken from all at once.
+    fn try_remove(&mut self, slot: usize, count: u8, limit: u8) -> Slot {
+        let is_result = self.is_result(slot);
+        let Some(Slot::Present(item)) = self.slots.get_mut(slot) else {
+            return Slot::Empty;
+        };
+        let count = count.min(limit).min(item.count);
+        if count == 0 || (is_result && count < item.count) {
+            return Slot::Empty;
+        }
+        let taken = SlotData {
+            count,
+            ..item.clone()
+        };
+        self.slots[slot] = shrink(self.slots[slot].clone(), count);
+        Slot::Present(taken)
+    }
+
+    fn is_result(&self, slot: usize) -> bool {
+        matches!(
+            self.layout().group_of(slot),
+            Some("result" | "crafting_result")
+        )
+    }
+
+    fn may_place(&self, slot: usize) -> bool {
+        !self.is_result(slot)
+    }
+
+    /// The most of an item that fits in a slot, which is less than the
+    /// item's usual stack size for slots like armor.
+    fn slot_max_stack_size(&self, slot: usize, item: &SlotData) -> u8 {
+        let slot_max = match self.layout().group_of(slot) {
+            Some("armor" | "payment" | "book") => 1,
+            _ => 64,
+        };
+        slot_max.min(max_stack_size_of(item))
+    }
+}
+
+/// Remove some items from a stack, leaving it empty if there aren't any
+/// left.
+fn shrink(slot: Slot, count: u8) -> Slot {
+    match slot {
+        Slot::Present(mut item) if item.count > count => {
+            item.count -= count;
+            Slot::Present(item)
+        }
+        _ => Slot::Empty,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_chat::Component;
+    use azalea_registry::{Item, Menu};
+
+    fn chest() -> Container {
+        let mut chest = Container::new(1, Menu::Generic9x1, Component::from("Chest"));
+        chest.state_id = 7;
+        chest
+    }
+
+    #[test]
+    fn test_pickup() {
+        let mut chest = chest();
+        chest.slots[0] = Slot::Present(SlotData::new(Item::Stone, 5));
+        let packets = chest.click(&ClickOperation::Pickup {
+            slot: Some(0),
+            button: MouseButton::Right,
+        });
+        assert_eq!(chest.slots[0], Slot::Present(SlotData::new(Item::Stone, 2)));
+        assert_eq!(chest.carried, Slot::Present(SlotData::new(Item::Stone, 3)));
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].state_id, 7);
+        assert_eq!(packets[0].button_num, 1);
+        assert_eq!(
+            packets[0].changed_slots,
+            HashMap::from([(0, Slot::Present(SlotData::new(Item::Stone, 2)))])
+        );
+        assert_eq!(
+            packets[0].carried_item,
+            Slot::Present(SlotData::new(Item::Stone, 3))
+        );
+
+        // put it down on a different item to swap them
+        chest.slots[1] = Slot::Present(SlotData::new(Item::Dirt, 1));
+        chest.click(&ClickOperation::Pickup {
+            slot: Some(1),
+            button: MouseButton::Left,
+        });
+        assert_eq!(chest.slots[1], Slot::Present(SlotData::new(Item::Stone, 3)));
+        assert_eq!(chest.carried, Slot::Present(SlotData::new(Item::Dirt, 1)));
+
+        let packets = chest.click(&ClickOperation::Pickup {
+            slot: None,
+            button: MouseButton::Left,
+        });
+        assert_eq!(packets[0].slot_num, -999i16 as u16);
+        assert!(packets[0].changed_slots.is_empty());
+        assert_eq!(chest.carried, Slot::Empty);
+    }
+
+    #[test]
+    fn test_quick_move() {
+        let mut chest = chest();
+        chest.slots[0] = Slot::Present(SlotData::new(Item::Stone, 40));
+        chest.slots[44] = Slot::Present(SlotData::new(Item::Stone, 60));
+        chest.slots[43] = Slot::Present(SlotData::new(Item::Dirt, 1));
+        chest.click(&ClickOperation::QuickMove { slot: 0 });
+        // stacks are filled before empty slots are used, from the end of the
+        // hotbar when moving into the player's inventory
+        assert_eq!(chest.slots[0], Slot::Empty);
+        assert_eq!(
+            chest.slots[44],
+            Slot::Present(SlotData::new(Item::Stone, 64))
+        );
+        assert_eq!(
+            chest.slots[42],
+            Slot::Present(SlotData::new(Item::Stone, 36))
+        );
+
+        chest.click(&ClickOperation::QuickMove { slot: 43 });
+        assert_eq!(chest.slots[0], Slot::Present(SlotData::new(Item::Dirt, 1)));
+
+        let mut inventory = Container::player_inventory();
+        inventory.slots[36] = Slot::Present(SlotData::new(Item::DiamondSword, 1));
+        inventory.click(&ClickOperation::QuickMove { slot: 36 });
+        assert_eq!(
+            inventory.slots[9],
+            Slot::Present(SlotData::new(Item::DiamondSword, 1))
+        );
+
+        // armor and shields are equipped if their slot is empty
+        inventory.slots[10] = Slot::Present(SlotData::new(Item::IronChestplate, 1));
+        inventory.click(&ClickOperation::QuickMove { slot: 10 });
+        assert_eq!(inventory.slots[10], Slot::Empty);
+        assert_eq!(
+            inventory.slots[6],
+            Slot::Present(SlotData::new(Item::IronChestplate, 1))
+        );
+        inventory.slots[37] = Slot::Present(SlotData::new(Item::GoldenChestplate, 1));
+        inventory.click(&ClickOperation::QuickMove { slot: 37 });
+        assert_eq!(
+            inventory.slots[10],
+            Slot::Present(SlotData::new(Item::GoldenChestplate, 1))
+        );
+        inventory.slots[38] = Slot::Present(SlotData::new(Item::Shield, 1));
+        inventory.click(&ClickOperation::QuickMove { slot: 38 });
+        assert_eq!(
+            inventory.slots[45],
+            Slot::Present(SlotData::new(Item::Shield, 1))
+        );
+        // and unequipped into the inventory
+        inventory.click(&ClickOperation::QuickMove { slot: 6 });
+        assert_eq!(inventory.slots[6], Slot::Empty);
+        assert_eq!(
+            inventory.slots[11],
+            Slot::Present(SlotData::new(Item::IronChestplate, 1))
+        );
+    }
+
+    #[test]
+    fn test_swap_and_drop() {
+        let mut chest = chest();
+        chest.slots[0] = Slot::Present(SlotData::new(Item::Stone, 5));
+        chest.slots[38] = Slot::Present(SlotData::new(Item::Dirt, 2));
+        let packets = chest.click(&ClickOperation::Swap {
+            slot: 0,
+            hotbar_slot: 2,
+        });
+        assert_eq!(chest.slots[0], Slot::Present(SlotData::new(Item::Dirt, 2)));
+        assert_eq!(
+            chest.slots[38],
+            Slot::Present(SlotData::new(Item::Stone, 5))
+        );
+        assert_eq!(packets[0].changed_slots.len(), 2);
+
+        chest.click(&ClickOperation::Drop {
+            slot: 38,
+            all: false,
+        });
+        assert_eq!(
+            chest.slots[38],
+            Slot::Present(SlotData::new(Item::Stone, 4))
+        );
+        chest.click(&ClickOperation::Drop {
+            slot: 38,
+            all: true,
+        });
+        assert_eq!(chest.slots[38], Slot::Empty);
+    }
+
+    #[test]
+    fn test_drag() {
+        let mut chest = chest();
+        chest.carried = Slot::Present(SlotData::new(Item::Stone, 10));
+        chest.slots[1] = Slot::Present(SlotData::new(Item::Stone, 1));
+        let packets = chest.click(&ClickOperation::Drag {
+            slots: vec![0, 1, 2],
+            button: MouseButton::Left,
+        });
+        assert_eq!(
+            packets
+                .iter()
+                .map(|p| (p.slot_num as i16, p.button_num))
+                .collect::<Vec<_>>(),
+            vec![(-999, 0), (0, 1), (1, 1), (2, 1), (-999, 2)]
+        );
+        assert!(packets[..4].iter().all(|p| p.changed_slots.is_empty()));
+        assert_eq!(packets[4].changed_slots.len(), 3);
+        assert_eq!(chest.slots[0], Slot::Present(SlotData::new(Item::Stone, 3)));
+        assert_eq!(chest.slots[1], Slot::Present(SlotData::new(Item::Stone, 4)));
+        assert_eq!(chest.carried, Slot::Present(SlotData::new(Item::Stone, 1)));
+
+        chest.carried = Slot::Present(SlotData::new(Item::Stone, 10));
+        chest.click(&ClickOperation::Drag {
+            slots: vec![3, 4],
+            button: MouseButton::Right,
+        });
+        assert_eq!(chest.slots[3], Slot::Present(SlotData::new(Item::Stone, 1)));
+        assert_eq!(chest.carried, Slot::Present(SlotData::new(Item::Stone, 8)));
+
+        // slots are only counted once even if they aren't next to each other
+        chest.carried = Slot::Present(SlotData::new(Item::Dirt, 6));
+        chest.click(&ClickOperation::Drag {
+            slots: vec![5, 6, 5],
+            button: MouseButton::Left,
+        });
+        assert_eq!(chest.slots[5], Slot::Present(SlotData::new(Item::Dirt, 3)));
+        assert_eq!(chest.slots[6], Slot::Present(SlotData::new(Item::Dirt, 3)));
+        assert_eq!(chest.carried, Slot::Empty);
+
+        // with fewer items than slots, only the first ones get an item
+        chest.carried = Slot::Present(SlotData::new(Item::Dirt, 2));
+        chest.click(&ClickOperation::Drag {
+            slots: vec![7, 8, 9],
+            button: MouseButton::Left,
+        });
+        assert_eq!(chest.slots[7], Slot::Present(SlotData::new(Item::Dirt, 1)));
+        assert_eq!(chest.slots[8], Slot::Present(SlotData::new(Item::Dirt, 1)));
+        assert_eq!(chest.slots[9], Slot::Empty);
+        assert_eq!(chest.carried, Slot::Empty);
+    }
+
+    #[test]
+    fn test_pickup_all() {
+        let mut chest = chest();
+        chest.carried = Slot::Present(SlotData::new(Item::Stone, 10));
+        chest.slots[1] = Slot::Present(SlotData::new(Item::Stone, 64));
+        chest.slots[2] = Slot::Present(SlotData::new(Item::Stone, 20));
+        chest.slots[3] = Slot::Present(SlotData::new(Item::Dirt, 20));
+        chest.click(&ClickOperation::PickupAll { slot: 0 });
+        // stacks that aren't full are taken first
+        assert_eq!(chest.slots[2], Slot::Empty);
+        assert_eq!(
+            chest.slots[1],
+            Slot::Present(SlotData::new(Item::Stone, 30))
+        );
+        assert_eq!(chest.slots[3], Slot::Present(SlotData::new(Item::Dirt, 20)));
+        assert_eq!(chest.carried, Slot::Present(SlotData::new(Item::Stone, 64)));
+    }
+
+    #[test]
+    fn test_result_slot() {
+        let mut table = Container::new(1, Menu::Crafting, Component::from("Crafting"));
+        table.slots[0] = Slot::Present(SlotData::new(Item::Stick, 4));
+        table.carried = Slot::Present(SlotData::new(Item::Stick, 1));
+        // we can't put items in the result slot
+        table.click(&ClickOperation::Pickup {
+            slot: Some(0),
+            button: MouseButton::Right,
+        });
+        assert_eq!(table.slots[0], Slot::Empty);
+        assert_eq!(table.carried, Slot::Present(SlotData::new(Item::Stick, 5)));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::MenuLayout;
+use azalea_chat::Component;
+use azalea_core::Slot;
+use azalea_registry::Menu;
+use std::collections::HashMap;
+
+/// The id of the player's inventory, which is always open.
+pub const PLAYER_CONTAINER_ID: u8 = 0;
+
+/// An open container and its contents, as the server last told us.
+#[derive(Clone, Debug)]
+pub struct Container {
+    /// The id the server gave the container when it opened it.
+    pub id: u8,
+    /// The kind of container, or `None` if it's the player's inventory.
+    pub menu: Option<Menu>,
+    pub title: Option<Component>,
+    /// The last state id the server sent, which clicks have to include.
+    pub state_id: u32,
+    pub slots: Vec<Slot>,
+    /// The item held by the cursor.
+    pub carried: Slot,
+    /// Numbers like a furnace's progress, which the server sends with
+    /// `ContainerSetData`.
+    pub data: HashMap<u16, u16>,
+}
+
+impl Container {
+    /// The player's inventory, before the server sent its contents.
+    pub fn player_inventory() -> Self {
+        Self {
+            id: PLAYER_CONTAINER_ID,
+            menu: None,
+            title: None,
+            state_id: 0,
+            slots: vec![Slot::Empty; MenuLayout::PLAYER.len()],
+            carried: Slot::Empty,
+            data: HashMap::new(),
+        }
+    }
+
+    /// A container that the server opened, before it sent the contents.
+    pub fn new(id: u8, menu: Menu, title: Component) -> Self {
+        Self {
+            id,
+            menu: Some(menu),
+            title: Some(title),
+            state_id: 0,
+            slots: vec![Slot::Empty; MenuLayout::of(menu).len()],
+            carried: Slot::Empty,
+            data: HashMap::new(),
+        }
+    }
+
+    pub fn layout(&self) -> MenuLayout {
+        match self.menu {
+            Some(menu) => MenuLayout::of(menu),
+            None => MenuLayout::PLAYER,
+        }
+    }
+
+    pub fn slot(&self, index: usize) -> Option<&Slot> {
+        self.slots.get(index)
+    }
+
+    /// The slots in a group, like `contents` or `hotbar`. See [`MenuLayout`]
+    /// for the names. This is empty if the container doesn't have the
+    /// group.
+    pub fn group(&self, name: &str) -> &[Slot] {
+        self.layout()
+            .range(name)
+            .and_then(|range| self.slots.get(range))
+            .unwrap_or_default()
+    }
+
+    /// The slots that belong to the container instead of the player.
+    pub fn contents(&self) -> &[Slot] {
+        self.slots
+            .get(self.layout().container_range())
+            .unwrap_or_default()
+    }
+
+    /// Replace every slot, like the server does with `ContainerSetContent`.
+    pub fn set_content(&mut self, state_id: u32, items: Vec<Slot>, carried: Slot) {
+        self.state_id = state_id;
+        self.slots = items;
+        // the server should always send the right number of slots, but
+        // make sure the layout's ranges still work if it doesn't
+        self.slots.resize(self.layout().len(), Slot::Empty);
+        self.carried = carried;
+    }
+
+    /// Replace one slot, like the server does with `ContainerSetSlot`. Slots
+    /// that don't exist are ignored.
+    pub fn set_slot(&mut self, state_id: u32, index: usize, item: Slot) {
+        self.state_id = state_id;
+        if let Some(slot) = self.slots.get_mut(index) {
+            *slot = item;
+        }
+    }
+
+    pub fn set_data(&mut self, id: u16, value: u16) {
+        self.data.insert(id, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_core::SlotData;
+    use azalea_registry::Item;
+
+    #[test]
+    fn test_set_slots() {
+        let mut furnace = Container::new(1, Menu::Furnace, Component::from("Furnace"));
+        assert_eq!(furnace.slots.len(), 39);
+
+        furnace.set_content(
+            3,
+            vec![Slot::Present(SlotData::new(Item::Stone, 1))],
+            Slot::Empty,
+        );
+        assert_eq!(furnace.slots.len(), 39);
+        assert_eq!(furnace.group("ingredient").len(), 1);
+        assert_eq!(furnace.contents().len(), 3);
+
+        furnace.set_slot(4, 1, Slot::Present(SlotData::new(Item::Granite, 64)));
+        furnace.set_slot(5, 100, Slot::Present(SlotData::new(Item::Granite, 64)));
+        assert_eq!(furnace.state_id, 5);
+        assert!(matches!(furnace.group("fuel"), [Slot::Present(s)] if s.count == 64));
+        assert!(furnace.group("bottles").is_empty());
+    }
+}
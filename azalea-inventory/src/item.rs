@@ -0,0 +1,181 @@
+//! Facts about items that matter for moving them around.
+
+use azalea_core::SlotData;
+use azalea_registry::{Block, Item};
+use std::str::FromStr;
+
+/// The most of an item that fits in one slot.
+pub fn max_stack_size(item: Item) -> u8 {
+    let name = item.to_string();
+    let name = name.trim_start_matches("minecraft:");
+    match name {
+        "bucket" | "snowball" | "egg" | "ender_pearl" | "honey_bottle" | "armor_stand"
+        | "written_book" => 16,
+        _ if name.ends_with("_sign") || name.ends_with("_banner") => 16,
+        "bow"
+        | "crossbow"
+        | "trident"
+        | "shield"
+        | "fishing_rod"
+        | "carrot_on_a_stick"
+        | "warped_fungus_on_a_stick"
+        | "flint_and_steel"
+        | "shears"
+        | "elytra"
+        | "saddle"
+        | "cake"
+        | "enchanted_book"
+        | "writable_book"
+        | "knowledge_book"
+        | "totem_of_undying"
+        | "debug_stick"
+        | "bundle"
+        | "goat_horn"
+        | "spyglass"
+        | "minecart" => 1,
+        _ if [
+            "_sword",
+            "_shovel",
+            "_pickaxe",
+            "_axe",
+            "_hoe",
+            "_helmet",
+            "_chestplate",
+            "_leggings",
+            "_boots",
+            "_horse_armor",
+            "_boat",
+            "_raft",
+            "_minecart",
+            "_bucket",
+            "_bed",
+            "shulker_box",
+            "potion",
+            "_stew",
+            "_soup",
+        ]
+        .iter()
+        .any(|suffix| name.ends_with(suffix)) =>
+        {
+            1
+        }
+        _ if name.starts_with("music_disc_") => 1,
+        _ => 64,
+    }
+}
+
+/// The most of this item that fits in one slot. Items we don't know, like
+/// modded ones, are assumed to stack to 64.
+pub fn max_stack_size_of(item: &SlotData) -> u8 {
+    item.item.vanilla().map_or(64, |item| max_stack_size(*item))
+}
+
+/// The slots an item can be equipped in besides the main hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquipmentSlot {
+    Head,
+    Chest,
+    Legs,
+    Feet,
+    Offhand,
+}
+
+/// Where the player wears this item, or `None` if it's only held in the main
+/// hand.
+pub fn equipment_slot(item: Item) -> Option<EquipmentSlot> {
+    let name = item.to_string();
+    let name = name.trim_start_matches("minecraft:");
+    match name {
+        "shield" => Some(EquipmentSlot::Offhand),
+        "elytra" => Some(EquipmentSlot::Chest),
+        "carved_pumpkin" => Some(EquipmentSlot::Head),
+        _ if name.ends_with("_helmet") || name.ends_with("_head") || name.ends_with("_skull") => {
+            Some(EquipmentSlot::Head)
+        }
+        _ if name.ends_with("_chestplate") => Some(EquipmentSlot::Chest),
+        _ if name.ends_with("_leggings") => Some(EquipmentSlot::Legs),
+        _ if name.ends_with("_boots") => Some(EquipmentSlot::Feet),
+        _ => None,
+    }
+}
+
+/// Where the player wears this item. Items we don't know, like modded ones,
+/// are assumed to only be held in the main hand.
+pub fn equipment_slot_of(item: &SlotData) -> Option<EquipmentSlot> {
+    item.item.vanilla().and_then(|item| equipment_slot(*item))
+}
+
+/// Whether using this item on a block places a block.
+pub fn is_block_item(item: Item) -> bool {
+    let name = item.to_string();
+    match name.trim_start_matches("minecraft:") {
+        "air" => false,
+        // these place a block that has a different name
+        "redstone" | "string" | "wheat_seeds" | "beetroot_seeds" | "melon_seeds"
+        | "pumpkin_seeds" | "carrot" | "potato" | "sweet_berries" | "glow_berries"
+        | "cocoa_beans" => true,
+        _ => Block::from_str(&name).is_ok(),
+    }
+}
+
+/// Whether using this item on a block places a block. Items we don't know,
+/// like modded ones, are assumed to be blocks.
+pub fn is_block_item_of(item: &SlotData) -> bool {
+    item.item
+        .vanilla()
+        .map_or(true, |item| is_block_item(*item))
+}
+
+/// Whether two items can be in the same stack.
+pub fn is_same_item(a: &SlotData, b: &SlotData) -> bool {
+    a.item == b.item && a.nbt == b.nbt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_stack_size() {
+        assert_eq!(max_stack_size(Item::Stone), 64);
+        assert_eq!(max_stack_size(Item::OakSign), 16);
+        assert_eq!(max_stack_size(Item::EnderPearl), 16);
+        assert_eq!(max_stack_size(Item::Bucket), 16);
+        assert_eq!(max_stack_size(Item::WaterBucket), 1);
+        assert_eq!(max_stack_size(Item::DiamondPickaxe), 1);
+        assert_eq!(max_stack_size(Item::RedShulkerBox), 1);
+        assert_eq!(max_stack_size(Item::MusicDisc13), 1);
+        assert_eq!(max_stack_size(Item::Stick), 64);
+    }
+
+    #[test]
+    fn test_is_block_item() {
+        assert!(is_block_item(Item::Stone));
+        assert!(is_block_item(Item::OakSign));
+        assert!(is_block_item(Item::WheatSeeds));
+        assert!(!is_block_item(Item::Air));
+        assert!(!is_block_item(Item::DiamondSword));
+        assert!(!is_block_item(Item::WaterBucket));
+    }
+
+    #[test]
+    fn test_equipment_slot() {
+        assert_eq!(
+            equipment_slot(Item::TurtleHelmet),
+            Some(EquipmentSlot::Head)
+        );
+        assert_eq!(equipment_slot(Item::CreeperHead), Some(EquipmentSlot::Head));
+        assert_eq!(equipment_slot(Item::Elytra), Some(EquipmentSlot::Chest));
+        assert_eq!(
+            equipment_slot(Item::IronLeggings),
+            Some(EquipmentSlot::Legs)
+        );
+        assert_eq!(
+            equipment_slot(Item::NetheriteBoots),
+            Some(EquipmentSlot::Feet)
+        );
+        assert_eq!(equipment_slot(Item::Shield), Some(EquipmentSlot::Offhand));
+        assert_eq!(equipment_slot(Item::IronHorseArmor), None);
+        assert_eq!(equipment_slot(Item::Stone), None);
+    }
+}
@@ -0,0 +1,223 @@
+//! Which slots are where in each menu.
+
+use azalea_registry::Menu;
+use std::ops::Range;
+
+const INVENTORY: (&str, usize) = ("inventory", 27);
+const HOTBAR: (&str, usize) = ("hotbar", 9);
+
+/// The slots of a menu, as named groups of consecutive slots.
+///
+/// Every menu except the lectern ends with the player's `inventory` (27
+/// slots) and `hotbar` (9 slots). The other groups are:
+///
+/// - Player inventory: `crafting_result`, `crafting`, `armor`, and `offhand`
+///   after the hotbar
+/// - Chests, barrels, shulker boxes, dispensers, and hoppers: `contents`
+/// - Crafting table: `result` and `crafting`
+/// - Furnaces: `ingredient`, `fuel`, and `result`
+/// - Brewing stand: `bottles`, `ingredient`, and `fuel`
+/// - Anvil, smithing table, and cartography table: `first`, `second`, and
+///   `result`
+/// - Grindstone and villagers: `inputs` and `result`
+/// - Enchanting table: `item` and `lapis`
+/// - Loom: `banner`, `dye`, `pattern`, and `result`
+/// - Stonecutter: `input` and `result`
+/// - Beacon: `payment`
+/// - Lectern: `book`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MenuLayout {
+    /// The name and size of each group, in order.
+    pub groups: &'static [(&'static str, usize)],
+}
+
+impl MenuLayout {
+    /// The layout of the player's inventory, which is open whenever no other
+    /// container is.
+    pub const PLAYER: MenuLayout = MenuLayout {
+        groups: &[
+            ("crafting_result", 1),
+            ("crafting", 4),
+            ("armor", 4),
+            INVENTORY,
+            HOTBAR,
+            ("offhand", 1),
+        ],
+    };
+
+    /// The layout of a menu that the server opened.
+    pub fn of(menu: Menu) -> MenuLayout {
+        let groups: &'static [(&'static str, usize)] = match menu {
+            Menu::Generic9x1 => &[("contents", 9), INVENTORY, HOTBAR],
+            Menu::Generic9x2 => &[("contents", 18), INVENTORY, HOTBAR],
+            Menu::Generic9x3 | Menu::ShulkerBox => &[("contents", 27), INVENTORY, HOTBAR],
+            Menu::Generic9x4 => &[("contents", 36), INVENTORY, HOTBAR],
+            Menu::Generic9x5 => &[("contents", 45), INVENTORY, HOTBAR],
+            Menu::Generic9x6 => &[("contents", 54), INVENTORY, HOTBAR],
+            Menu::Generic3x3 => &[("contents", 9), INVENTORY, HOTBAR],
+            Menu::Hopper => &[("contents", 5), INVENTORY, HOTBAR],
+            Menu::Crafting => &[("result", 1), ("crafting", 9), INVENTORY, HOTBAR],
+            Menu::Furnace | Menu::BlastFurnace | Menu::Smoker => &[
+                ("ingredient", 1),
+                ("fuel", 1),
+                ("result", 1),
+                INVENTORY,
+                HOTBAR,
+            ],
+            Menu::BrewingStand => &[
+                ("bottles", 3),
+                ("ingredient", 1),
+                ("fuel", 1),
+                INVENTORY,
+                HOTBAR,
+            ],
+            Menu::Anvil | Menu::Smithing | Menu::CartographyTable => &[
+                ("first", 1),
+                ("second", 1),
+                ("result", 1),
+                INVENTORY,
+                HOTBAR,
+            ],
+            Menu::Grindstone | Menu::Merchant => &[("inputs", 2), ("result", 1), INVENTORY, HOTBAR],
+            Menu::Enchantment => &[("item", 1), ("lapis", 1), INVENTORY, HOTBAR],
+            Menu::Loom => &[
+                ("banner", 1),
+                ("dye", 1),
+                ("pattern", 1),
+                ("result", 1),
+                INVENTORY,
+                HOTBAR,
+            ],
+            Menu::Stonecutter => &[("input", 1), ("result", 1), INVENTORY, HOTBAR],
+            Menu::Beacon => &[("payment", 1), INVENTORY, HOTBAR],
+            Menu::Lectern => &[("book", 1)],
+        };
+        MenuLayout { groups }
+    }
+
+    /// The total number of slots.
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|(_, size)| size).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The slots in the group with the given name, or `None` if this menu
+    /// doesn't have it.
+    pub fn range(&self, name: &str) -> Option<Range<usize>> {
+        let mut start = 0;
+        for (group, size) in self.groups {
+            if *group == name {
+                return Some(start..start + size);
+            }
+            start += size;
+        }
+        None
+    }
+
+    /// The name of the group that a slot is in.
+    pub fn group_of(&self, index: usize) -> Option<&'static str> {
+        let mut start = 0;
+        for (group, size) in self.groups {
+            if index < start + size {
+                return Some(group);
+            }
+            start += size;
+        }
+        None
+    }
+
+    /// The player's inventory and hotbar, which are always next to each
+    /// other.
+    pub fn player_range(&self) -> Option<Range<usize>> {
+        let inventory = self.range("inventory")?;
+        Some(inventory.start..inventory.end + HOTBAR.1)
+    }
+
+    /// The slots that belong to the container instead of the player, which
+    /// are the ones before the player's inventory.
+    pub fn container_range(&self) -> Range<usize> {
+        match self.range("inventory") {
+            Some(inventory) => 0..inventory.start,
+            None => 0..self.len(),
+        }
+    }
+
+    /// The index in the [player's inventory](MenuLayout::PLAYER) of a slot in
+    /// this menu, if it's one of the player's slots.
+    pub fn player_inventory_index(&self, index: usize) -> Option<usize> {
+        let player_range = self.player_range()?;
+        if !player_range.contains(&index) {
+            return None;
+        }
+        let player_start = MenuLayout::PLAYER.player_range().unwrap().start;
+        Some(index - player_range.start + player_start)
+    }
+
+    /// The index in this menu of a slot in the
+    /// [player's inventory](MenuLayout::PLAYER), if this menu shows it.
+    pub fn from_player_inventory_index(&self, index: usize) -> Option<usize> {
+        let player_range = MenuLayout::PLAYER.player_range().unwrap();
+        if !player_range.contains(&index) {
+            return None;
+        }
+        Some(index - player_range.start + self.player_range()?.start)
+    }
+
+    /// The index in the [player's inventory](MenuLayout::PLAYER) of a slot in
+    /// the player's `Inventory`, which is how the server numbers slots when it
+    /// sets them directly. The hotbar is first there, then the rest of the
+    /// inventory, then the armor from feet to head, and then the offhand.
+    pub fn from_inventory_index(index: usize) -> Option<usize> {
+        match index {
+            0..=8 => Some(36 + index),
+            9..=35 => Some(index),
+            36..=39 => Some(8 - (index - 36)),
+            40 => Some(45),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts() {
+        assert_eq!(MenuLayout::PLAYER.len(), 46);
+        assert_eq!(MenuLayout::PLAYER.range("offhand"), Some(45..46));
+        assert_eq!(MenuLayout::of(Menu::Generic9x3).len(), 63);
+        assert_eq!(MenuLayout::of(Menu::Crafting).len(), 46);
+        assert_eq!(MenuLayout::of(Menu::Hopper).player_range(), Some(5..41));
+        assert_eq!(MenuLayout::of(Menu::Lectern).player_range(), None);
+        assert_eq!(MenuLayout::of(Menu::Furnace).group_of(1), Some("fuel"));
+        assert_eq!(MenuLayout::of(Menu::Generic9x6).container_range(), 0..54);
+    }
+
+    #[test]
+    fn test_player_inventory_index() {
+        let chest = MenuLayout::of(Menu::Generic9x1);
+        assert_eq!(chest.player_inventory_index(0), None);
+        // the first slot of the inventory and the last slot of the hotbar
+        assert_eq!(chest.player_inventory_index(9), Some(9));
+        assert_eq!(chest.player_inventory_index(44), Some(44));
+        assert_eq!(MenuLayout::PLAYER.player_inventory_index(45), None);
+        assert_eq!(chest.from_player_inventory_index(44), Some(44));
+        assert_eq!(
+            MenuLayout::of(Menu::Hopper).from_player_inventory_index(36),
+            Some(32)
+        );
+        assert_eq!(
+            MenuLayout::of(Menu::Lectern).from_player_inventory_index(9),
+            None
+        );
+
+        // the first hotbar slot, the helmet, and the offhand
+        assert_eq!(MenuLayout::from_inventory_index(0), Some(36));
+        assert_eq!(MenuLayout::from_inventory_index(39), Some(5));
+        assert_eq!(MenuLayout::from_inventory_index(40), Some(45));
+    }
+}
@@ -0,0 +1,20 @@
+//! The player's inventory and the containers that the server can open, like
+//! chests and furnaces.
+//!
+//! A [`Container`] keeps track of the slots that the server sent for a menu,
+//! and its [`MenuLayout`] says which of those slots are which, like a
+//! furnace's fuel slot or the player's hotbar. [`Container::click`] predicts
+//! what clicking its slots does and makes the packets to send for it, and
+//! [`ItemStack`] reads the NBT of the items in them.
+
+mod click;
+mod container;
+pub mod item;
+mod layout;
+mod stack;
+
+pub use azalea_core::{Slot, SlotData};
+pub use click::{ClickOperation, MouseButton};
+pub use container::{Container, PLAYER_CONTAINER_ID};
+pub use layout::MenuLayout;
+pub use stack::{ItemStack, PotionEffect};
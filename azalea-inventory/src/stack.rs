@@ -0,0 +1,354 @@
+use crate::item::{is_same_item, max_stack_size_of};
+use azalea_chat::Component;
+use azalea_core::SlotData;
+use azalea_nbt::Tag;
+use azalea_registry::{Enchantment, Item, MobEffect, Potion};
+use log::warn;
+
+/// A typed view of an item stack, which reads the common parts of its NBT
+/// like its name, enchantments, and damage.
+///
+/// ```
+/// # use azalea_inventory::{ItemStack, SlotData};
+/// # use azalea_registry::Enchantment;
+/// /// The sword with the most sharpness.
+/// fn best_sword(items: &[SlotData]) -> Option<&SlotData> {
+///     items
+///         .iter()
+///         .filter(|item| item.item.vanilla() == Some(&azalea_registry::Item::DiamondSword))
+///         .max_by_key(|item| ItemStack::new(item).enchantment_level(Enchantment::Sharpness))
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ItemStack<'a> {
+    data: &'a SlotData,
+}
+
+/// An effect that a potion gives, besides the ones from its [`Potion`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PotionEffect {
+    pub effect: MobEffect,
+    /// The level of the effect minus one.
+    pub amplifier: u8,
+    /// How long the effect lasts, in ticks.
+    pub duration: i32,
+}
+
+impl<'a> From<&'a SlotData> for ItemStack<'a> {
+    fn from(data: &'a SlotData) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<'a> ItemStack<'a> {
+    pub fn new(data: &'a SlotData) -> Self {
+        Self { data }
+    }
+
+    pub fn data(&self) -> &'a SlotData {
+        self.data
+    }
+
+    /// The item, or `None` if it's a modded item.
+    pub fn item(&self) -> Option<Item> {
+        self.data.item.vanilla().copied()
+    }
+
+    pub fn count(&self) -> u8 {
+        self.data.count
+    }
+
+    /// A tag in the root of the item's NBT.
+    pub fn tag(&self, name: &str) -> Option<&'a Tag> {
+        // the NBT is a compound with one unnamed compound in it, which has the
+        // item's tags
+        self.data
+            .nbt
+            .as_compound()?
+            .get("")?
+            .as_compound()?
+            .get(name)
+    }
+
+    /// The name the item was renamed to, if it was.
+    pub fn display_name(&self) -> Option<Component> {
+        let Some(Tag::Compound(display)) = self.tag("display") else {
+            return None;
+        };
+        match display.get("Name") {
+            Some(Tag::String(name)) => parse_component(name),
+            _ => None,
+        }
+    }
+
+    /// The lines of text shown under the item's name.
+    pub fn lore(&self) -> Vec<Component> {
+        let Some(Tag::Compound(display)) = self.tag("display") else {
+            return Vec::new();
+        };
+        let Some(Tag::List(lore)) = display.get("Lore") else {
+            return Vec::new();
+        };
+        lore.iter()
+            .filter_map(|line| match line {
+                Tag::String(line) => parse_component(line),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The enchantments on the item and their levels.
+    pub fn enchantments(&self) -> Vec<(Enchantment, u32)> {
+        read_enchantments(self.tag("Enchantments"))
+    }
+
+    /// The enchantments that an enchanted book can apply, which aren't
+    /// enchantments on the book itself.
+    pub fn stored_enchantments(&self) -> Vec<(Enchantment, u32)> {
+        read_enchantments(self.tag("StoredEnchantments"))
+    }
+
+    /// The level of an enchantment on the item, or 0 if it doesn't have it.
+    pub fn enchantment_level(&self, enchantment: Enchantment) -> u32 {
+        self.enchantments()
+            .into_iter()
+            .find(|(e, _)| *e == enchantment)
+            .map_or(0, |(_, level)| level)
+    }
+
+    /// How much durability the item has lost.
+    pub fn damage(&self) -> u32 {
+        match self.tag("Damage") {
+            Some(Tag::Int(damage)) => (*damage).max(0) as u32,
+            _ => 0,
+        }
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.damage() > 0
+    }
+
+    pub fn is_unbreakable(&self) -> bool {
+        matches!(self.tag("Unbreakable"), Some(Tag::Byte(b)) if *b != 0)
+    }
+
+    /// The number resource packs use to give the item a different model.
+    pub fn custom_model_data(&self) -> Option<i32> {
+        match self.tag("CustomModelData") {
+            Some(Tag::Int(data)) => Some(*data),
+            _ => None,
+        }
+    }
+
+    /// The potion that a potion, splash potion, lingering potion, or tipped
+    /// arrow has.
+    pub fn potion(&self) -> Option<Potion> {
+        match self.tag("Potion") {
+            Some(Tag::String(potion)) => parse_id(potion),
+            _ => None,
+        }
+    }
+
+    /// The effects a potion gives besides the ones from its [`potion`].
+    ///
+    /// [`potion`]: ItemStack::potion
+    pub fn custom_potion_effects(&self) -> Vec<PotionEffect> {
+        let Some(Tag::List(effects)) = self.tag("CustomPotionEffects") else {
+            return Vec::new();
+        };
+        effects
+            .iter()
+            .filter_map(|effect| {
+                let Tag::Compound(effect) = effect else {
+                    return None;
+                };
+                let Some(Tag::Byte(id)) = effect.get("Id") else {
+                    return None;
+                };
+                Some(PotionEffect {
+                    effect: MobEffect::try_from(*id as u8 as u32).ok()?,
+                    amplifier: match effect.get("Amplifier") {
+                        Some(Tag::Byte(amplifier)) => *amplifier as u8,
+                        _ => 0,
+                    },
+                    duration: match effect.get("Duration") {
+                        Some(Tag::Int(duration)) => *duration,
+                        _ => 0,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// The most of this item that fits in one slot.
+    pub fn max_stack_size(&self) -> u8 {
+        max_stack_size_of(self.data)
+    }
+
+    /// Whether more than one of this item can be in a slot. Damaged items
+    /// never stack.
+    pub fn is_stackable(&self) -> bool {
+        self.max_stack_size() > 1 && !self.is_damaged()
+    }
+
+    /// Whether this item and `other` can be in the same slot, because
+    /// they're the same item with the same NBT and they stack.
+    pub fn is_stackable_with(&self, other: &ItemStack) -> bool {
+        self.is_stackable() && is_same_item(self.data, other.data)
+    }
+}
+
+/// Parse a text component from a JSON string in NBT.
+fn parse_component(json: &str) -> Option<Component> {
+    match serde_json::from_str(json) {
+        Ok(component) => Some(component),
+        Err(e) => {
+            warn!("Couldn't parse text component in item nbt: {e}");
+            None
+        }
+    }
+}
+
+/// Parse a registry id in NBT, which might not have a namespace.
+fn parse_id<T: std::str::FromStr>(id: &str) -> Option<T> {
+    if id.contains(':') {
+        id.parse().ok()
+    } else {
+        format!("minecraft:{id}").parse().ok()
+    }
+}
+
+fn read_enchantments(tag: Option<&Tag>) -> Vec<(Enchantment, u32)> {
+    let Some(Tag::List(enchantments)) = tag else {
+        return Vec::new();
+    };
+    enchantments
+        .iter()
+        .filter_map(|enchantment| {
+            let Tag::Compound(enchantment) = enchantment else {
+                return None;
+            };
+            let Some(Tag::String(id)) = enchantment.get("id") else {
+                return None;
+            };
+            let level = match enchantment.get("lvl") {
+                Some(Tag::Short(level)) => (*level).max(0) as u32,
+                Some(Tag::Int(level)) => (*level).max(0) as u32,
+                _ => 1,
+            };
+            Some((parse_id(id)?, level))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn compound(tags: Vec<(&str, Tag)>) -> Tag {
+        Tag::Compound(
+            tags.into_iter()
+                .map(|(name, tag)| (name.to_string(), tag))
+                .collect(),
+        )
+    }
+
+    /// Item NBT decoded from the bytes a server would send for it.
+    fn nbt(tags: Vec<(&str, Tag)>) -> Tag {
+        // the root compound's type and its empty name
+        let mut bytes = vec![10, 0, 0];
+        compound(tags).write_without_end(&mut bytes).unwrap();
+        Tag::read(&mut Cursor::new(&bytes[..])).unwrap()
+    }
+
+    #[test]
+    fn test_item_stack() {
+        let sword = SlotData {
+            nbt: nbt(vec![
+                (
+                    "display",
+                    compound(vec![
+                        ("Name", Tag::String(r#"{"text":"Slicer"}"#.to_string())),
+                        (
+                            "Lore",
+                            Tag::List(vec![Tag::String(r#""Very sharp""#.to_string())]),
+                        ),
+                    ]),
+                ),
+                (
+                    "Enchantments",
+                    Tag::List(vec![
+                        compound(vec![
+                            ("id", Tag::String("minecraft:sharpness".to_string())),
+                            ("lvl", Tag::Short(5)),
+                        ]),
+                        compound(vec![
+                            ("id", Tag::String("unbreaking".to_string())),
+                            ("lvl", Tag::Short(3)),
+                        ]),
+                    ]),
+                ),
+                ("Damage", Tag::Int(12)),
+                ("CustomModelData", Tag::Int(4)),
+            ]),
+            ..SlotData::new(Item::DiamondSword, 1)
+        };
+        let sword = ItemStack::new(&sword);
+        assert_eq!(sword.item(), Some(Item::DiamondSword));
+        assert_eq!(sword.display_name().unwrap().to_string(), "Slicer");
+        assert_eq!(sword.lore()[0].to_string(), "Very sharp");
+        assert_eq!(sword.enchantment_level(Enchantment::Sharpness), 5);
+        assert_eq!(sword.enchantment_level(Enchantment::Unbreaking), 3);
+        assert_eq!(sword.enchantment_level(Enchantment::Mending), 0);
+        assert_eq!(sword.damage(), 12);
+        assert!(!sword.is_unbreakable());
+        assert_eq!(sword.custom_model_data(), Some(4));
+        assert!(!sword.is_stackable());
+
+        let potion = SlotData {
+            nbt: nbt(vec![
+                (
+                    "Potion",
+                    Tag::String("minecraft:strong_healing".to_string()),
+                ),
+                (
+                    "CustomPotionEffects",
+                    Tag::List(vec![compound(vec![
+                        ("Id", Tag::Byte(1)),
+                        ("Amplifier", Tag::Byte(2)),
+                        ("Duration", Tag::Int(200)),
+                    ])]),
+                ),
+            ]),
+            ..SlotData::new(Item::Potion, 1)
+        };
+        let potion = ItemStack::new(&potion);
+        assert_eq!(potion.potion(), Some(Potion::StrongHealing));
+        assert_eq!(
+            potion.custom_potion_effects(),
+            vec![PotionEffect {
+                effect: MobEffect::Speed,
+                amplifier: 2,
+                duration: 200,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stackable_with() {
+        let stone = SlotData::new(Item::Stone, 1);
+        let named_stone = SlotData {
+            nbt: nbt(vec![(
+                "display",
+                compound(vec![("Name", Tag::String(r#""Rock""#.to_string()))]),
+            )]),
+            ..SlotData::new(Item::Stone, 1)
+        };
+        let pearl = SlotData::new(Item::EnderPearl, 1);
+        assert!(ItemStack::new(&stone).is_stackable_with(&ItemStack::new(&stone)));
+        assert!(!ItemStack::new(&stone).is_stackable_with(&ItemStack::new(&named_stone)));
+        assert!(!ItemStack::new(&stone).is_stackable_with(&ItemStack::new(&pearl)));
+        assert_eq!(ItemStack::new(&pearl).max_stack_size(), 16);
+    }
+}
@@ -3,7 +3,7 @@ use quote::quote;
 use syn::{
     self, braced,
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, DeriveInput, FieldsNamed, Ident, LitInt, Token,
+    parse_macro_input, DeriveInput, FieldsNamed, Ident, LitInt, Path, Token,
 };
 
 fn as_packet_derive(input: TokenStream, state: proc_macro2::TokenStream) -> TokenStream {
@@ -73,6 +73,13 @@ pub fn derive_serverbound_forge_packet(input: TokenStream) -> TokenStream {
         quote! {crate::packets::forge::ServerboundForgePacket},
     )
 }
+#[proc_macro_derive(ServerboundFml2Packet, attributes(var))]
+pub fn derive_serverbound_fml2_packet(input: TokenStream) -> TokenStream {
+    as_packet_derive(
+        input,
+        quote! {crate::packets::forge::fml2::ServerboundFml2Packet},
+    )
+}
 
 #[proc_macro_derive(ClientboundGamePacket, attributes(var))]
 pub fn derive_clientbound_game_packet(input: TokenStream) -> TokenStream {
@@ -106,12 +113,22 @@ pub fn derive_clientbound_forge_packet(input: TokenStream) -> TokenStream {
         quote! {crate::packets::forge::ClientboundForgePacket},
     )
 }
+#[proc_macro_derive(ClientboundFml2Packet, attributes(var))]
+pub fn derive_clientbound_fml2_packet(input: TokenStream) -> TokenStream {
+    as_packet_derive(
+        input,
+        quote! {crate::packets::forge::fml2::ClientboundFml2Packet},
+    )
+}
 
 #[derive(Debug)]
 struct PacketIdPair {
     id: u32,
     module: Ident,
     name: Ident,
+    /// The type the packet is read and written as instead of the packet
+    /// itself, used when an older protocol version has a different layout.
+    compat: Option<Path>,
 }
 #[derive(Debug)]
 struct PacketIdMap {
@@ -136,11 +153,18 @@ impl Parse for PacketIdMap {
             input.parse::<Token![::]>()?;
             // ClientboundChangeDifficultyPacket
             let name: Ident = input.parse()?;
+            // as v760::ClientboundChangeDifficultyPacket (optional)
+            let compat = if input.parse::<Token![as]>().is_ok() {
+                Some(input.parse()?)
+            } else {
+                None
+            };
 
             packets.push(PacketIdPair {
                 id: packet_id,
                 module,
                 name,
+                compat,
             });
 
             if input.parse::<Token![,]>().is_err() {
@@ -152,11 +176,53 @@ impl Parse for PacketIdMap {
     }
 }
 
+/// The ids (and layouts, if they changed) that the packets of a state have in
+/// an older protocol version. Packets that aren't listed don't exist in that
+/// version.
+#[derive(Debug)]
+struct VersionPackets {
+    version: u32,
+    serverbound: PacketIdMap,
+    clientbound: PacketIdMap,
+}
+
 #[derive(Debug)]
 struct DeclareStatePackets {
     name: Ident,
     serverbound: PacketIdMap,
     clientbound: PacketIdMap,
+    versions: Vec<VersionPackets>,
+}
+
+/// Parse `Serverbound => { ... }, Clientbound => { ... }`
+fn parse_bound_maps(input: ParseStream) -> Result<(PacketIdMap, PacketIdMap)> {
+    let serverbound_token: Ident = input.parse()?;
+    if serverbound_token != "Serverbound" {
+        return Err(syn::Error::new(
+            serverbound_token.span(),
+            "Expected `Serverbound`",
+        ));
+    }
+    input.parse::<Token![=>]>()?;
+    let content;
+    braced!(content in input);
+    let serverbound = content.parse()?;
+
+    input.parse::<Token![,]>()?;
+
+    let clientbound_token: Ident = input.parse()?;
+    if clientbound_token != "Clientbound" {
+        return Err(syn::Error::new(
+            clientbound_token.span(),
+            "Expected `Clientbound`",
+        ));
+    }
+    input.parse::<Token![=>]>()?;
+    let content;
+    braced!(content in input);
+    let clientbound = content.parse()?;
+
+    Ok((serverbound, clientbound))
 }
 
 impl Parse for DeclareStatePackets {
@@ -164,47 +230,239 @@ impl Parse for DeclareStatePackets {
         let name = input.parse()?;
         input.parse::<Token![,]>()?;
 
-        let serverbound_token: Ident = input.parse()?;
-        if serverbound_token != "Serverbound" {
-            return Err(syn::Error::new(
-                serverbound_token.span(),
-                "Expected `Serverbound`",
-            ));
+        let (serverbound, clientbound) = parse_bound_maps(input)?;
+        for packet in serverbound.packets.iter().chain(&clientbound.packets) {
+            if let Some(compat) = &packet.compat {
+                return Err(syn::Error::new_spanned(
+                    compat,
+                    "Only older versions can have a different layout",
+                ));
+            }
         }
-        input.parse::<Token![=>]>()?;
-        let content;
-        braced!(content in input);
-        let serverbound = content.parse()?;
 
-        input.parse::<Token![,]>()?;
-
-        let clientbound_token: Ident = input.parse()?;
-        if clientbound_token != "Clientbound" {
-            return Err(syn::Error::new(
-                clientbound_token.span(),
-                "Expected `Clientbound`",
-            ));
+        // example:
+        // Versions => {
+        //     760 => {
+        //         Serverbound => { ... },
+        //         Clientbound => { ... },
+        //     },
+        // }
+        let mut versions = Vec::new();
+        if input.parse::<Token![,]>().is_ok() && !input.is_empty() {
+            let versions_token: Ident = input.parse()?;
+            if versions_token != "Versions" {
+                return Err(syn::Error::new(
+                    versions_token.span(),
+                    "Expected `Versions`",
+                ));
+            }
+            input.parse::<Token![=>]>()?;
+            let content;
+            braced!(content in input);
+            while let Ok(version) = content.parse::<LitInt>() {
+                let version = version.base10_parse::<u32>()?;
+                content.parse::<Token![=>]>()?;
+                let version_content;
+                braced!(version_content in content);
+                let (serverbound, clientbound) = parse_bound_maps(&version_content)?;
+                let _ = version_content.parse::<Token![,]>();
+                versions.push(VersionPackets {
+                    version,
+                    serverbound,
+                    clientbound,
+                });
+                if content.parse::<Token![,]>().is_err() {
+                    break;
+                }
+            }
+            let _ = input.parse::<Token![,]>();
         }
-        input.parse::<Token![=>]>()?;
-        let content;
-        braced!(content in input);
-        let clientbound = content.parse()?;
 
         Ok(DeclareStatePackets {
             name,
             serverbound,
             clientbound,
+            versions,
         })
     }
 }
+
+/// Generate a match arm that reads the packet with the given id and returns it
+/// as a variant of the state enum.
+fn read_match_arm(
+    state_name: &Ident,
+    PacketIdPair {
+        id,
+        module,
+        name,
+        compat,
+    }: &PacketIdPair,
+) -> proc_macro2::TokenStream {
+    let name_litstr = syn::LitStr::new(&name.to_string(), name.span());
+    let read = match compat {
+        Some(compat) => {
+            let variant_name = variant_name_from(name);
+            quote! {
+                <#compat as azalea_buf::McBufReadable>::read_from(buf)
+                    .map(|packet| #state_name::#variant_name(packet.into()))
+            }
+        }
+        None => quote! { #module::#name::read(buf) },
+    };
+    quote! {
+        #id => {
+            let data = #read.map_err(|e| crate::read::ReadPacketError::Parse {
+                source: e,
+                packet_id: #id,
+                backtrace: Box::new(std::backtrace::Backtrace::capture()),
+                packet_name: #name_litstr.to_string(),
+            })?;
+            #[cfg(debug_assertions)]
+            {
+                let mut leftover = Vec::new();
+                let _ = std::io::Read::read_to_end(buf, &mut leftover);
+                if !leftover.is_empty() {
+                    return Err(
+                        Box::new(
+                            crate::read::ReadPacketError::LeftoverData {
+                                packet_name: #name_litstr.to_string(),
+                                data: leftover
+                            }
+                        )
+                    );
+                }
+            }
+            data
+        },
+    }
+}
+
+/// Generate the `id_for`, `kind_for_id`, `read_for`, and `write_for`
+/// functions of `ProtocolPacket`. Versions that aren't declared are the same
+/// as the latest version.
+fn versioned_functions(
+    state_name: &Ident,
+    kind_name: &Ident,
+    state_name_litstr: &syn::LitStr,
+    versions: &[(u32, &PacketIdMap)],
+) -> proc_macro2::TokenStream {
+    let mut id_for_match_contents = quote!();
+    let mut kind_for_id_match_contents = quote!();
+    let mut read_for_match_contents = quote!();
+    let mut write_for_match_contents = quote!();
+
+    for (version, packets) in versions {
+        let mut id_match_contents = quote!();
+        let mut kind_match_contents = quote!();
+        let mut read_match_contents = quote!();
+        let mut write_match_contents = quote!();
+        for packet in &packets.packets {
+            let id = packet.id;
+            let variant_name = variant_name_from(&packet.name);
+            id_match_contents.extend(quote! {
+                #state_name::#variant_name(_packet) => Some(#id),
+            });
+            kind_match_contents.extend(quote! {
+                #id => Some(#kind_name::#variant_name),
+            });
+            read_match_contents.extend(read_match_arm(state_name, packet));
+            write_match_contents.extend(match &packet.compat {
+                Some(compat) => quote! {
+                    #state_name::#variant_name(packet) => azalea_buf::McBufWritable::write_into(
+                        &<#compat>::from(packet.clone()),
+                        buf,
+                    ),
+                },
+                None => quote! {
+                    #state_name::#variant_name(packet) => packet.write(buf),
+                },
+            });
+        }
+
+        id_for_match_contents.extend(quote! {
+            #version => match self {
+                #id_match_contents
+                _ => None,
+            },
+        });
+        kind_for_id_match_contents.extend(quote! {
+            #version => match id {
+                #kind_match_contents
+                _ => None,
+            },
+        });
+        read_for_match_contents.extend(quote! {
+            #version => Ok(match id {
+                #read_match_contents
+                _ => return Err(Box::new(crate::read::ReadPacketError::UnknownPacketId { state_name: #state_name_litstr.to_string(), id })),
+            }),
+        });
+        write_for_match_contents.extend(quote! {
+            #version => match self {
+                #write_match_contents
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{:?} can't be sent in protocol version {}", self, version),
+                )),
+            },
+        });
+    }
+
+    quote! {
+        #[allow(unreachable_patterns)]
+        fn id_for(&self, version: crate::packets::ProtocolVersion) -> Option<u32> {
+            match version.id() {
+                #id_for_match_contents
+                _ => Some(self.id()),
+            }
+        }
+
+        #[allow(unreachable_patterns)]
+        fn kind_for_id(version: crate::packets::ProtocolVersion, id: u32) -> Option<#kind_name> {
+            match version.id() {
+                #kind_for_id_match_contents
+                _ => Self::kind_for_latest_id(id),
+            }
+        }
+
+        #[allow(unreachable_patterns)]
+        fn read_for(
+            version: crate::packets::ProtocolVersion,
+            id: u32,
+            buf: &mut std::io::Cursor<&[u8]>,
+        ) -> Result<#state_name, Box<crate::read::ReadPacketError>> {
+            match version.id() {
+                #read_for_match_contents
+                _ => Self::read(id, buf),
+            }
+        }
+
+        #[allow(unreachable_patterns)]
+        fn write_for(
+            &self,
+            version: crate::packets::ProtocolVersion,
+            buf: &mut impl std::io::Write,
+        ) -> Result<(), std::io::Error> {
+            match version.id() {
+                #write_for_match_contents
+                _ => self.write(buf),
+            }
+        }
+    }
+}
+
 #[proc_macro]
 pub fn declare_state_packets(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeclareStatePackets);
 
     let serverbound_state_name =
         Ident::new(&format!("Serverbound{}", input.name), input.name.span());
+    let serverbound_kind_name =
+        Ident::new(&format!("Serverbound{}Kind", input.name), input.name.span());
     let clientbound_state_name =
         Ident::new(&format!("Clientbound{}", input.name), input.name.span());
+    let clientbound_kind_name =
+        Ident::new(&format!("Clientbound{}Kind", input.name), input.name.span());
 
     let state_name_litstr = syn::LitStr::new(&input.name.to_string(), input.name.span());
 
@@ -218,12 +476,20 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
     let mut serverbound_write_match_contents = quote!();
     let mut clientbound_write_match_contents = quote!();
     let mut serverbound_read_match_contents = quote!();
+    let mut serverbound_kind_enum_contents = quote!();
+    let mut serverbound_kind_match_contents = quote!();
+    let mut serverbound_kind_for_id_match_contents = quote!();
     let mut clientbound_read_match_contents = quote!();
+    let mut clientbound_kind_enum_contents = quote!();
+    let mut clientbound_kind_match_contents = quote!();
+    let mut clientbound_kind_for_id_match_contents = quote!();
 
-    for PacketIdPair { id, module, name } in input.serverbound.packets {
-        let variant_name = variant_name_from(&name);
+    for packet in &input.serverbound.packets {
+        let PacketIdPair {
+            id, module, name, ..
+        } = packet;
+        let variant_name = variant_name_from(name);
 
-        let name_litstr = syn::LitStr::new(&name.to_string(), name.span());
         serverbound_enum_contents.extend(quote! {
             #variant_name(#module::#name),
         });
@@ -233,29 +499,22 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         serverbound_write_match_contents.extend(quote! {
             #serverbound_state_name::#variant_name(packet) => packet.write(buf),
         });
-        serverbound_read_match_contents.extend(quote! {
-            #id => {
-                let data = #module::#name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
-                    source: e,
-                    packet_id: #id,
-                    backtrace: Box::new(std::backtrace::Backtrace::capture()),
-                    packet_name: #name_litstr.to_string(),
-                })?;
-                #[cfg(debug_assertions)]
-                {
-                    let mut leftover = Vec::new();
-                    let _ = std::io::Read::read_to_end(buf, &mut leftover);
-                    if !leftover.is_empty() {
-                        return Err(Box::new(crate::read::ReadPacketError::LeftoverData { packet_name: #name_litstr.to_string(), data: leftover }));
-                    }
-                }
-                data
-            },
+        serverbound_read_match_contents.extend(read_match_arm(&serverbound_state_name, packet));
+        serverbound_kind_enum_contents.extend(quote! {
+            #variant_name,
+        });
+        serverbound_kind_match_contents.extend(quote! {
+            #serverbound_state_name::#variant_name(_packet) => #serverbound_kind_name::#variant_name,
+        });
+        serverbound_kind_for_id_match_contents.extend(quote! {
+            #id => Some(#serverbound_kind_name::#variant_name),
         });
     }
-    for PacketIdPair { id, module, name } in input.clientbound.packets {
-        let name_litstr = syn::LitStr::new(&name.to_string(), name.span());
-        let variant_name = variant_name_from(&name);
+    for packet in &input.clientbound.packets {
+        let PacketIdPair {
+            id, module, name, ..
+        } = packet;
+        let variant_name = variant_name_from(name);
 
         clientbound_enum_contents.extend(quote! {
             #variant_name(#module::#name),
@@ -266,34 +525,39 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         clientbound_write_match_contents.extend(quote! {
             #clientbound_state_name::#variant_name(packet) => packet.write(buf),
         });
-        clientbound_read_match_contents.extend(quote! {
-            #id => {
-                let data = #module::#name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
-                    source: e,
-                    packet_id: #id,
-                    backtrace: Box::new(std::backtrace::Backtrace::capture()),
-                    packet_name: #name_litstr.to_string(),
-                })?;
-                #[cfg(debug_assertions)]
-                {
-                    let mut leftover = Vec::new();
-                    let _ = std::io::Read::read_to_end(buf, &mut leftover);
-                    if !leftover.is_empty() {
-                        return Err(
-                            Box::new(
-                                crate::read::ReadPacketError::LeftoverData {
-                                    packet_name: #name_litstr.to_string(),
-                                    data: leftover
-                                }
-                            )
-                        );
-                    }
-                }
-                data
-            },
+        clientbound_read_match_contents.extend(read_match_arm(&clientbound_state_name, packet));
+        clientbound_kind_enum_contents.extend(quote! {
+            #variant_name,
+        });
+        clientbound_kind_match_contents.extend(quote! {
+            #clientbound_state_name::#variant_name(_packet) => #clientbound_kind_name::#variant_name,
+        });
+        clientbound_kind_for_id_match_contents.extend(quote! {
+            #id => Some(#clientbound_kind_name::#variant_name),
         });
     }
 
+    let serverbound_versioned_functions = versioned_functions(
+        &serverbound_state_name,
+        &serverbound_kind_name,
+        &state_name_litstr,
+        &input
+            .versions
+            .iter()
+            .map(|v| (v.version, &v.serverbound))
+            .collect::<Vec<_>>(),
+    );
+    let clientbound_versioned_functions = versioned_functions(
+        &clientbound_state_name,
+        &clientbound_kind_name,
+        &state_name_litstr,
+        &input
+            .versions
+            .iter()
+            .map(|v| (v.version, &v.clientbound))
+            .collect::<Vec<_>>(),
+    );
+
     if !has_serverbound_packets {
         serverbound_id_match_contents.extend(quote! {
             _ => unreachable!("This enum is empty and can't exist.")
@@ -301,6 +565,9 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         serverbound_write_match_contents.extend(quote! {
             _ => unreachable!("This enum is empty and can't exist.")
         });
+        serverbound_kind_match_contents.extend(quote! {
+            _ => unreachable!("This enum is empty and can't exist.")
+        });
     }
     if !has_clientbound_packets {
         clientbound_id_match_contents.extend(quote! {
@@ -309,10 +576,14 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         clientbound_write_match_contents.extend(quote! {
             _ => unreachable!("This enum is empty and can't exist.")
         });
+        clientbound_kind_match_contents.extend(quote! {
+            _ => unreachable!("This enum is empty and can't exist.")
+        });
     }
 
     let mut contents = quote! {
         #[derive(Clone, Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum #serverbound_state_name
         where
         Self: Sized,
@@ -320,12 +591,28 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
             #serverbound_enum_contents
         }
         #[derive(Clone, Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum #clientbound_state_name
         where
             Self: Sized,
         {
             #clientbound_enum_contents
         }
+
+        /// Which packet a
+        #[doc = concat!("[`", stringify!(#serverbound_state_name), "`]")]
+        /// is, without its data.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum #serverbound_kind_name {
+            #serverbound_kind_enum_contents
+        }
+        /// Which packet a
+        #[doc = concat!("[`", stringify!(#clientbound_state_name), "`]")]
+        /// is, without its data.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum #clientbound_kind_name {
+            #clientbound_kind_enum_contents
+        }
     };
 
     contents.extend(quote! {
@@ -343,6 +630,14 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                 }
             }
 
+            type Kind = #serverbound_kind_name;
+
+            fn kind(&self) -> #serverbound_kind_name {
+                match self {
+                    #serverbound_kind_match_contents
+                }
+            }
+
             /// Read a packet by its id, ConnectionProtocol, and flow
             fn read(
                 id: u32,
@@ -356,6 +651,18 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                     _ => return Err(Box::new(crate::read::ReadPacketError::UnknownPacketId { state_name: #state_name_litstr.to_string(), id })),
                 })
             }
+
+            #serverbound_versioned_functions
+        }
+
+        impl #serverbound_state_name {
+            #[allow(unreachable_patterns)]
+            fn kind_for_latest_id(id: u32) -> Option<#serverbound_kind_name> {
+                match id {
+                    #serverbound_kind_for_id_match_contents
+                    _ => None,
+                }
+            }
         }
     });
 
@@ -374,6 +681,14 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                 }
             }
 
+            type Kind = #clientbound_kind_name;
+
+            fn kind(&self) -> #clientbound_kind_name {
+                match self {
+                    #clientbound_kind_match_contents
+                }
+            }
+
             /// Read a packet by its id, ConnectionProtocol, and flow
             fn read(
                 id: u32,
@@ -387,6 +702,18 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
                     _ => return Err(Box::new(crate::read::ReadPacketError::UnknownPacketId { state_name: #state_name_litstr.to_string(), id })),
                 })
             }
+
+            #clientbound_versioned_functions
+        }
+
+        impl #clientbound_state_name {
+            #[allow(unreachable_patterns)]
+            fn kind_for_latest_id(id: u32) -> Option<#clientbound_kind_name> {
+                match id {
+                    #clientbound_kind_for_id_match_contents
+                    _ => None,
+                }
+            }
         }
     });
 
@@ -1,8 +1,9 @@
 //! A "simple" server that gets login information and proxies connections.
 //! After login all connections are encrypted and Azalea cannot read them.
+//! If you want to read them, use `azalea_client::mitm` instead.
 
 use azalea_protocol::{
-    connect::Connection,
+    connect::{Connection, ReadStream, WriteStream},
     packets::{
         handshake::{
             client_intention_packet::ClientIntentionPacket, ClientboundHandshakePacket,
@@ -155,7 +156,7 @@ async fn handle_connection(stream: TcpStream) -> anyhow::Result<()> {
                                 }
                             );
 
-                            tokio::spawn(transfer(conn.unwrap()?, intent, hello).map(|r| {
+                            tokio::spawn(transfer(conn.unwrap(), intent, hello).map(|r| {
                                 if let Err(e) = r {
                                     error!("Failed to proxy: {e}");
                                 }
@@ -185,7 +186,7 @@ async fn handle_connection(stream: TcpStream) -> anyhow::Result<()> {
 }
 
 async fn transfer(
-    mut inbound: TcpStream,
+    inbound: (ReadStream, WriteStream),
     intent: ClientIntentionPacket,
     hello: ServerboundHelloPacket,
 ) -> Result<(), Box<dyn Error>> {
@@ -202,12 +203,10 @@ async fn transfer(
     let mut outbound_conn = outbound_conn.login();
     outbound_conn.write(hello.get()).await?;
 
-    let mut outbound = outbound_conn.unwrap()?;
-
-    // Split the incoming and outgoing connections in
-    // halves and handle each pair on separate threads.
-    let (mut ri, mut wi) = inbound.split();
-    let (mut ro, mut wo) = outbound.split();
+    // Handle each pair of halves of the incoming and outgoing connections on
+    // separate threads.
+    let (mut ri, mut wi) = inbound;
+    let (mut ro, mut wo) = outbound_conn.unwrap();
 
     let client_to_server = async {
         io::copy(&mut ri, &mut wo).await?;
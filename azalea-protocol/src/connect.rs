@@ -5,40 +5,84 @@ use crate::packets::handshake::{ClientboundHandshakePacket, ServerboundHandshake
 use crate::packets::login::clientbound_hello_packet::ClientboundHelloPacket;
 use crate::packets::login::{ClientboundLoginPacket, ServerboundLoginPacket};
 use crate::packets::status::{ClientboundStatusPacket, ServerboundStatusPacket};
-use crate::packets::ProtocolPacket;
-use crate::read::{read_packet, ReadPacketError};
-use crate::write::write_packet;
+use crate::packets::{ProtocolPacket, ProtocolVersion};
+use crate::proxy::{Proxy, ProxyError};
+use crate::read::{deserialize_packet, read_raw_packet_with_size, ReadPacketError};
+use crate::stats::{SharedTrafficStats, TrafficStats};
+use crate::write::{encode_packet, encode_raw_packet};
+use crate::ServerAddress;
 use azalea_auth::game_profile::GameProfile;
 use azalea_auth::sessionserver::{ClientSessionServerError, ServerSessionServerError};
+use azalea_buf::remap::{with_remapper, IdRemapper};
+use azalea_buf::McBufVarReadable;
 use azalea_crypto::{Aes128CfbDec, Aes128CfbEnc};
 use bytes::BytesMut;
-use log::{error, info};
+use log::{error, info, warn};
+use parking_lot::{Mutex, RwLock};
 use std::fmt::Debug;
+use std::io::Cursor;
 use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
 use thiserror::Error;
-use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf, ReuniteError};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
 use uuid::Uuid;
 
+/// The stream that a [`ReadConnection`] reads from. This can be anything
+/// that implements [`AsyncRead`], like half of a TCP stream, a Unix socket, or
+/// a [`tokio::io::DuplexStream`].
+pub type ReadStream = Box<dyn AsyncRead + Send + Sync + Unpin>;
+/// The stream that a [`WriteConnection`] writes to. See [`ReadStream`].
+pub type WriteStream = Box<dyn AsyncWrite + Send + Sync + Unpin>;
+/// The remapper that a connection's reader and writer share. It's behind a
+/// lock so it can be replaced while the connection is being used, since some
+/// servers only send their ids once we're in the game.
+pub type SharedRemapper = Arc<RwLock<Option<Arc<dyn IdRemapper>>>>;
+
 /// The read half of a connection.
 pub struct ReadConnection<R: ProtocolPacket> {
-    pub read_stream: OwnedReadHalf,
+    pub read_stream: ReadStream,
     pub buffer: BytesMut,
     pub compression_threshold: Option<u32>,
     pub dec_cipher: Option<Aes128CfbDec>,
+    /// The protocol version that packets are read with.
+    pub version: ProtocolVersion,
+    /// Statistics about the packets we've read. Clone the `Arc` if you want
+    /// to keep watching them while the connection is being used.
+    pub stats: SharedTrafficStats<R::Kind>,
+    /// Translates the registry ids the server uses into vanilla ones while
+    /// packets are decoded. This is set for modded servers that told us their
+    /// ids.
+    pub remapper: SharedRemapper,
     _reading: PhantomData<R>,
 }
 
 /// The write half of a connection.
 pub struct WriteConnection<W: ProtocolPacket> {
-    pub write_stream: OwnedWriteHalf,
+    pub write_stream: WriteStream,
     pub compression_threshold: Option<u32>,
     pub enc_cipher: Option<Aes128CfbEnc>,
+    /// The protocol version that packets are written with.
+    pub version: ProtocolVersion,
+    /// Statistics about the packets we've written.
+    pub stats: SharedTrafficStats<W::Kind>,
+    /// Translates vanilla registry ids into the ones the server uses while
+    /// packets are encoded. This is the same as the
+    /// [`ReadConnection::remapper`].
+    pub remapper: SharedRemapper,
+    /// The bytes of the packets that were written while batching, which will
+    /// be sent on the next [`WriteConnection::flush`]. This is `None` if we're
+    /// not batching.
+    batch: Option<Vec<u8>>,
     _writing: PhantomData<W>,
 }
 
+/// If this many bytes are waiting to be sent while batching, they're sent
+/// immediately instead of waiting for a flush.
+const MAX_BATCH_SIZE: usize = 64 * 1024;
+
 /// A connection that can read and write packets.
 ///
 /// # Examples
@@ -132,13 +176,32 @@ where
 {
     /// Read a packet from the stream.
     pub async fn read(&mut self) -> Result<R, Box<ReadPacketError>> {
-        read_packet::<R, _>(
+        let data = self.read_raw().await?;
+        let start = Instant::now();
+        let packet = with_remapper(self.remapper.read().clone(), || {
+            deserialize_packet::<R>(&mut Cursor::new(&data[..]), self.version)
+        })?;
+        self.stats
+            .lock()
+            .record_decode(packet.kind(), start.elapsed());
+        Ok(packet)
+    }
+
+    /// Read the bytes of a packet from the stream without parsing it. You can
+    /// parse it later with [`crate::read::deserialize_packet`].
+    pub async fn read_raw(&mut self) -> Result<Vec<u8>, Box<ReadPacketError>> {
+        let (data, wire_size) = read_raw_packet_with_size(
             &mut self.read_stream,
             &mut self.buffer,
             self.compression_threshold,
             &mut self.dec_cipher,
         )
-        .await
+        .await?;
+        let kind = u32::var_read_from(&mut Cursor::new(&data[..]))
+            .ok()
+            .and_then(|id| R::kind_for_id(self.version, id));
+        self.stats.lock().record(kind, data.len(), wire_size);
+        Ok(data)
     }
 }
 impl<W> WriteConnection<W>
@@ -147,18 +210,54 @@ where
 {
     /// Write a packet to the server.
     pub async fn write(&mut self, packet: W) -> std::io::Result<()> {
-        if let Err(e) = write_packet(
+        let remapper = self.remapper.read().clone();
+        let (buf, size) = encode_packet(
             &packet,
-            &mut self.write_stream,
             self.compression_threshold,
             &mut self.enc_cipher,
+            self.version,
+            remapper,
         )
-        .await
-        {
+        .await?;
+        self.stats
+            .lock()
+            .record(Some(packet.kind()), size, buf.len());
+        self.send(buf).await
+    }
+
+    /// Write the bytes of a packet that was already serialized, like one from
+    /// [`ReadConnection::read_raw`]. They're sent as they are, so registry ids
+    /// aren't remapped.
+    pub async fn write_raw(&mut self, data: Vec<u8>) -> std::io::Result<()> {
+        let kind = u32::var_read_from(&mut Cursor::new(&data[..]))
+            .ok()
+            .and_then(|id| W::kind_for_id(self.version, id));
+        let size = data.len();
+        let buf = encode_raw_packet(data, self.compression_threshold, &mut self.enc_cipher).await;
+        self.stats.lock().record(kind, size, buf.len());
+        self.send(buf).await
+    }
+
+    /// Send the bytes of an encoded packet, or add them to the batch.
+    async fn send(&mut self, buf: Vec<u8>) -> std::io::Result<()> {
+        match &mut self.batch {
+            Some(batch) => {
+                batch.extend_from_slice(&buf);
+                if batch.len() >= MAX_BATCH_SIZE {
+                    self.flush().await?;
+                }
+                Ok(())
+            }
+            None => self.write_bytes(&buf).await,
+        }
+    }
+
+    async fn write_bytes(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        if let Err(e) = self.write_stream.write_all(buf).await {
             // detect broken pipe
             if e.kind() == std::io::ErrorKind::BrokenPipe {
                 info!("Broken pipe, shutting down connection.");
-                if let Err(e) = self.shutdown().await {
+                if let Err(e) = self.write_stream.shutdown().await {
                     error!("Couldn't shut down: {}", e);
                 }
             }
@@ -167,8 +266,47 @@ where
         Ok(())
     }
 
-    /// End the connection.
+    /// Whether packets are being batched. See
+    /// [`WriteConnection::set_batching`].
+    pub fn batching(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// When batching is enabled, packets aren't sent when they're written.
+    /// Instead, they're kept until [`WriteConnection::flush`] is called and
+    /// then sent all at once, which means fewer syscalls and TCP segments.
+    ///
+    /// Disabling batching sends any packets that are still waiting.
+    pub async fn set_batching(&mut self, batching: bool) -> std::io::Result<()> {
+        if batching {
+            self.batch.get_or_insert_with(Vec::new);
+            Ok(())
+        } else {
+            let result = self.flush().await;
+            self.batch = None;
+            result
+        }
+    }
+
+    /// Send the packets that were batched. This does nothing if we're not
+    /// batching or there's nothing to send.
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        let Some(batch) = &mut self.batch else {
+            return Ok(());
+        };
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let buf = std::mem::take(batch);
+        self.write_bytes(&buf).await?;
+        self.write_stream.flush().await
+    }
+
+    /// Send the packets that are waiting to be sent and end the connection.
     pub async fn shutdown(&mut self) -> std::io::Result<()> {
+        if let Err(e) = self.flush().await {
+            warn!("Couldn't send batched packets before shutting down: {e}");
+        }
         self.write_stream.shutdown().await
     }
 }
@@ -188,6 +326,26 @@ where
         self.writer.write(packet).await
     }
 
+    /// The protocol version that this connection reads and writes packets
+    /// with.
+    pub fn version(&self) -> ProtocolVersion {
+        self.reader.version
+    }
+
+    /// Change the protocol version that packets are read and written with.
+    /// Connections use the latest version by default, so you should set this
+    /// before sending the handshake if you want to use another version.
+    pub fn set_version(&mut self, version: ProtocolVersion) {
+        self.reader.version = version;
+        self.writer.version = version;
+    }
+
+    /// Replace the remapper that registry ids are read and written with.
+    pub fn set_remapper(&self, remapper: Option<Arc<dyn IdRemapper>>) {
+        *self.reader.remapper.write() = remapper.clone();
+        *self.writer.remapper.write() = remapper;
+    }
+
     /// Split the reader and writer into two objects. This doesn't allocate.
     pub fn into_split(self) -> (ReadConnection<R>, WriteConnection<W>) {
         (self.reader, self.writer)
@@ -198,6 +356,8 @@ where
 pub enum ConnectionError {
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Proxy(#[from] ProxyError),
 }
 
 impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
@@ -208,23 +368,18 @@ impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
         // enable tcp_nodelay
         stream.set_nodelay(true)?;
 
-        let (read_stream, write_stream) = stream.into_split();
+        Ok(Connection::wrap(stream))
+    }
 
-        Ok(Connection {
-            reader: ReadConnection {
-                read_stream,
-                buffer: BytesMut::new(),
-                compression_threshold: None,
-                dec_cipher: None,
-                _reading: PhantomData,
-            },
-            writer: WriteConnection {
-                write_stream,
-                compression_threshold: None,
-                enc_cipher: None,
-                _writing: PhantomData,
-            },
-        })
+    /// Create a new connection to the given address through a proxy. The
+    /// address should come from [`Proxy::resolve`].
+    pub async fn new_with_proxy(
+        address: &ServerAddress,
+        proxy: &Proxy,
+    ) -> Result<Self, ConnectionError> {
+        let stream = proxy.connect(address).await?;
+        stream.set_nodelay(true)?;
+        Ok(Connection::wrap(stream))
     }
 
     /// Change our state from handshake to login. This is the state that is used
@@ -417,12 +572,20 @@ where
                 buffer: connection.reader.buffer,
                 compression_threshold: connection.reader.compression_threshold,
                 dec_cipher: connection.reader.dec_cipher,
+                version: connection.reader.version,
+                // the packet types are different now, so the stats start over
+                stats: Arc::new(Mutex::new(TrafficStats::default())),
+                remapper: connection.reader.remapper,
                 _reading: PhantomData,
             },
             writer: WriteConnection {
                 compression_threshold: connection.writer.compression_threshold,
                 write_stream: connection.writer.write_stream,
                 enc_cipher: connection.writer.enc_cipher,
+                version: connection.writer.version,
+                stats: Arc::new(Mutex::new(TrafficStats::default())),
+                remapper: connection.writer.remapper,
+                batch: connection.writer.batch,
                 _writing: PhantomData,
             },
         }
@@ -431,26 +594,195 @@ where
     /// Convert an existing `TcpStream` into a `Connection`. Useful for servers.
     pub fn wrap(stream: TcpStream) -> Connection<R1, W1> {
         let (read_stream, write_stream) = stream.into_split();
+        Self::from_split(read_stream, write_stream)
+    }
 
+    /// Make a `Connection` from any stream that can be read from and written
+    /// to, like a Unix socket or a [`tokio::io::duplex`] pipe (which is
+    /// useful for tests).
+    ///
+    /// ```rust
+    /// use azalea_protocol::{
+    ///     connect::Connection,
+    ///     packets::handshake::{ClientboundHandshakePacket, ServerboundHandshakePacket},
+    /// };
+    ///
+    /// let (client, server) = tokio::io::duplex(1024);
+    /// let client: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> =
+    ///     Connection::from_stream(client);
+    /// let server: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> =
+    ///     Connection::from_stream(server);
+    /// ```
+    pub fn from_stream(
+        stream: impl AsyncRead + AsyncWrite + Send + Sync + 'static,
+    ) -> Connection<R1, W1> {
+        let (read_stream, write_stream) = tokio::io::split(stream);
+        Self::from_split(read_stream, write_stream)
+    }
+
+    /// Make a `Connection` from a stream that's already split into its read
+    /// and write halves.
+    pub fn from_split(
+        read_stream: impl AsyncRead + Send + Sync + Unpin + 'static,
+        write_stream: impl AsyncWrite + Send + Sync + Unpin + 'static,
+    ) -> Connection<R1, W1> {
+        let remapper = SharedRemapper::default();
         Connection {
             reader: ReadConnection {
-                read_stream,
+                read_stream: Box::new(read_stream),
                 buffer: BytesMut::new(),
                 compression_threshold: None,
                 dec_cipher: None,
+                version: ProtocolVersion::LATEST,
+                stats: Arc::new(Mutex::new(TrafficStats::default())),
+                remapper: remapper.clone(),
                 _reading: PhantomData,
             },
             writer: WriteConnection {
-                write_stream,
+                write_stream: Box::new(write_stream),
                 compression_threshold: None,
                 enc_cipher: None,
+                version: ProtocolVersion::LATEST,
+                stats: Arc::new(Mutex::new(TrafficStats::default())),
+                remapper,
+                batch: None,
                 _writing: PhantomData,
             },
         }
     }
 
-    /// Convert from a `Connection` into a `TcpStream`. Useful for servers.
-    pub fn unwrap(self) -> Result<TcpStream, ReuniteError> {
-        self.reader.read_stream.reunite(self.writer.write_stream)
+    /// Get the streams that this connection was made from back. Anything that
+    /// was already read into the buffer is lost.
+    pub fn unwrap(self) -> (ReadStream, WriteStream) {
+        (self.reader.read_stream, self.writer.write_stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::{
+        game::{
+            serverbound_keep_alive_packet::ServerboundKeepAlivePacket, ServerboundGamePacketKind,
+        },
+        handshake::{client_intention_packet::ClientIntentionPacket, ClientIdentifier},
+        ConnectionProtocol,
+    };
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket() {
+        let (client, server) = tokio::net::UnixStream::pair().unwrap();
+        let (client_read, client_write) = client.into_split();
+        let mut client: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> =
+            Connection::from_split(client_read, client_write);
+        let mut server: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> =
+            Connection::from_stream(server);
+
+        client
+            .write(
+                ClientIntentionPacket {
+                    protocol_version: ProtocolVersion::LATEST.id(),
+                    hostname: "localhost".to_string(),
+                    port: 25565,
+                    intention: ConnectionProtocol::Status,
+                    identifier: ClientIdentifier::Vanilla,
+                    forwarding: None,
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+        let ServerboundHandshakePacket::ClientIntention(intent) = server.read().await.unwrap();
+        assert_eq!(intent.hostname, "localhost");
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client: Connection<ClientboundGamePacket, ServerboundGamePacket> =
+            Connection::from_stream(client);
+        let mut server: Connection<ServerboundGamePacket, ClientboundGamePacket> =
+            Connection::from_stream(server);
+        client.writer.compression_threshold = Some(256);
+        server.reader.compression_threshold = Some(256);
+
+        for id in 0..3 {
+            client
+                .write(ServerboundKeepAlivePacket { id }.get())
+                .await
+                .unwrap();
+            server.read().await.unwrap();
+        }
+
+        let sent = client.writer.stats.lock().clone();
+        let received = server.reader.stats.lock().clone();
+        assert_eq!(sent.packets, 3);
+        assert_eq!(received.packets, 3);
+        assert_eq!(sent.bytes, received.bytes);
+        assert_eq!(sent.wire_bytes, received.wire_bytes);
+        // the length prefix and uncompressed size are extra
+        assert_eq!(sent.wire_bytes, sent.bytes + 2 * 3);
+        assert_eq!(sent.by_kind[&ServerboundGamePacketKind::KeepAlive].count, 3);
+        assert_eq!(
+            received.by_kind[&ServerboundGamePacketKind::KeepAlive].count,
+            3
+        );
+    }
+
+    /// A writer that remembers how many times it was written to.
+    #[derive(Clone, Default)]
+    struct CountingWriter(Arc<Mutex<Vec<usize>>>);
+    impl AsyncWrite for CountingWriter {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            self.0.lock().push(buf.len());
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batching() {
+        let writes = CountingWriter::default();
+        let mut conn: Connection<ClientboundGamePacket, ServerboundGamePacket> =
+            Connection::from_split(tokio::io::empty(), writes.clone());
+
+        conn.writer.set_batching(true).await.unwrap();
+        for id in 0..3 {
+            conn.write(ServerboundKeepAlivePacket { id }.get())
+                .await
+                .unwrap();
+        }
+        assert!(writes.0.lock().is_empty());
+
+        conn.writer.flush().await.unwrap();
+        let sizes = writes.0.lock().clone();
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0] as u64, conn.writer.stats.lock().wire_bytes);
+
+        // disabling batching sends the packets that are waiting
+        conn.write(ServerboundKeepAlivePacket { id: 3 }.get())
+            .await
+            .unwrap();
+        conn.writer.set_batching(false).await.unwrap();
+        conn.write(ServerboundKeepAlivePacket { id: 4 }.get())
+            .await
+            .unwrap();
+        assert_eq!(writes.0.lock().len(), 3);
     }
 }
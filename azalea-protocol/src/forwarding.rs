@@ -0,0 +1,289 @@
+//! Player information forwarding, which is how proxies like BungeeCord and
+//! Velocity tell the servers behind them who is actually connecting.
+//!
+//! BungeeCord adds the player's IP, UUID, and profile properties to the
+//! hostname in the handshake. Nothing is signed, so backends that use it must
+//! only be reachable from the proxy.
+//!
+//! Velocity's "modern" forwarding instead has the backend send a
+//! `velocity:player_info` login plugin query, which the proxy answers with
+//! the player's information signed with a secret that both of them know.
+
+use azalea_auth::game_profile::{GameProfile, ProfilePropertyValue};
+use azalea_buf::{BufReadError, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, io::Cursor, net::IpAddr, str::FromStr};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// The login plugin channel that Velocity uses for forwarding.
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+/// The only version of Velocity's forwarding that we support. Later versions
+/// add the player's chat signing key.
+pub const VELOCITY_FORWARDING_VERSION: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum ForwardingError {
+    #[error("{0}")]
+    Read(#[from] BufReadError),
+    #[error("The forwarded player information has an invalid signature")]
+    InvalidSignature,
+    #[error("Unsupported Velocity forwarding version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Invalid forwarded address {0:?}")]
+    InvalidAddress(String),
+    #[error("The proxy didn't forward any player information")]
+    NotForwarded,
+}
+
+/// How player information is forwarded to a backend server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForwardingMode {
+    /// BungeeCord's forwarding through the handshake hostname.
+    BungeeCord,
+    /// Velocity's modern forwarding, signed with the given secret. This is
+    /// the `forwarding-secret` from Velocity's config.
+    Velocity { secret: Vec<u8> },
+}
+
+/// Forwarding that a client does itself, as if it were a proxy. This lets
+/// bots connect straight to a server that's normally behind BungeeCord or
+/// Velocity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forwarding {
+    pub mode: ForwardingMode,
+    /// The IP address that the server will think the player has.
+    pub address: IpAddr,
+}
+
+impl Forwarding {
+    /// Forward like BungeeCord, pretending to connect from the given address.
+    pub fn bungeecord(address: IpAddr) -> Self {
+        Self {
+            mode: ForwardingMode::BungeeCord,
+            address,
+        }
+    }
+
+    /// Forward like Velocity with the given secret, pretending to connect from
+    /// the given address.
+    pub fn velocity(secret: impl Into<Vec<u8>>, address: IpAddr) -> Self {
+        Self {
+            mode: ForwardingMode::Velocity {
+                secret: secret.into(),
+            },
+            address,
+        }
+    }
+}
+
+/// A profile property as it's written in BungeeCord's handshake.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardedProperty {
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// The player information that BungeeCord adds to the handshake hostname, in
+/// the format `host\0ip\0uuid\0properties`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BungeeCordForwarding {
+    pub address: IpAddr,
+    pub uuid: Uuid,
+    pub properties: Vec<ForwardedProperty>,
+}
+
+impl BungeeCordForwarding {
+    /// Get the forwarded information for a profile.
+    pub fn new(address: IpAddr, profile: &GameProfile) -> Self {
+        Self {
+            address,
+            uuid: profile.uuid,
+            properties: profile
+                .properties
+                .iter()
+                .map(|(name, property)| ForwardedProperty {
+                    name: name.clone(),
+                    value: property.value.clone(),
+                    signature: property.signature.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The properties in the same shape that a [`GameProfile`] has them.
+    pub fn profile_properties(&self) -> HashMap<String, ProfilePropertyValue> {
+        self.properties
+            .iter()
+            .map(|p| {
+                (
+                    p.name.clone(),
+                    ProfilePropertyValue {
+                        value: p.value.clone(),
+                        signature: p.signature.clone(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Split the forwarded information from a hostname that was sent by
+    /// BungeeCord. If the hostname doesn't have any, it's returned unchanged.
+    pub fn split_from_hostname(hostname: String) -> (String, Option<Self>) {
+        let parts: Vec<&str> = hostname.split('\0').collect();
+        let (host, address, uuid, properties) = match parts[..] {
+            [host, address, uuid] => (host, address, uuid, None),
+            [host, address, uuid, properties] => (host, address, uuid, Some(properties)),
+            _ => return (hostname, None),
+        };
+        let (Ok(address), Ok(uuid)) = (IpAddr::from_str(address), Uuid::parse_str(uuid)) else {
+            return (hostname, None);
+        };
+        let properties = match properties.map(serde_json::from_str) {
+            Some(Ok(properties)) => properties,
+            Some(Err(_)) => return (hostname, None),
+            None => Vec::new(),
+        };
+        (
+            host.to_string(),
+            Some(Self {
+                address,
+                uuid,
+                properties,
+            }),
+        )
+    }
+}
+
+impl std::fmt::Display for BungeeCordForwarding {
+    /// The part that's added after the hostname, starting with a `\0`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\0{}\0{}", self.address, self.uuid.simple())?;
+        if !self.properties.is_empty() {
+            let properties =
+                serde_json::to_string(&self.properties).map_err(|_| std::fmt::Error)?;
+            write!(f, "\0{properties}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The data for a `velocity:player_info` query, which asks for the version
+/// of forwarding that we support.
+pub fn velocity_request() -> Vec<u8> {
+    vec![VELOCITY_FORWARDING_VERSION]
+}
+
+/// Make the signed answer to a `velocity:player_info` query. `request` is the
+/// data that was in the query.
+pub fn write_velocity_response(
+    secret: &[u8],
+    request: &[u8],
+    address: IpAddr,
+    profile: &GameProfile,
+) -> Vec<u8> {
+    // the server says the highest version it supports, and we use the highest
+    // version that we both support
+    let version = request
+        .first()
+        .copied()
+        .unwrap_or(VELOCITY_FORWARDING_VERSION)
+        .min(VELOCITY_FORWARDING_VERSION);
+
+    let mut data = Vec::new();
+    // writing to a Vec can't fail
+    (version as u32).var_write_into(&mut data).unwrap();
+    address.to_string().write_into(&mut data).unwrap();
+    // a game profile is written as its uuid, name, and properties, which is
+    // the same as velocity does it
+    profile.write_into(&mut data).unwrap();
+
+    let mut response = azalea_crypto::hmac_sha256(secret, &data).to_vec();
+    response.extend(data);
+    response
+}
+
+/// Check the signature of the answer to a `velocity:player_info` query and
+/// read the player's address and profile from it.
+pub fn read_velocity_response(
+    secret: &[u8],
+    response: &[u8],
+) -> Result<(IpAddr, GameProfile), ForwardingError> {
+    if response.len() < 32 {
+        return Err(ForwardingError::InvalidSignature);
+    }
+    let (signature, data) = response.split_at(32);
+    if !azalea_crypto::verify_hmac_sha256(secret, data, signature) {
+        return Err(ForwardingError::InvalidSignature);
+    }
+
+    let mut buf = Cursor::new(data);
+    let version = u32::var_read_from(&mut buf)?;
+    if version != VELOCITY_FORWARDING_VERSION as u32 {
+        return Err(ForwardingError::UnsupportedVersion(version));
+    }
+    let address = String::read_from(&mut buf)?;
+    let address =
+        IpAddr::from_str(&address).map_err(|_| ForwardingError::InvalidAddress(address))?;
+    let profile = GameProfile::read_from(&mut buf)?;
+    Ok((address, profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> GameProfile {
+        let mut profile = GameProfile::new(
+            Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap(),
+            "Notch".to_string(),
+        );
+        profile.properties.insert(
+            "textures".to_string(),
+            ProfilePropertyValue {
+                value: "abc".to_string(),
+                signature: Some("def".to_string()),
+            },
+        );
+        profile
+    }
+
+    #[test]
+    fn test_bungeecord_hostname() {
+        let forwarding = BungeeCordForwarding::new("10.0.0.5".parse().unwrap(), &profile());
+        let hostname = format!("localhost{forwarding}");
+        assert!(
+            hostname.starts_with("localhost\x0010.0.0.5\x00069a79f444e94726a5befca90e38aaf5\x00[")
+        );
+
+        let (host, parsed) = BungeeCordForwarding::split_from_hostname(hostname);
+        assert_eq!(host, "localhost");
+        assert_eq!(parsed.unwrap(), forwarding);
+
+        let (host, parsed) = BungeeCordForwarding::split_from_hostname("localhost".to_string());
+        assert_eq!(host, "localhost");
+        assert!(parsed.is_none());
+    }
+
+    #[test]
+    fn test_velocity_response() {
+        let address = "10.0.0.5".parse().unwrap();
+        let response = write_velocity_response(b"secret", &velocity_request(), address, &profile());
+
+        let (read_address, read_profile) = read_velocity_response(b"secret", &response).unwrap();
+        assert_eq!(read_address, address);
+        assert_eq!(read_profile, profile());
+
+        assert!(matches!(
+            read_velocity_response(b"wrong secret", &response),
+            Err(ForwardingError::InvalidSignature)
+        ));
+        assert!(matches!(
+            read_velocity_response(b"secret", &response[..16]),
+            Err(ForwardingError::InvalidSignature)
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+//! The server list ping from before Minecraft 1.7, which isn't framed like
+//! normal packets.
+//!
+//! The client sends `0xFE` (Beta 1.8 to 1.3), `0xFE 0x01` (1.4 and 1.5), or
+//! `0xFE 0x01` followed by an `MC|PingHost` plugin message (1.6). The server
+//! replies with a kick packet (`0xFF`) whose reason contains the status,
+//! separated by `§` for Beta clients and by `\0` (after a `§1` prefix) for
+//! everything newer.
+//!
+//! Modern servers still answer these, so it's also a way of pinging servers
+//! that are too old to understand the normal handshake.
+
+use crate::packets::status::clientbound_status_response_packet::{
+    ClientboundStatusResponsePacket, Players, Version,
+};
+use azalea_chat::{text_component::TextComponent, Component};
+use std::io;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// The protocol version of 1.6.4, the last version that used the legacy
+/// ping. This is what we send in our requests.
+pub const LEGACY_PROTOCOL_VERSION: u8 = 78;
+/// The protocol version modern servers reply with, so old clients know that
+/// they can't join.
+pub const LEGACY_INCOMPATIBLE_VERSION: i32 = 127;
+
+const PING_HOST_CHANNEL: &str = "MC|PingHost";
+
+#[derive(Error, Debug)]
+pub enum LegacyPingError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("Expected a legacy ping but got packet {0:#x}")]
+    UnexpectedPacket(u8),
+    #[error("Invalid legacy ping: {0}")]
+    Invalid(String),
+}
+
+/// A legacy ping sent by a client. Which one it is decides the format of the
+/// response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LegacyPingRequest {
+    /// Beta 1.8 to 1.3, which only send `0xFE`.
+    Beta,
+    /// 1.4 and 1.5, which send `0xFE 0x01`.
+    V1_4,
+    /// 1.6, which also says which version it is and the address it connected
+    /// to.
+    V1_6 {
+        protocol_version: u8,
+        hostname: String,
+        port: u16,
+    },
+}
+
+impl LegacyPingRequest {
+    /// Get the bytes that a client would send for this request.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = vec![0xFE];
+        match self {
+            Self::Beta => {}
+            Self::V1_4 => buf.push(0x01),
+            Self::V1_6 {
+                protocol_version,
+                hostname,
+                port,
+            } => {
+                buf.extend([0x01, 0xFA]);
+                write_utf16(&mut buf, PING_HOST_CHANNEL);
+                let hostname_length = hostname.encode_utf16().count() as u16;
+                buf.extend((7 + hostname_length * 2).to_be_bytes());
+                buf.push(*protocol_version);
+                write_utf16(&mut buf, hostname);
+                buf.extend((*port as i32).to_be_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Read a legacy ping from a client. The first byte has to be `0xFE`.
+    ///
+    /// Since Beta and 1.4 clients send so little, they're told apart by how
+    /// much was sent in the first write.
+    pub async fn read_from(
+        stream: &mut (impl AsyncRead + Unpin),
+    ) -> Result<LegacyPingRequest, LegacyPingError> {
+        let mut buf = [0; 512];
+        let n = stream.read(&mut buf).await?;
+        match &buf[..n] {
+            [] => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            [0xFE] => Ok(Self::Beta),
+            [0xFE, 0x01] => Ok(Self::V1_4),
+            [0xFE, 0x01, 0xFA, rest @ ..] => {
+                let mut stream = rest.chain(stream);
+                let channel = read_utf16(&mut stream).await?;
+                if channel != PING_HOST_CHANNEL {
+                    return Err(LegacyPingError::Invalid(format!(
+                        "Unknown channel {channel}"
+                    )));
+                }
+                let _length = stream.read_u16().await?;
+                let protocol_version = stream.read_u8().await?;
+                let hostname = read_utf16(&mut stream).await?;
+                let port = stream.read_i32().await? as u16;
+                Ok(Self::V1_6 {
+                    protocol_version,
+                    hostname,
+                    port,
+                })
+            }
+            [0xFE, ..] => Err(LegacyPingError::Invalid(
+                "Unknown data after 0xFE 0x01".to_string(),
+            )),
+            [id, ..] => Err(LegacyPingError::UnexpectedPacket(*id)),
+        }
+    }
+}
+
+/// Get the kick packet that a server would reply to the request with.
+///
+/// Only the plain text of the description is sent, and Beta clients get it
+/// without any `§`, since that's their separator.
+pub fn serialize_response(
+    request: &LegacyPingRequest,
+    status: &ClientboundStatusResponsePacket,
+) -> Vec<u8> {
+    let motd = status.description.to_string();
+    let reason = match request {
+        LegacyPingRequest::Beta => format!(
+            "{}§{}§{}",
+            motd.replace('§', ""),
+            status.players.online,
+            status.players.max
+        ),
+        LegacyPingRequest::V1_4 | LegacyPingRequest::V1_6 { .. } => format!(
+            "§1\0{}\0{}\0{}\0{}\0{}",
+            LEGACY_INCOMPATIBLE_VERSION,
+            status.version.name,
+            motd,
+            status.players.online,
+            status.players.max
+        ),
+    };
+    let mut buf = vec![0xFF];
+    write_utf16(&mut buf, &reason);
+    buf
+}
+
+/// Read the kick packet that a server replies to a legacy ping with and
+/// parse it.
+pub async fn read_response(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> Result<ClientboundStatusResponsePacket, LegacyPingError> {
+    let id = stream.read_u8().await?;
+    if id != 0xFF {
+        return Err(LegacyPingError::UnexpectedPacket(id));
+    }
+    let reason = read_utf16(stream).await?;
+    parse_response(&reason)
+}
+
+/// Convert the reason from a legacy ping's kick packet into the same shape as
+/// a normal status response.
+///
+/// Beta servers don't say what version they are, so the version will have an
+/// empty name and a protocol of -1.
+pub fn parse_response(reason: &str) -> Result<ClientboundStatusResponsePacket, LegacyPingError> {
+    let parse_count = |count: &str| {
+        count
+            .parse::<i32>()
+            .map_err(|_| LegacyPingError::Invalid(format!("Invalid player count {count:?}")))
+    };
+
+    let (version, motd, online, max) = if let Some(reason) = reason.strip_prefix("§1\0") {
+        let parts: Vec<&str> = reason.split('\0').collect();
+        let [protocol, name, motd, online, max] = parts[..] else {
+            return Err(LegacyPingError::Invalid(format!(
+                "Expected 6 fields but got {}",
+                parts.len() + 1
+            )));
+        };
+        let protocol = protocol
+            .parse()
+            .map_err(|_| LegacyPingError::Invalid(format!("Invalid protocol {protocol:?}")))?;
+        let version = Version {
+            name: name.to_string(),
+            protocol,
+        };
+        (version, motd, online, max)
+    } else {
+        // the motd can't contain §, so the last two are always the counts
+        let mut parts = reason.rsplitn(3, '§');
+        let (Some(max), Some(online), Some(motd)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(LegacyPingError::Invalid(format!(
+                "Unknown response {reason:?}"
+            )));
+        };
+        let version = Version {
+            name: String::new(),
+            protocol: -1,
+        };
+        (version, motd, online, max)
+    };
+
+    Ok(ClientboundStatusResponsePacket {
+        description: Component::Text(TextComponent::new(motd.to_string())),
+        favicon: None,
+        players: Players {
+            max: parse_count(max)?,
+            online: parse_count(online)?,
+            sample: Vec::new(),
+        },
+        version,
+        previews_chat: None,
+        enforces_secure_chat: None,
+        forge: None,
+    })
+}
+
+/// Write a string prefixed by its length in UTF-16 code units.
+fn write_utf16(buf: &mut Vec<u8>, string: &str) {
+    let units: Vec<u16> = string.encode_utf16().collect();
+    buf.extend((units.len() as u16).to_be_bytes());
+    for unit in units {
+        buf.extend(unit.to_be_bytes());
+    }
+}
+
+async fn read_utf16(stream: &mut (impl AsyncRead + Unpin)) -> Result<String, LegacyPingError> {
+    let length = stream.read_u16().await?;
+    let mut units = Vec::with_capacity(length as usize);
+    for _ in 0..length {
+        units.push(stream.read_u16().await?);
+    }
+    String::from_utf16(&units).map_err(|e| LegacyPingError::Invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> ClientboundStatusResponsePacket {
+        parse_response(&["§1", "127", "1.19.3", "A Minecraft Server", "3", "20"].join("\0"))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let status = status();
+        assert_eq!(status.version.name, "1.19.3");
+        assert_eq!(status.version.protocol, 127);
+        assert_eq!(status.description.to_string(), "A Minecraft Server");
+        assert_eq!(status.players.online, 3);
+        assert_eq!(status.players.max, 20);
+    }
+
+    #[test]
+    fn test_parse_beta_response() {
+        let status = parse_response("A Minecraft Server§3§20").unwrap();
+        assert_eq!(status.version.protocol, -1);
+        assert_eq!(status.description.to_string(), "A Minecraft Server");
+        assert_eq!(status.players.online, 3);
+        assert_eq!(status.players.max, 20);
+
+        assert!(parse_response("A Minecraft Server").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_request() {
+        for request in [
+            LegacyPingRequest::Beta,
+            LegacyPingRequest::V1_4,
+            LegacyPingRequest::V1_6 {
+                protocol_version: LEGACY_PROTOCOL_VERSION,
+                hostname: "localhost".to_string(),
+                port: 25565,
+            },
+        ] {
+            let buf = request.serialize();
+            let read = LegacyPingRequest::read_from(&mut &buf[..]).await.unwrap();
+            assert_eq!(read, request);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response() {
+        let buf = serialize_response(&LegacyPingRequest::V1_4, &status());
+        let read = read_response(&mut &buf[..]).await.unwrap();
+        assert_eq!(read.version.name, "1.19.3");
+        assert_eq!(read.players.max, 20);
+
+        let buf = serialize_response(&LegacyPingRequest::Beta, &status());
+        let read = read_response(&mut &buf[..]).await.unwrap();
+        assert_eq!(read.version.protocol, -1);
+        assert_eq!(read.players.online, 3);
+    }
+}
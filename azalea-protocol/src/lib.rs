@@ -7,7 +7,8 @@
 //! [`azalea`]: https://crates.io/crates/azalea
 //! [`azalea_client`]: https://crates.io/crates/azalea-client
 //!
-//! See [`crate::connect::Connection`] for an example.
+//! See [`crate::connect::Connection`] for an example, or [`crate::server`] if
+//! you're making a server.
 
 // these two are necessary for thiserror backtraces
 #![feature(error_generic_member_access)]
@@ -18,9 +19,21 @@ use std::{net::SocketAddr, str::FromStr};
 #[cfg(feature = "connecting")]
 pub mod connect;
 #[cfg(feature = "packets")]
+pub mod forwarding;
+#[cfg(feature = "packets")]
+pub mod legacy_ping;
+#[cfg(feature = "packets")]
 pub mod packets;
+#[cfg(feature = "connecting")]
+pub mod proxy;
 pub mod read;
+#[cfg(feature = "packets")]
+pub mod remap;
 pub mod resolver;
+#[cfg(feature = "connecting")]
+pub mod server;
+#[cfg(feature = "connecting")]
+pub mod stats;
 pub mod write;
 
 /// A host and port. It's possible that the port doesn't resolve to anything.
@@ -76,8 +89,9 @@ mod tests {
     use std::io::Cursor;
 
     use crate::{
-        packets::login::{
-            serverbound_hello_packet::ServerboundHelloPacket, ServerboundLoginPacket,
+        packets::{
+            login::{serverbound_hello_packet::ServerboundHelloPacket, ServerboundLoginPacket},
+            ProtocolVersion,
         },
         read::read_packet,
         write::write_packet,
@@ -93,9 +107,15 @@ mod tests {
         }
         .get();
         let mut stream = Vec::new();
-        write_packet(&packet, &mut stream, None, &mut None)
-            .await
-            .unwrap();
+        write_packet(
+            &packet,
+            &mut stream,
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .unwrap();
 
         let mut stream = Cursor::new(stream);
 
@@ -104,6 +124,7 @@ mod tests {
             &mut BytesMut::new(),
             None,
             &mut None,
+            ProtocolVersion::LATEST,
         )
         .await
         .unwrap();
@@ -117,21 +138,45 @@ mod tests {
         }
         .get();
         let mut stream = Vec::new();
-        write_packet(&packet, &mut stream, None, &mut None)
-            .await
-            .unwrap();
-        write_packet(&packet, &mut stream, None, &mut None)
-            .await
-            .unwrap();
+        write_packet(
+            &packet,
+            &mut stream,
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .unwrap();
+        write_packet(
+            &packet,
+            &mut stream,
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .unwrap();
         let mut stream = Cursor::new(stream);
 
         let mut buffer = BytesMut::new();
 
-        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream, &mut buffer, None, &mut None)
-            .await
-            .unwrap();
-        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream, &mut buffer, None, &mut None)
-            .await
-            .unwrap();
+        let _ = read_packet::<ServerboundLoginPacket, _>(
+            &mut stream,
+            &mut buffer,
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .unwrap();
+        let _ = read_packet::<ServerboundLoginPacket, _>(
+            &mut stream,
+            &mut buffer,
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .unwrap();
     }
 }
@@ -1,7 +1,379 @@
-use azalea_buf::McBuf;
+//! The login queries and plugin channels that Fabric API uses to sync
+//! registries.
+//!
+//! During login, the server sends an [`EARLY_REGISTRATION_CHANNEL`] query
+//! with the play channels it can receive, and the client answers with the
+//! channels it can receive. Servers with modded registry entries kick clients
+//! that didn't say they can receive the registry sync channels. Once the
+//! client is in the game, the server sends the raw ids of every registry
+//! entry on one of those channels.
+
+use azalea_buf::{
+    BufReadError, McBuf, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
+};
 use azalea_core::ResourceLocation;
+use azalea_nbt::Tag;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Cursor, Write},
+};
+
+/// The login query the server sends with the play channels it can receive.
+pub const EARLY_REGISTRATION_CHANNEL: &str = "fabric-networking-api-v1:early_registration";
+/// The channel that registries are synced on in Fabric's compact format.
+pub const REGISTRY_SYNC_DIRECT_CHANNEL: &str = "fabric:registry/sync/direct";
+/// The channel that registries are synced on as NBT, which older versions of
+/// Fabric API use.
+pub const REGISTRY_SYNC_NBT_CHANNEL: &str = "fabric:registry/sync";
 
+/// The play channels that the server can receive, sent in the
+/// [`EARLY_REGISTRATION_CHANNEL`] login query.
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundFabricRegistryPacket {
     pub registry: Vec<ResourceLocation>,
 }
+
+/// The answer to [`ClientboundFabricRegistryPacket`], with the play channels
+/// that we can receive.
+#[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundFabricRegistryPacket {
+    pub registry: Vec<ResourceLocation>,
+}
+
+impl ServerboundFabricRegistryPacket {
+    /// The channels that a client needs to receive registry syncs.
+    pub fn registry_sync() -> Self {
+        Self {
+            registry: vec![
+                ResourceLocation::new(REGISTRY_SYNC_DIRECT_CHANNEL).unwrap(),
+                ResourceLocation::new(REGISTRY_SYNC_NBT_CHANNEL).unwrap(),
+            ],
+        }
+    }
+}
+
+/// The raw ids of registry entries that a Fabric server synced, which can be
+/// different from the vanilla ones when mods add entries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FabricRegistries {
+    /// The ids of the entries in each registry.
+    registries: HashMap<ResourceLocation, HashMap<ResourceLocation, u32>>,
+    /// The entries in each registry by their ids.
+    entries: HashMap<ResourceLocation, HashMap<u32, ResourceLocation>>,
+}
+
+impl From<HashMap<ResourceLocation, HashMap<ResourceLocation, u32>>> for FabricRegistries {
+    fn from(registries: HashMap<ResourceLocation, HashMap<ResourceLocation, u32>>) -> Self {
+        let entries = registries
+            .iter()
+            .map(|(registry, ids)| {
+                let entries = ids.iter().map(|(entry, id)| (*id, entry.clone()));
+                (registry.clone(), entries.collect())
+            })
+            .collect();
+        Self {
+            registries,
+            entries,
+        }
+    }
+}
+
+/// Fabric leaves out the `minecraft` namespace to save space.
+fn location(namespace: &str, path: String) -> ResourceLocation {
+    ResourceLocation {
+        namespace: if namespace.is_empty() {
+            "minecraft".to_string()
+        } else {
+            namespace.to_string()
+        },
+        path,
+    }
+}
+
+fn optimize_namespace(namespace: &str) -> &str {
+    if namespace == "minecraft" {
+        ""
+    } else {
+        namespace
+    }
+}
+
+impl FabricRegistries {
+    /// The ids of the entries in each registry.
+    pub fn registries(&self) -> &HashMap<ResourceLocation, HashMap<ResourceLocation, u32>> {
+        &self.registries
+    }
+
+    /// The raw id of an entry in a registry.
+    pub fn id(&self, registry: &ResourceLocation, entry: &ResourceLocation) -> Option<u32> {
+        self.registries.get(registry)?.get(entry).copied()
+    }
+
+    /// The entry in a registry that has the given raw id.
+    pub fn entry(&self, registry: &ResourceLocation, id: u32) -> Option<&ResourceLocation> {
+        self.entries.get(registry)?.get(&id)
+    }
+
+    /// Read registries in the format of [`REGISTRY_SYNC_DIRECT_CHANNEL`],
+    /// after all the parts of the payload were put together.
+    ///
+    /// Registries and their entries are grouped by namespace, and the entries
+    /// are in runs of consecutive raw ids.
+    pub fn read_direct(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        let mut registries = HashMap::new();
+        for _ in 0..u32::var_read_from(buf)? {
+            let registry_namespace = String::read_from(buf)?;
+            for _ in 0..u32::var_read_from(buf)? {
+                let registry = location(&registry_namespace, String::read_from(buf)?);
+                let mut entries = HashMap::new();
+                // the raw ids of runs are relative to the end of the previous
+                // run in this registry
+                let mut last_id = 0i32;
+                for _ in 0..u32::var_read_from(buf)? {
+                    let namespace = String::read_from(buf)?;
+                    for _ in 0..u32::var_read_from(buf)? {
+                        let start_diff = i32::var_read_from(buf)?;
+                        let size = u32::var_read_from(buf)?;
+                        let mut id = last_id.checked_add(start_diff);
+                        for _ in 0..size {
+                            let valid_id = id.filter(|id| *id >= 0).ok_or_else(|| {
+                                BufReadError::Custom(format!(
+                                    "Invalid raw id in registry {registry}"
+                                ))
+                            })?;
+                            entries.insert(
+                                location(&namespace, String::read_from(buf)?),
+                                valid_id as u32,
+                            );
+                            last_id = valid_id;
+                            id = valid_id.checked_add(1);
+                        }
+                    }
+                }
+                registries.insert(registry, entries);
+            }
+        }
+        Ok(registries.into())
+    }
+
+    /// Write the registries in the format of
+    /// [`REGISTRY_SYNC_DIRECT_CHANNEL`].
+    pub fn write_direct(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        // BTreeMaps so the output doesn't depend on HashMap order
+        let mut by_namespace: BTreeMap<&str, Vec<&ResourceLocation>> = BTreeMap::new();
+        for registry in self.registries.keys() {
+            by_namespace
+                .entry(&registry.namespace)
+                .or_default()
+                .push(registry);
+        }
+
+        (by_namespace.len() as u32).var_write_into(buf)?;
+        for (registry_namespace, registries) in by_namespace {
+            optimize_namespace(registry_namespace)
+                .to_string()
+                .write_into(buf)?;
+            (registries.len() as u32).var_write_into(buf)?;
+            for registry in registries {
+                registry.path.write_into(buf)?;
+
+                let mut entries_by_namespace: BTreeMap<&str, Vec<(&ResourceLocation, u32)>> =
+                    BTreeMap::new();
+                for (entry, id) in &self.registries[registry] {
+                    entries_by_namespace
+                        .entry(&entry.namespace)
+                        .or_default()
+                        .push((entry, *id));
+                }
+
+                (entries_by_namespace.len() as u32).var_write_into(buf)?;
+                let mut last_id = 0;
+                for (namespace, mut entries) in entries_by_namespace {
+                    entries.sort_by_key(|(_, id)| *id);
+                    let mut runs: Vec<Vec<(&ResourceLocation, u32)>> = Vec::new();
+                    for entry in entries {
+                        match runs.last_mut() {
+                            Some(run) if run.last().unwrap().1 + 1 == entry.1 => run.push(entry),
+                            _ => runs.push(vec![entry]),
+                        }
+                    }
+
+                    optimize_namespace(namespace).to_string().write_into(buf)?;
+                    (runs.len() as u32).var_write_into(buf)?;
+                    for run in runs {
+                        (run[0].1 as i32 - last_id as i32).var_write_into(buf)?;
+                        (run.len() as u32).var_write_into(buf)?;
+                        for (entry, id) in run {
+                            entry.path.write_into(buf)?;
+                            last_id = id;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read registries in the format of [`REGISTRY_SYNC_NBT_CHANNEL`]. The
+    /// NBT can be gzip or zlib compressed.
+    pub fn read_nbt(data: &[u8]) -> Result<Self, BufReadError> {
+        let tag = match data {
+            [0x1f, 0x8b, ..] => Tag::read_gzip(&mut Cursor::new(data.to_vec()))?,
+            [0x78, ..] => Tag::read_zlib(&mut &data[..])?,
+            _ => Tag::read(&mut Cursor::new(data))?,
+        };
+        let invalid = || BufReadError::Custom("Invalid Fabric registry sync NBT".to_string());
+
+        // the root compound has one unnamed tag with everything in it
+        let root = tag
+            .as_compound()
+            .and_then(|root| root.values().next())
+            .and_then(|root| root.as_compound())
+            .ok_or_else(invalid)?;
+        let synced = root
+            .get("registries")
+            .and_then(|registries| registries.as_compound())
+            .ok_or_else(invalid)?;
+
+        let mut registries = HashMap::new();
+        for (registry, entries) in synced {
+            let entries = entries
+                .as_compound()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|(entry, id)| {
+                    let id = id.as_int().ok_or_else(invalid)?;
+                    Ok((ResourceLocation::new(entry)?, *id as u32))
+                })
+                .collect::<Result<_, BufReadError>>()?;
+            registries.insert(ResourceLocation::new(registry)?, entries);
+        }
+        Ok(registries.into())
+    }
+}
+
+/// Puts together the parts of a [`REGISTRY_SYNC_DIRECT_CHANNEL`] payload,
+/// which the server splits up and ends with an empty packet.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FabricRegistrySyncBuffer {
+    data: Vec<u8>,
+}
+
+impl FabricRegistrySyncBuffer {
+    /// Add a part of the payload. When it's the last part, the whole payload
+    /// is read and the buffer is emptied.
+    pub fn push(&mut self, part: &[u8]) -> Option<Result<FabricRegistries, BufReadError>> {
+        if !part.is_empty() {
+            self.data.extend_from_slice(part);
+            return None;
+        }
+        let data = std::mem::take(&mut self.data);
+        Some(FabricRegistries::read_direct(&mut Cursor::new(&data[..])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registries() -> FabricRegistries {
+        let mut items = HashMap::new();
+        items.insert(ResourceLocation::new("minecraft:air").unwrap(), 0);
+        items.insert(ResourceLocation::new("minecraft:stone").unwrap(), 1);
+        items.insert(ResourceLocation::new("minecraft:dirt").unwrap(), 5);
+        items.insert(ResourceLocation::new("examplemod:ruby").unwrap(), 2);
+        items.insert(ResourceLocation::new("examplemod:sapphire").unwrap(), 3);
+        let mut blocks = HashMap::new();
+        blocks.insert(ResourceLocation::new("examplemod:ruby_block").unwrap(), 900);
+
+        let mut registries = HashMap::new();
+        registries.insert(ResourceLocation::new("minecraft:item").unwrap(), items);
+        registries.insert(ResourceLocation::new("minecraft:block").unwrap(), blocks);
+        registries.into()
+    }
+
+    #[test]
+    fn test_direct_split() {
+        let mut data = Vec::new();
+        registries().write_direct(&mut data).unwrap();
+
+        let mut buffer = FabricRegistrySyncBuffer::default();
+        let (first, second) = data.split_at(data.len() / 2);
+        assert!(buffer.push(first).is_none());
+        assert!(buffer.push(second).is_none());
+        let read = buffer.push(&[]).unwrap().unwrap();
+        assert_eq!(read, registries());
+
+        let item = ResourceLocation::new("minecraft:item").unwrap();
+        assert_eq!(
+            read.id(&item, &ResourceLocation::new("examplemod:ruby").unwrap()),
+            Some(2)
+        );
+        assert_eq!(read.entry(&item, 5).unwrap().to_string(), "minecraft:dirt");
+    }
+
+    #[test]
+    fn test_direct_invalid_id() {
+        for start_diff in [-1, i32::MAX] {
+            let mut data = Vec::new();
+            // one registry namespace with one registry
+            1u32.var_write_into(&mut data).unwrap();
+            "minecraft".write_into(&mut data).unwrap();
+            1u32.var_write_into(&mut data).unwrap();
+            "item".write_into(&mut data).unwrap();
+            // one entry namespace with one run of two entries
+            1u32.var_write_into(&mut data).unwrap();
+            "minecraft".write_into(&mut data).unwrap();
+            1u32.var_write_into(&mut data).unwrap();
+            start_diff.var_write_into(&mut data).unwrap();
+            2u32.var_write_into(&mut data).unwrap();
+            "stone".write_into(&mut data).unwrap();
+            "dirt".write_into(&mut data).unwrap();
+
+            assert!(matches!(
+                FabricRegistries::read_direct(&mut Cursor::new(&data[..])),
+                Err(BufReadError::Custom(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_nbt() {
+        let entries = Tag::Compound(
+            [("minecraft:stone".to_string(), Tag::Int(1))]
+                .into_iter()
+                .collect(),
+        );
+        let root = Tag::Compound(
+            [
+                (
+                    "registries".to_string(),
+                    Tag::Compound(
+                        [("minecraft:item".to_string(), entries)]
+                            .into_iter()
+                            .collect(),
+                    ),
+                ),
+                ("version".to_string(), Tag::Int(1)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let tag = Tag::Compound([("".to_string(), root)].into_iter().collect());
+
+        let mut data = Vec::new();
+        tag.write_zlib(&mut data).unwrap();
+        let read = FabricRegistries::read_nbt(&data).unwrap();
+        assert_eq!(
+            read.id(
+                &ResourceLocation::new("minecraft:item").unwrap(),
+                &ResourceLocation::new("minecraft:stone").unwrap()
+            ),
+            Some(1)
+        );
+    }
+}
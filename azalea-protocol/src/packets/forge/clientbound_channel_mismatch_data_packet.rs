@@ -2,6 +2,7 @@ use azalea_buf::{McBuf, UnsizedByteArray};
 use azalea_protocol_macros::ClientboundForgePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundForgePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundChannelMismatchDataPacket {
     pub data: UnsizedByteArray,
 }
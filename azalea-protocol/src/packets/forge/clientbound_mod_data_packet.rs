@@ -4,6 +4,7 @@ use azalea_buf::{McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritabl
 use azalea_protocol_macros::ClientboundForgePacket;
 
 #[derive(Clone, Debug, ClientboundForgePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundModDataPacket {
     pub list: HashMap<String, (String, String)>,
 }
@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundForgePacket;
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, McBuf, ClientboundForgePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundModListPacket {
     pub mods: Vec<String>,
     pub channels: HashMap<ResourceLocation, String>,
@@ -4,12 +4,14 @@ use azalea_protocol_macros::ClientboundForgePacket;
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, McBuf, ClientboundForgePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundRegistryPacket {
     pub registry: ResourceLocation,
     pub data: Option<ForgeRegistryData>,
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForgeRegistryData {
     #[var]
     pub ids: HashMap<ResourceLocation, u32>,
@@ -0,0 +1,9 @@
+use azalea_buf::McBuf;
+use azalea_protocol_macros::ClientboundFml2Packet;
+
+#[derive(Clone, Debug, McBuf, ClientboundFml2Packet)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientboundConfigDataPacket {
+    pub filename: String,
+    pub filedata: Vec<u8>,
+}
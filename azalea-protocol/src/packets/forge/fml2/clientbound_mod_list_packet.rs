@@ -0,0 +1,14 @@
+use azalea_buf::McBuf;
+use azalea_core::ResourceLocation;
+use azalea_protocol_macros::ClientboundFml2Packet;
+use std::collections::HashMap;
+
+/// The mods, channels, and registries the server has. Unlike FML3, there's no
+/// list of data pack registries at the end.
+#[derive(Clone, Debug, McBuf, ClientboundFml2Packet)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientboundModListPacket {
+    pub mods: Vec<String>,
+    pub channels: HashMap<ResourceLocation, String>,
+    pub registries: Vec<ResourceLocation>,
+}
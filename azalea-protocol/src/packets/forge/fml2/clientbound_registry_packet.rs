@@ -0,0 +1,11 @@
+use crate::packets::forge::clientbound_registry_packet::ForgeRegistryData;
+use azalea_buf::McBuf;
+use azalea_core::ResourceLocation;
+use azalea_protocol_macros::ClientboundFml2Packet;
+
+#[derive(Clone, Debug, McBuf, ClientboundFml2Packet)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientboundRegistryPacket {
+    pub registry: ResourceLocation,
+    pub data: Option<ForgeRegistryData>,
+}
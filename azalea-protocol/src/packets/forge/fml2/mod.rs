@@ -0,0 +1,130 @@
+//! The `fml:loginwrapper` packets used by Forge for Minecraft 1.13 to 1.17,
+//! which is version 2 of its network protocol (FML2). Newer versions of Forge
+//! use the packets in [`crate::packets::forge`] instead.
+
+pub mod clientbound_config_data_packet;
+pub mod clientbound_mod_list_packet;
+pub mod clientbound_registry_packet;
+pub mod serverbound_acknowledge_packet;
+pub mod serverbound_mod_list_reply_packet;
+
+use crate::packets::ProtocolVersion;
+use crate::read::{read_packet, ReadPacketError};
+use crate::write::write_packet;
+use azalea_protocol_macros::declare_state_packets;
+
+use bytes::BytesMut;
+use std::io::{Read, Write};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+declare_state_packets!(
+    Fml2Packet,
+    Serverbound => {
+        0x02: serverbound_mod_list_reply_packet::ServerboundModListReplyPacket,
+        0x63: serverbound_acknowledge_packet::ServerboundAcknowledgePacket,
+    },
+    Clientbound => {
+        0x01: clientbound_mod_list_packet::ClientboundModListPacket,
+        0x03: clientbound_registry_packet::ClientboundRegistryPacket,
+        0x04: clientbound_config_data_packet::ClientboundConfigDataPacket,
+    }
+);
+
+impl ServerboundFml2Packet {
+    pub async fn write_to_buf(
+        &self,
+        buf: &mut (impl Write + AsyncWrite + Send + Unpin),
+    ) -> Result<(), std::io::Error> {
+        write_packet(self, buf, None, &mut None, ProtocolVersion::LATEST).await?;
+        Ok(())
+    }
+
+    pub async fn write_to_vec(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut buf: Vec<u8> = Vec::new();
+        self.write_to_buf(&mut buf).await?;
+        Ok(buf)
+    }
+
+    pub async fn read_from_buf(
+        buf: &mut (impl Read + AsyncRead + Send + Sync + Unpin),
+    ) -> Result<ServerboundFml2Packet, Box<ReadPacketError>> {
+        read_packet::<ServerboundFml2Packet, _>(
+            buf,
+            &mut BytesMut::new(),
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+    }
+}
+
+impl ClientboundFml2Packet {
+    pub async fn write_to_buf(
+        &self,
+        buf: &mut (impl Write + AsyncWrite + Send + Unpin),
+    ) -> Result<(), std::io::Error> {
+        write_packet(self, buf, None, &mut None, ProtocolVersion::LATEST).await?;
+        Ok(())
+    }
+
+    pub async fn write_to_vec(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut buf: Vec<u8> = Vec::new();
+        self.write_to_buf(&mut buf).await?;
+        Ok(buf)
+    }
+
+    pub async fn read_from_buf(
+        buf: &mut (impl Read + AsyncRead + Send + Sync + Unpin),
+    ) -> Result<ClientboundFml2Packet, Box<ReadPacketError>> {
+        read_packet::<ClientboundFml2Packet, _>(
+            buf,
+            &mut BytesMut::new(),
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clientbound_mod_list_packet::ClientboundModListPacket,
+        serverbound_mod_list_reply_packet::ServerboundModListReplyPacket, *,
+    };
+    use azalea_core::ResourceLocation;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_mod_list_round_trip() {
+        let packet = ClientboundModListPacket {
+            mods: vec!["forge".to_string(), "minecraft".to_string()],
+            channels: HashMap::from([(
+                ResourceLocation::new("forge:tier_sorting").unwrap(),
+                "1.0".to_string(),
+            )]),
+            registries: vec![ResourceLocation::new("minecraft:item").unwrap()],
+        }
+        .get();
+        let data = packet.write_to_vec().await.unwrap();
+        let ClientboundFml2Packet::ModList(read) =
+            ClientboundFml2Packet::read_from_buf(&mut &data[..])
+                .await
+                .unwrap()
+        else {
+            panic!("expected a mod list");
+        };
+        assert_eq!(read.mods, ["forge", "minecraft"]);
+
+        let reply = ServerboundModListReplyPacket::from(read);
+        assert_eq!(
+            reply.registries,
+            HashMap::from([(
+                ResourceLocation::new("minecraft:item").unwrap(),
+                "".to_string()
+            )])
+        );
+    }
+}
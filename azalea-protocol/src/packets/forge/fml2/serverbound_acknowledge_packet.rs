@@ -0,0 +1,6 @@
+use azalea_buf::McBuf;
+use azalea_protocol_macros::ServerboundFml2Packet;
+
+#[derive(Clone, Debug, Default, McBuf, ServerboundFml2Packet)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundAcknowledgePacket {}
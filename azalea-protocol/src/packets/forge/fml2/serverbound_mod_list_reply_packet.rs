@@ -0,0 +1,30 @@
+use azalea_buf::McBuf;
+use azalea_core::ResourceLocation;
+use azalea_protocol_macros::ServerboundFml2Packet;
+use std::collections::HashMap;
+
+use super::clientbound_mod_list_packet::ClientboundModListPacket;
+
+#[derive(Clone, Debug, McBuf, ServerboundFml2Packet)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundModListReplyPacket {
+    pub mods: Vec<String>,
+    pub channels: HashMap<ResourceLocation, String>,
+    /// The registries we have and the marker of their snapshot, which is empty
+    /// since we don't have any.
+    pub registries: HashMap<ResourceLocation, String>,
+}
+
+impl From<ClientboundModListPacket> for ServerboundModListReplyPacket {
+    fn from(value: ClientboundModListPacket) -> Self {
+        Self {
+            mods: value.mods,
+            channels: value.channels,
+            registries: value
+                .registries
+                .into_iter()
+                .map(|registry| (registry, "".to_string()))
+                .collect(),
+        }
+    }
+}
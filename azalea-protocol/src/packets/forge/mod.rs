@@ -3,9 +3,11 @@ pub mod clientbound_config_data_packet;
 pub mod clientbound_mod_data_packet;
 pub mod clientbound_mod_list_packet;
 pub mod clientbound_registry_packet;
+pub mod fml2;
 pub mod serverbound_acknowledge_packet;
 pub mod serverbound_mod_list_reply_packet;
 
+use super::ProtocolVersion;
 use crate::read::{read_packet, ReadPacketError};
 use crate::write::write_packet;
 use azalea_protocol_macros::declare_state_packets;
@@ -34,7 +36,7 @@ impl ServerboundForgePacket {
         &self,
         buf: &mut (impl Write + AsyncWrite + Send + Unpin),
     ) -> Result<(), std::io::Error> {
-        write_packet(self, buf, None, &mut None).await?;
+        write_packet(self, buf, None, &mut None, ProtocolVersion::LATEST).await?;
         Ok(())
     }
 
@@ -47,7 +49,14 @@ impl ServerboundForgePacket {
     pub async fn read_from_buf(
         buf: &mut (impl Read + AsyncRead + Send + Sync + Unpin),
     ) -> Result<ServerboundForgePacket, Box<ReadPacketError>> {
-        read_packet::<ServerboundForgePacket, _>(buf, &mut BytesMut::new(), None, &mut None).await
+        read_packet::<ServerboundForgePacket, _>(
+            buf,
+            &mut BytesMut::new(),
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
     }
 }
 
@@ -56,7 +65,7 @@ impl ClientboundForgePacket {
         &self,
         buf: &mut (impl Write + AsyncWrite + Send + Unpin),
     ) -> Result<(), std::io::Error> {
-        write_packet(self, buf, None, &mut None).await?;
+        write_packet(self, buf, None, &mut None, ProtocolVersion::LATEST).await?;
         Ok(())
     }
 
@@ -69,6 +78,13 @@ impl ClientboundForgePacket {
     pub async fn read_from_buf(
         buf: &mut (impl Read + AsyncRead + Send + Sync + Unpin),
     ) -> Result<ClientboundForgePacket, Box<ReadPacketError>> {
-        read_packet::<ClientboundForgePacket, _>(buf, &mut BytesMut::new(), None, &mut None).await
+        read_packet::<ClientboundForgePacket, _>(
+            buf,
+            &mut BytesMut::new(),
+            None,
+            &mut None,
+            ProtocolVersion::LATEST,
+        )
+        .await
     }
 }
@@ -2,6 +2,7 @@ use azalea_buf::McBuf;
 use azalea_protocol_macros::ServerboundForgePacket;
 
 #[derive(Clone, Debug, Default, McBuf, ServerboundForgePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundAcknowledgePacket {}
 
 impl ServerboundAcknowledgePacket {
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use super::clientbound_mod_list_packet::ClientboundModListPacket;
 
 #[derive(Clone, Debug, ServerboundForgePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundModListReplyPacket {
     pub mods: Vec<String>,
     pub channels: HashMap<ResourceLocation, String>,
@@ -1,16 +1,17 @@
-use azalea_buf::McBuf;
+use azalea_buf::{remap::MaybeModded, McBuf};
 use azalea_core::Vec3;
 use azalea_protocol_macros::ClientboundGamePacket;
 use azalea_world::entity::{EntityData, EntityMetadata};
 use uuid::Uuid;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundAddEntityPacket {
     /// The id of the entity.
     #[var]
     pub id: u32,
     pub uuid: Uuid,
-    pub entity_type: azalea_registry::EntityType,
+    pub entity_type: MaybeModded<azalea_registry::EntityType>,
     pub x: f64,
     pub y: f64,
     pub z: f64,
@@ -24,9 +25,14 @@ pub struct ClientboundAddEntityPacket {
     pub z_vel: i16,
 }
 
-impl From<&ClientboundAddEntityPacket> for EntityData {
-    fn from(p: &ClientboundAddEntityPacket) -> Self {
-        Self::new(
+impl TryFrom<&ClientboundAddEntityPacket> for EntityData {
+    type Error = ();
+
+    /// Fails if the entity is modded, since we don't know what metadata it
+    /// has.
+    fn try_from(p: &ClientboundAddEntityPacket) -> Result<Self, Self::Error> {
+        let entity_type = *p.entity_type.vanilla().ok_or(())?;
+        Ok(Self::new(
             p.uuid,
             Vec3 {
                 x: p.x,
@@ -34,7 +40,7 @@ impl From<&ClientboundAddEntityPacket> for EntityData {
                 z: p.z,
             },
             // default metadata for the entity type
-            EntityMetadata::from(p.entity_type),
-        )
+            EntityMetadata::from(entity_type),
+        ))
     }
 }
@@ -7,6 +7,7 @@ use uuid::Uuid;
 /// This packet is sent by the server when a player comes into visible range,
 /// not when a player joins.
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundAddPlayerPacket {
     #[var]
     pub id: u32,
@@ -1,10 +1,11 @@
 use azalea_block::BlockState;
-use azalea_buf::McBuf;
+use azalea_buf::{remap::MaybeModded, McBuf};
 use azalea_core::BlockPos;
 use azalea_protocol_macros::ClientboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundBlockUpdatePacket {
     pub pos: BlockPos,
-    pub block_state: BlockState,
+    pub block_state: MaybeModded<BlockState>,
 }
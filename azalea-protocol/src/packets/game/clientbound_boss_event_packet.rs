@@ -8,12 +8,14 @@ use std::io::Write;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundBossEventPacket {
     pub id: Uuid,
     pub operation: Operation,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operation {
     Add(AddOperation),
     Remove,
@@ -74,6 +76,7 @@ impl McBufWritable for Operation {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddOperation {
     name: Component,
     progress: f32,
@@ -82,12 +85,14 @@ pub struct AddOperation {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Style {
     color: BossBarColor,
     overlay: BossBarOverlay,
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BossBarColor {
     Pink = 0,
     Blue = 1,
@@ -99,6 +104,7 @@ pub enum BossBarColor {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BossBarOverlay {
     Progress = 0,
     Notched6 = 1,
@@ -108,6 +114,7 @@ pub enum BossBarOverlay {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Properties {
     pub darken_screen: bool,
     pub play_music: bool,
@@ -10,14 +10,16 @@ use std::io::Cursor;
 use std::io::Write;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundCommandsPacket {
     pub entries: Vec<BrigadierNodeStub>,
     #[var]
     pub root_index: u32,
-    extra: UnsizedByteArray,
+    pub(super) extra: UnsizedByteArray,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BrigadierNodeStub {
     pub is_executable: bool,
     pub children: Vec<u32>,
@@ -26,6 +28,7 @@ pub struct BrigadierNodeStub {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BrigadierNumber<T> {
     pub min: Option<T>,
     pub max: Option<T>,
@@ -72,6 +75,7 @@ impl<T: McBufWritable> McBufWritable for BrigadierNumber<T> {
 }
 
 #[derive(Debug, Clone, Copy, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BrigadierString {
     /// Reads a single word
     SingleWord = 0,
@@ -83,6 +87,7 @@ pub enum BrigadierString {
 }
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BrigadierParser {
     Bool,
     Float(BrigadierNumber<f32>),
@@ -135,6 +140,7 @@ pub enum BrigadierParser {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityParser {
     pub single: bool,
     pub players_only: bool,
@@ -165,6 +171,23 @@ impl McBufWritable for EntityParser {
 // TODO: BrigadierNodeStub should have more stuff
 impl McBufReadable for BrigadierNodeStub {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        Self::read_with_parser(buf, BrigadierParser::read_from)
+    }
+}
+
+impl McBufWritable for BrigadierNodeStub {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        self.write_with_parser(buf, |parser, mut buf| parser.write_into(&mut buf))
+    }
+}
+
+impl BrigadierNodeStub {
+    /// Read a node, with the argument parser read by `read_parser`. Versions
+    /// number the parsers differently, so this lets them be converted.
+    pub(super) fn read_with_parser(
+        buf: &mut Cursor<&[u8]>,
+        read_parser: impl Fn(&mut Cursor<&[u8]>) -> Result<BrigadierParser, BufReadError>,
+    ) -> Result<Self, BufReadError> {
         let flags = u8::read_from(buf)?;
         if flags > 31 {
             warn!(
@@ -187,7 +210,7 @@ impl McBufReadable for BrigadierNodeStub {
         // argument node
         if node_type == 2 {
             let name = String::read_from(buf)?;
-            let parser = BrigadierParser::read_from(buf)?;
+            let parser = read_parser(buf)?;
             let suggestions_type = if has_suggestions_type {
                 Some(ResourceLocation::read_from(buf)?)
             } else {
@@ -222,10 +245,13 @@ impl McBufReadable for BrigadierNodeStub {
             node_type: NodeType::Root,
         })
     }
-}
 
-impl McBufWritable for BrigadierNodeStub {
-    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+    /// Write a node, with the argument parser written by `write_parser`.
+    pub(super) fn write_with_parser(
+        &self,
+        buf: &mut impl Write,
+        write_parser: impl Fn(&BrigadierParser, &mut dyn Write) -> Result<(), std::io::Error>,
+    ) -> Result<(), std::io::Error> {
         match &self.node_type {
             NodeType::Root => {
                 let mut flags = 0x00;
@@ -285,7 +311,7 @@ impl McBufWritable for BrigadierNodeStub {
                 }
 
                 name.write_into(buf)?;
-                parser.write_into(buf)?;
+                write_parser(parser, buf)?;
 
                 if let Some(suggestion) = suggestions_type {
                     suggestion.write_into(buf)?;
@@ -297,6 +323,7 @@ impl McBufWritable for BrigadierNodeStub {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NodeType {
     Root,
     Literal {
@@ -4,6 +4,7 @@ use azalea_chat::Component;
 use azalea_protocol_macros::ClientboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundDisguisedChatPacket {
     pub message: Component,
     pub chat_type: ChatTypeBound,
@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use super::clientbound_light_update_packet::ClientboundLightUpdatePacketData;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundLevelChunkWithLightPacket {
     pub x: i32,
     pub z: i32,
@@ -12,6 +13,7 @@ pub struct ClientboundLevelChunkWithLightPacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundLevelChunkPacketData {
     pub heightmaps: azalea_nbt::Tag,
     // we can't parse the data in azalea-protocol because it dependso on context from other packets
@@ -20,6 +22,7 @@ pub struct ClientboundLevelChunkPacketData {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockEntity {
     pub packed_xz: u8,
     pub y: u16,
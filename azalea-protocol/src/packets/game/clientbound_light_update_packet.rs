@@ -3,6 +3,7 @@ use azalea_core::BitSet;
 use azalea_protocol_macros::ClientboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundLightUpdatePacket {
     #[var]
     pub x: i32,
@@ -12,6 +13,7 @@ pub struct ClientboundLightUpdatePacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundLightUpdatePacketData {
     pub trust_edges: bool,
     pub sky_y_mask: BitSet,
@@ -5,6 +5,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use std::io::{Cursor, Write};
 
 #[derive(Clone, Debug, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundMapItemDataPacket {
     // #[var]
     pub map_id: u32,
@@ -69,6 +70,7 @@ impl McBufWritable for ClientboundMapItemDataPacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MapDecoration {
     pub decoration_type: DecorationType,
     pub x: i8,
@@ -80,6 +82,7 @@ pub struct MapDecoration {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MapPatch {
     pub start_x: u8,
     pub start_y: u8,
@@ -89,6 +92,7 @@ pub struct MapPatch {
 }
 
 #[derive(Clone, Copy, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DecorationType {
     Player,
     Frame,
@@ -3,6 +3,7 @@ use azalea_core::PositionDelta8;
 use azalea_protocol_macros::ClientboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundMoveEntityPosPacket {
     #[var]
     pub entity_id: u32,
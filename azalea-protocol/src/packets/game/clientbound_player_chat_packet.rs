@@ -12,6 +12,7 @@ use std::io::{Cursor, Write};
 use uuid::Uuid;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPlayerChatPacket {
     pub sender: Uuid,
     #[var]
@@ -24,6 +25,7 @@ pub struct ClientboundPlayerChatPacket {
 }
 
 #[derive(Clone, Debug, PartialEq, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackedSignedMessageBody {
     // the error is here, for some reason it skipped a byte earlier and here
     // it's reading `0` when it should be `11`
@@ -34,18 +36,21 @@ pub struct PackedSignedMessageBody {
 }
 
 #[derive(Clone, Debug, PartialEq, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackedLastSeenMessages {
     pub entries: Vec<PackedMessageSignature>,
 }
 
 /// Messages can be deleted by either their signature or message id.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PackedMessageSignature {
     Signature(Box<MessageSignature>),
     Id(u32),
 }
 
 #[derive(Clone, Debug, PartialEq, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FilterMask {
     PassThrough,
     FullyFiltered,
@@ -53,6 +58,7 @@ pub enum FilterMask {
 }
 
 #[derive(Copy, Clone, Debug, McBuf, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChatType {
     Chat = 0,
     SayCommand = 1,
@@ -64,6 +70,7 @@ pub enum ChatType {
 }
 
 #[derive(Clone, Debug, McBuf, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChatTypeBound {
     pub chat_type: ChatType,
     pub name: Component,
@@ -72,6 +79,7 @@ pub struct ChatTypeBound {
 
 // must be in Client
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MessageSignatureCache {
     pub entries: Vec<Option<MessageSignature>>,
 }
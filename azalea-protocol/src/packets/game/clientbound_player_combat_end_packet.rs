@@ -3,6 +3,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 
 /// Unused in vanilla.
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPlayerCombatEndPacket {
     #[var]
     pub duration: u32,
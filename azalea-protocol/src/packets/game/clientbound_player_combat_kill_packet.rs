@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 
 /// Used to send a respawn screen.
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPlayerCombatKillPacket {
     #[var]
     pub player_id: u32,
@@ -3,6 +3,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPlayerInfoRemovePacket {
     pub profile_ids: Vec<Uuid>,
 }
@@ -14,12 +14,14 @@ use uuid::Uuid;
 use super::serverbound_chat_session_update_packet::RemoteChatSessionData;
 
 #[derive(Clone, Debug, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPlayerInfoUpdatePacket {
     pub actions: ActionEnumSet,
     pub entries: Vec<PlayerInfoEntry>,
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerInfoEntry {
     pub profile: GameProfile,
     pub listed: bool,
@@ -30,28 +32,34 @@ pub struct PlayerInfoEntry {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddPlayerAction {
     pub name: String,
     pub properties: HashMap<String, ProfilePropertyValue>,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InitializeChatAction {
     pub chat_session: Option<RemoteChatSessionData>,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateGameModeAction {
     pub game_mode: GameType,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateListedAction {
     pub listed: bool,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateLatencyAction {
     #[var]
     pub latency: i32,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateDisplayNameAction {
     pub display_name: Option<Component>,
 }
@@ -152,6 +160,7 @@ impl McBufWritable for ClientboundPlayerInfoUpdatePacket {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ActionEnumSet {
     pub add_player: bool,
     pub initialize_chat: bool,
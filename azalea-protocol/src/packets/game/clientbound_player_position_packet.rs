@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use std::io::{Cursor, Write};
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPlayerPositionPacket {
     pub x: f64,
     pub y: f64,
@@ -19,6 +20,7 @@ pub struct ClientboundPlayerPositionPacket {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RelativeArguments {
     pub x: bool,
     pub y: bool,
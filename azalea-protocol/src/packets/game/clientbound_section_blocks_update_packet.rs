@@ -1,5 +1,6 @@
 use azalea_block::BlockState;
 use azalea_buf::{
+    remap::{remap, MaybeModded, ModdedEntry, Remappable, RemappedId},
     BufReadError, McBuf, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
 };
 use azalea_core::{ChunkSectionBlockPos, ChunkSectionPos};
@@ -7,6 +8,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use std::io::{Cursor, Write};
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundSectionBlocksUpdatePacket {
     pub section_pos: ChunkSectionPos,
     pub suppress_light_updates: bool,
@@ -14,9 +16,10 @@ pub struct ClientboundSectionBlocksUpdatePacket {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockStateWithPosition {
     pub pos: ChunkSectionBlockPos,
-    pub state: BlockState,
+    pub state: MaybeModded<BlockState>,
 }
 
 impl McBufReadable for BlockStateWithPosition {
@@ -24,8 +27,16 @@ impl McBufReadable for BlockStateWithPosition {
         let data = u64::var_read_from(buf)?;
         let position_part = data & 4095;
         let state = (data >> 12) as u32;
-        let state = BlockState::try_from(state)
-            .map_err(|_| BufReadError::UnexpectedEnumVariant { id: state as i32 })?;
+        let state = match remap(BlockState::REGISTRY, state) {
+            RemappedId::Vanilla(id) => match BlockState::try_from(id) {
+                Ok(state) => MaybeModded::Vanilla(state),
+                Err(_) => MaybeModded::Modded(ModdedEntry {
+                    id: state,
+                    name: None,
+                }),
+            },
+            RemappedId::Modded(entry) => MaybeModded::Modded(entry),
+        };
         let pos = ChunkSectionBlockPos {
             x: (position_part >> 8 & 15) as u8,
             y: (position_part & 15) as u8,
@@ -37,7 +48,11 @@ impl McBufReadable for BlockStateWithPosition {
 
 impl McBufWritable for BlockStateWithPosition {
     fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
-        let data = (self.state as u64) << 12
+        let state = match &self.state {
+            MaybeModded::Vanilla(state) => *state as u32,
+            MaybeModded::Modded(entry) => entry.id,
+        };
+        let data = (state as u64) << 12
             | ((self.pos.x as u64) << 8 | (self.pos.z as u64) << 4 | (self.pos.y as u64));
         u64::var_write_into(&data, buf)?;
         Ok(())
@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use azalea_registry::OptionalRegistry;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundSoundEntityPacket {
     pub sound: OptionalRegistry<azalea_registry::SoundEvent>,
     pub source: SoundSource,
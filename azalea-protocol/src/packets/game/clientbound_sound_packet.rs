@@ -3,6 +3,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use azalea_registry::OptionalRegistry;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundSoundPacket {
     pub sound: OptionalRegistry<azalea_registry::SoundEvent>,
     pub source: SoundSource,
@@ -16,6 +17,7 @@ pub struct ClientboundSoundPacket {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SoundSource {
     Master = 0,
     Music = 1,
@@ -6,6 +6,7 @@ use std::io::{Cursor, Write};
 use super::clientbound_sound_packet::SoundSource;
 
 #[derive(Clone, Debug, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundStopSoundPacket {
     pub source: Option<SoundSource>,
     pub name: Option<ResourceLocation>,
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use std::io::Cursor;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundUpdateAdvancementsPacket {
     pub reset: bool,
     pub added: HashMap<ResourceLocation, Advancement>,
@@ -14,6 +15,7 @@ pub struct ClientboundUpdateAdvancementsPacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Advancement {
     parent_id: Option<ResourceLocation>,
     display: Option<DisplayInfo>,
@@ -24,6 +26,7 @@ pub struct Advancement {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DisplayInfo {
     pub title: Component,
     pub description: Component,
@@ -97,6 +100,7 @@ impl azalea_buf::McBufReadable for DisplayInfo {
 }
 
 #[derive(Clone, Debug, Copy, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrameType {
     Task = 0,
     Challenge = 1,
@@ -105,11 +109,13 @@ pub enum FrameType {
 
 // nothing is written here
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Criterion {}
 
 pub type AdvancementProgress = HashMap<ResourceLocation, CriterionProgress>;
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CriterionProgress {
     date: Option<u64>,
 }
@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundGamePacket;
 use azalea_world::entity::attributes::AttributeModifier;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundUpdateAttributesPacket {
     #[var]
     pub entity_id: u32,
@@ -11,6 +12,7 @@ pub struct ClientboundUpdateAttributesPacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttributeSnapshot {
     pub attribute: ResourceLocation,
     pub base: f64,
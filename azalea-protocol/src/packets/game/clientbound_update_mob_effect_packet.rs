@@ -2,6 +2,7 @@ use azalea_buf::McBuf;
 use azalea_protocol_macros::ClientboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundUpdateMobEffectPacket {
     #[var]
     pub entity_id: u32,
@@ -12,3 +13,29 @@ pub struct ClientboundUpdateMobEffectPacket {
     pub flags: u8,
     pub factor_data: Option<azalea_nbt::Tag>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_buf::McBufReadable;
+    use azalea_registry::MobEffect;
+    use std::io::Cursor;
+
+    fn read_effect(id: u8) -> Option<MobEffect> {
+        // entity id, effect, amplifier, duration, flags, no factor data
+        let buf = [0, id, 0, 0, 0, 0];
+        ClientboundUpdateMobEffectPacket::read_from(&mut Cursor::new(&buf[..]))
+            .ok()
+            .map(|p| p.effect)
+    }
+
+    #[test]
+    fn test_effect_ids() {
+        // mob effect ids start at 1
+        assert_eq!(read_effect(0), None);
+        assert_eq!(read_effect(1), Some(MobEffect::Speed));
+        assert_eq!(read_effect(3), Some(MobEffect::Haste));
+        assert_eq!(read_effect(33), Some(MobEffect::Darkness));
+        assert_eq!(read_effect(34), None);
+    }
+}
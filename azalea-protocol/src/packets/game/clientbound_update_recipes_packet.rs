@@ -7,6 +7,7 @@ use log::debug;
 use std::io::{Cursor, Write};
 
 #[derive(Clone, Debug, McBufWritable, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundUpdateRecipesPacket {
     pub recipes: Vec<Recipe>,
 }
@@ -68,12 +69,14 @@ impl McBufReadable for ClientboundUpdateRecipesPacket {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Recipe {
     pub identifier: ResourceLocation,
     pub data: RecipeData,
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapelessRecipe {
     /// Used to group similar recipes together in the recipe book.
     /// Tag is present in recipe JSON
@@ -83,6 +86,7 @@ pub struct ShapelessRecipe {
     pub result: Slot,
 }
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapedRecipe {
     pub width: usize,
     pub height: usize,
@@ -93,6 +97,7 @@ pub struct ShapedRecipe {
 }
 
 #[derive(Clone, Debug, Copy, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CraftingBookCategory {
     Building = 0,
     Redstone,
@@ -138,6 +143,7 @@ impl McBufReadable for ShapedRecipe {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CookingRecipe {
     pub group: String,
     pub category: CraftingBookCategory,
@@ -148,12 +154,14 @@ pub struct CookingRecipe {
     pub cooking_time: u32,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StoneCutterRecipe {
     pub group: String,
     pub ingredient: Ingredient,
     pub result: Slot,
 }
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SmithingRecipe {
     pub base: Ingredient,
     pub addition: Ingredient,
@@ -161,11 +169,13 @@ pub struct SmithingRecipe {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleRecipe {
     pub category: CraftingBookCategory,
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RecipeData {
     CraftingShapeless(ShapelessRecipe),
     CraftingShaped(ShapedRecipe),
@@ -192,6 +202,7 @@ pub enum RecipeData {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ingredient {
     pub allowed: Vec<Slot>,
 }
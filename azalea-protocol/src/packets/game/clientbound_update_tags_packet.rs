@@ -1,3 +1,4 @@
+use azalea_buf::remap::{remap, server_id, RemappedId};
 use azalea_buf::{BufReadError, McBuf, McBufVarReadable, McBufVarWritable};
 use azalea_buf::{McBufReadable, McBufWritable};
 use azalea_core::ResourceLocation;
@@ -7,17 +8,20 @@ use std::ops::Deref;
 use std::{collections::HashMap, io::Write};
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundUpdateTagsPacket {
     pub tags: TagMap,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tags {
     pub name: ResourceLocation,
     pub elements: Vec<i32>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TagMap(HashMap<ResourceLocation, Vec<Tags>>);
 
 impl McBufReadable for TagMap {
@@ -28,8 +32,17 @@ impl McBufReadable for TagMap {
             let tag_type = ResourceLocation::read_from(buf)?;
             let tags_count = i32::var_read_from(buf)? as usize;
             let mut tags_vec = Vec::with_capacity(tags_count);
+            let registry = tag_type.to_string();
             for _ in 0..tags_count {
-                let tags = Tags::read_from(buf)?;
+                let mut tags = Tags::read_from(buf)?;
+                // the elements are ids in the registry, which the server might
+                // number differently than vanilla
+                for element in &mut tags.elements {
+                    *element = match remap(&registry, *element as u32) {
+                        RemappedId::Vanilla(id) => id as i32,
+                        RemappedId::Modded(entry) => entry.id as i32,
+                    };
+                }
                 tags_vec.push(tags);
             }
             data.insert(tag_type, tags_vec);
@@ -40,10 +53,19 @@ impl McBufReadable for TagMap {
 
 impl McBufWritable for TagMap {
     fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
-        (self.len() as u32).write_into(buf)?;
+        (self.len() as u32).var_write_into(buf)?;
         for (k, v) in &self.0 {
             k.write_into(buf)?;
-            v.write_into(buf)?;
+            let registry = k.to_string();
+            (v.len() as u32).var_write_into(buf)?;
+            for tags in v {
+                tags.name.write_into(buf)?;
+                tags.elements
+                    .iter()
+                    .map(|element| server_id(&registry, *element as u32) as i32)
+                    .collect::<Vec<_>>()
+                    .var_write_into(buf)?;
+            }
         }
         Ok(())
     }
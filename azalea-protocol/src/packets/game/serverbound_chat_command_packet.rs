@@ -4,6 +4,7 @@ use azalea_crypto::MessageSignature;
 use azalea_protocol_macros::ServerboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundChatCommandPacket {
     pub command: String,
     pub timestamp: u64,
@@ -13,6 +14,7 @@ pub struct ServerboundChatCommandPacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArgumentSignature {
     pub name: String,
     pub signature: MessageSignature,
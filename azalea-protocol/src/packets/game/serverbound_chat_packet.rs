@@ -4,6 +4,7 @@ use azalea_crypto::MessageSignature;
 use azalea_protocol_macros::ServerboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundChatPacket {
     pub message: String,
     pub timestamp: u64,
@@ -13,6 +14,7 @@ pub struct ServerboundChatPacket {
 }
 
 #[derive(Clone, Debug, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LastSeenMessagesUpdate {
     #[var]
     pub offset: u32,
@@ -3,17 +3,20 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundChatSessionUpdatePacket {
     pub chat_session: RemoteChatSessionData,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoteChatSessionData {
     pub session_id: Uuid,
     pub profile_public_key: ProfilePublicKeyData,
 }
 
 #[derive(Clone, Debug, McBuf, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProfilePublicKeyData {
     pub expires_at: u64,
     pub key: Vec<u8>,
@@ -2,6 +2,7 @@ use azalea_buf::McBuf;
 use azalea_protocol_macros::ServerboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundClientInformationPacket {
     /// The locale of the client.
     pub language: String,
@@ -38,6 +39,7 @@ impl Default for ServerboundClientInformationPacket {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChatVisibility {
     /// All chat messages should be sent to the client.
     Full = 0,
@@ -49,6 +51,7 @@ pub enum ChatVisibility {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HumanoidArm {
     Left = 0,
     Right = 1,
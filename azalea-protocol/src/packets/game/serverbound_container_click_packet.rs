@@ -4,6 +4,7 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundContainerClickPacket {
     pub container_id: u8,
     #[var]
@@ -16,6 +17,7 @@ pub struct ServerboundContainerClickPacket {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClickType {
     Pickup = 0,
     QuickMove = 1,
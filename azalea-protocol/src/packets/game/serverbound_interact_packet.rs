@@ -5,6 +5,7 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use std::io::{Cursor, Write};
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundInteractPacket {
     #[var]
     pub entity_id: u32,
@@ -14,6 +15,7 @@ pub struct ServerboundInteractPacket {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ActionType {
     Interact {
         hand: InteractionHand,
@@ -78,6 +80,7 @@ impl McBufReadable for ActionType {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InteractionHand {
     MainHand = 0,
     OffHand = 1,
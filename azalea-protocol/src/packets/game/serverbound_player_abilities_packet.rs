@@ -4,6 +4,7 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use std::io::Cursor;
 
 #[derive(Clone, Debug, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundPlayerAbilitiesPacket {
     is_flying: bool,
 }
@@ -4,6 +4,7 @@ use azalea_core::Direction;
 use azalea_protocol_macros::ServerboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundPlayerActionPacket {
     pub action: Action,
     pub pos: BlockPos,
@@ -13,6 +14,7 @@ pub struct ServerboundPlayerActionPacket {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     StartDestroyBlock = 0,
     AbortDestroyBlock = 1,
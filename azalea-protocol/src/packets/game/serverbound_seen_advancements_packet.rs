@@ -5,12 +5,14 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use std::io::Cursor;
 
 #[derive(Clone, Debug, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundSeenAdvancementsPacket {
     pub action: Action,
     pub tab: Option<ResourceLocation>,
 }
 
 #[derive(McBuf, Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     OpenedTab = 0,
     ClosedScreen = 1,
@@ -5,6 +5,7 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use std::io::Cursor;
 
 #[derive(Clone, Debug, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundSetCommandBlockPacket {
     pub pos: BlockPos,
     pub command: String,
@@ -16,6 +17,7 @@ pub struct ServerboundSetCommandBlockPacket {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     Sequence = 0,
     Auto = 1,
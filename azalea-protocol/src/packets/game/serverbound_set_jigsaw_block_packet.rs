@@ -9,6 +9,7 @@ use std::io::Cursor;
 use std::io::Write;
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundSetJigsawBlockPacket {
     pub pos: BlockPos,
     pub name: ResourceLocation,
@@ -19,6 +20,7 @@ pub struct ServerboundSetJigsawBlockPacket {
                         * implemented */
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JointType {
     Rollable,
     Aligned,
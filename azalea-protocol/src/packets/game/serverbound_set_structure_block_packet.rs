@@ -6,6 +6,7 @@ use azalea_protocol_macros::ServerboundGamePacket;
 use std::io::{Cursor, Write};
 
 #[derive(Clone, Debug, McBuf, ServerboundGamePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundSetStructureBlockPacket {
     pub pos: BlockPos,
     pub update_type: UpdateType,
@@ -23,6 +24,7 @@ pub struct ServerboundSetStructureBlockPacket {
 }
 
 #[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BytePosition {
     pub x: u8,
     pub y: u8,
@@ -30,6 +32,7 @@ pub struct BytePosition {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UpdateType {
     UpdateData = 0,
     SaveArea = 1,
@@ -38,6 +41,7 @@ pub enum UpdateType {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StructureMode {
     Save = 0,
     Load = 1,
@@ -46,6 +50,7 @@ pub enum StructureMode {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mirror {
     None = 0,
     LeftRight = 1,
@@ -53,6 +58,7 @@ pub enum Mirror {
 }
 
 #[derive(McBuf, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Rotation {
     None = 0,
     Clockwise90 = 1,
@@ -61,6 +67,7 @@ pub enum Rotation {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flags {
     pub ignore_entities: bool,
     pub show_air: bool,
@@ -0,0 +1,369 @@
+//! Game packets that have a different layout in 1.19.2.
+//!
+//! Chat signing worked differently in 1.19.2, so the signatures and
+//! acknowledgements in chat packets aren't converted. Unsigned messages (which
+//! is what azalea sends) work in both versions.
+
+use super::{
+    clientbound_commands_packet::{
+        BrigadierNodeStub, BrigadierParser, BrigadierString, ClientboundCommandsPacket,
+    },
+    clientbound_merchant_offers_packet::{ClientboundMerchantOffersPacket, MerchantOffer},
+    clientbound_server_data_packet::ClientboundServerDataPacket,
+    serverbound_chat_command_packet::ServerboundChatCommandPacket,
+    serverbound_chat_packet::{LastSeenMessagesUpdate, ServerboundChatPacket},
+};
+use azalea_buf::{
+    BufReadError, McBuf, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
+    UnsizedByteArray,
+};
+use azalea_chat::Component;
+use azalea_core::{ResourceLocation, Slot};
+use azalea_crypto::MessageSignature;
+use std::io::{Cursor, Write};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundChatPacketV760 {
+    pub message: String,
+    pub timestamp: u64,
+    pub salt: u64,
+    /// Empty if the message isn't signed.
+    pub signature: Vec<u8>,
+    pub signed_preview: bool,
+    pub last_seen_messages: LastSeenMessagesUpdateV760,
+}
+
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundChatCommandPacketV760 {
+    pub command: String,
+    pub timestamp: u64,
+    pub salt: u64,
+    pub argument_signatures: Vec<ArgumentSignatureV760>,
+    pub signed_preview: bool,
+    pub last_seen_messages: LastSeenMessagesUpdateV760,
+}
+
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArgumentSignatureV760 {
+    pub name: String,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Clone, Debug, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LastSeenMessagesUpdateV760 {
+    pub last_seen: Vec<LastSeenMessagesEntryV760>,
+    pub last_received: Option<LastSeenMessagesEntryV760>,
+}
+
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LastSeenMessagesEntryV760 {
+    pub profile_id: Uuid,
+    pub last_signature: Vec<u8>,
+}
+
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientboundServerDataPacketV760 {
+    pub motd: Option<Component>,
+    pub icon_base64: Option<String>,
+    pub previews_chat: bool,
+    pub enforces_secure_chat: bool,
+}
+
+impl From<ServerboundChatPacket> for ServerboundChatPacketV760 {
+    fn from(packet: ServerboundChatPacket) -> Self {
+        Self {
+            message: packet.message,
+            timestamp: packet.timestamp,
+            salt: packet.salt,
+            signature: packet
+                .signature
+                .map(|s| s.bytes.to_vec())
+                .unwrap_or_default(),
+            signed_preview: false,
+            last_seen_messages: LastSeenMessagesUpdateV760::default(),
+        }
+    }
+}
+
+impl From<ServerboundChatPacketV760> for ServerboundChatPacket {
+    fn from(packet: ServerboundChatPacketV760) -> Self {
+        Self {
+            message: packet.message,
+            timestamp: packet.timestamp,
+            salt: packet.salt,
+            signature: packet
+                .signature
+                .try_into()
+                .ok()
+                .map(|bytes| MessageSignature { bytes }),
+            last_seen_messages: LastSeenMessagesUpdate::default(),
+        }
+    }
+}
+
+impl From<ServerboundChatCommandPacket> for ServerboundChatCommandPacketV760 {
+    fn from(packet: ServerboundChatCommandPacket) -> Self {
+        Self {
+            command: packet.command,
+            timestamp: packet.timestamp,
+            salt: packet.salt,
+            argument_signatures: Vec::new(),
+            signed_preview: false,
+            last_seen_messages: LastSeenMessagesUpdateV760::default(),
+        }
+    }
+}
+
+impl From<ServerboundChatCommandPacketV760> for ServerboundChatCommandPacket {
+    fn from(packet: ServerboundChatCommandPacketV760) -> Self {
+        Self {
+            command: packet.command,
+            timestamp: packet.timestamp,
+            salt: packet.salt,
+            argument_signatures: Vec::new(),
+            last_seen_messages: LastSeenMessagesUpdate::default(),
+        }
+    }
+}
+
+impl From<ClientboundServerDataPacket> for ClientboundServerDataPacketV760 {
+    fn from(packet: ClientboundServerDataPacket) -> Self {
+        Self {
+            motd: packet.motd,
+            icon_base64: packet.icon_base64,
+            previews_chat: false,
+            enforces_secure_chat: packet.enforces_secure_chat,
+        }
+    }
+}
+
+impl From<ClientboundServerDataPacketV760> for ClientboundServerDataPacket {
+    fn from(packet: ClientboundServerDataPacketV760) -> Self {
+        Self {
+            motd: packet.motd,
+            icon_base64: packet.icon_base64,
+            enforces_secure_chat: packet.enforces_secure_chat,
+        }
+    }
+}
+
+/// 1.19.2 numbers the argument parsers differently, and has a few that 1.19.3
+/// replaced with `resource` parsers for their registry.
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientboundCommandsPacketV760 {
+    pub entries: Vec<BrigadierNodeStubV760>,
+    #[var]
+    pub root_index: u32,
+    extra: UnsizedByteArray,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrigadierNodeStubV760(pub BrigadierNodeStub);
+
+/// Make a parser for a resource in the given registry.
+fn resource_parser(registry: &str) -> BrigadierParser {
+    BrigadierParser::Resource {
+        registry_key: ResourceLocation::new(registry).expect("registry names are valid"),
+    }
+}
+
+fn read_parser_v760(buf: &mut Cursor<&[u8]>) -> Result<BrigadierParser, BufReadError> {
+    let id = u32::var_read_from(buf)?;
+    let id = match id {
+        34 => return Ok(resource_parser("minecraft:mob_effect")),
+        39 => return Ok(resource_parser("minecraft:enchantment")),
+        40 => return Ok(resource_parser("minecraft:entity_type")),
+        // function, entity_anchor, int_range, and float_range
+        35..=38 => id - 1,
+        // dimension
+        41 => 38,
+        // time
+        42 => 40,
+        // resource_or_tag
+        43 => 41,
+        // resource
+        44 => 43,
+        _ => id,
+    };
+    BrigadierParser::read_from_id(buf, id)
+}
+
+fn write_parser_v760(
+    parser: &BrigadierParser,
+    mut buf: &mut dyn Write,
+) -> Result<(), std::io::Error> {
+    let parser = match parser {
+        BrigadierParser::Resource { registry_key } => {
+            let id: Option<u32> = match registry_key.to_string().as_str() {
+                "minecraft:mob_effect" => Some(34),
+                "minecraft:enchantment" => Some(39),
+                "minecraft:entity_type" => Some(40),
+                _ => None,
+            };
+            if let Some(id) = id {
+                return id.var_write_into(&mut buf);
+            }
+            parser.clone()
+        }
+        // 1.19.2 doesn't have these, so they're written as the closest parser
+        // it does have
+        BrigadierParser::GameMode => BrigadierParser::String(BrigadierString::SingleWord),
+        BrigadierParser::ResourceOrTagKey { registry_key } => BrigadierParser::ResourceOrTag {
+            registry_key: registry_key.clone(),
+        },
+        BrigadierParser::ResourceKey { registry_key } => BrigadierParser::Resource {
+            registry_key: registry_key.clone(),
+        },
+        parser => parser.clone(),
+    };
+    let id: u32 = match &parser {
+        BrigadierParser::Function => 35,
+        BrigadierParser::EntityAnchor => 36,
+        BrigadierParser::IntRange => 37,
+        BrigadierParser::FloatRange => 38,
+        BrigadierParser::Dimension => 41,
+        BrigadierParser::Time => 42,
+        BrigadierParser::ResourceOrTag { .. } => 43,
+        BrigadierParser::Resource { .. } => 44,
+        // the rest have the same id in both versions
+        parser => {
+            return parser.write_into(&mut buf);
+        }
+    };
+    id.var_write_into(&mut buf)?;
+    parser.write_without_id(&mut buf)
+}
+
+impl McBufReadable for BrigadierNodeStubV760 {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        BrigadierNodeStub::read_with_parser(buf, read_parser_v760).map(Self)
+    }
+}
+
+impl McBufWritable for BrigadierNodeStubV760 {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        self.0.write_with_parser(buf, write_parser_v760)
+    }
+}
+
+impl From<ClientboundCommandsPacket> for ClientboundCommandsPacketV760 {
+    fn from(packet: ClientboundCommandsPacket) -> Self {
+        Self {
+            entries: packet
+                .entries
+                .into_iter()
+                .map(BrigadierNodeStubV760)
+                .collect(),
+            root_index: packet.root_index,
+            extra: packet.extra,
+        }
+    }
+}
+
+impl From<ClientboundCommandsPacketV760> for ClientboundCommandsPacket {
+    fn from(packet: ClientboundCommandsPacketV760) -> Self {
+        Self {
+            entries: packet.entries.into_iter().map(|node| node.0).collect(),
+            root_index: packet.root_index,
+            extra: packet.extra,
+        }
+    }
+}
+
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientboundMerchantOffersPacketV760 {
+    #[var]
+    pub container_id: u32,
+    pub offers: Vec<MerchantOfferV760>,
+    #[var]
+    pub villager_level: u32,
+    #[var]
+    pub villager_xp: u32,
+    pub show_progress: bool,
+    pub can_restock: bool,
+}
+
+/// In 1.19.2 the second cost is only there if the trade has one.
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MerchantOfferV760 {
+    pub base_cost_a: Slot,
+    pub result: Slot,
+    pub cost_b: Option<Slot>,
+    pub out_of_stock: bool,
+    pub uses: u32,
+    pub max_uses: u32,
+    pub xp: u32,
+    pub special_price_diff: i32,
+    pub price_multiplier: f32,
+    pub demand: u32,
+}
+
+impl From<ClientboundMerchantOffersPacket> for ClientboundMerchantOffersPacketV760 {
+    fn from(packet: ClientboundMerchantOffersPacket) -> Self {
+        Self {
+            container_id: packet.container_id,
+            offers: packet
+                .offers
+                .into_iter()
+                .map(|offer| MerchantOfferV760 {
+                    base_cost_a: offer.base_cost_a,
+                    result: offer.result,
+                    cost_b: match offer.cost_b {
+                        Slot::Empty => None,
+                        cost_b => Some(cost_b),
+                    },
+                    out_of_stock: offer.out_of_stock,
+                    uses: offer.uses,
+                    max_uses: offer.max_uses,
+                    xp: offer.xp,
+                    special_price_diff: offer.special_price_diff,
+                    price_multiplier: offer.price_multiplier,
+                    demand: offer.demand,
+                })
+                .collect(),
+            villager_level: packet.villager_level,
+            villager_xp: packet.villager_xp,
+            show_progress: packet.show_progress,
+            can_restock: packet.can_restock,
+        }
+    }
+}
+
+impl From<ClientboundMerchantOffersPacketV760> for ClientboundMerchantOffersPacket {
+    fn from(packet: ClientboundMerchantOffersPacketV760) -> Self {
+        Self {
+            container_id: packet.container_id,
+            offers: packet
+                .offers
+                .into_iter()
+                .map(|offer| MerchantOffer {
+                    base_cost_a: offer.base_cost_a,
+                    result: offer.result,
+                    cost_b: offer.cost_b.unwrap_or(Slot::Empty),
+                    out_of_stock: offer.out_of_stock,
+                    uses: offer.uses,
+                    max_uses: offer.max_uses,
+                    xp: offer.xp,
+                    special_price_diff: offer.special_price_diff,
+                    price_multiplier: offer.price_multiplier,
+                    demand: offer.demand,
+                })
+                .collect(),
+            villager_level: packet.villager_level,
+            villager_xp: packet.villager_xp,
+            show_progress: packet.show_progress,
+            can_restock: packet.can_restock,
+        }
+    }
+}
@@ -1,11 +1,12 @@
-use crate::packets::ConnectionProtocol;
-use azalea_buf::{McBufReadable, McBufVarWritable, McBufWritable};
+use crate::{forwarding::BungeeCordForwarding, packets::ConnectionProtocol};
+use azalea_buf::{McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable};
 use azalea_protocol_macros::ServerboundHandshakePacket;
 use std::hash::Hash;
 
 pub use super::ClientIdentifier;
 
 #[derive(Hash, Clone, Debug, ServerboundHandshakePacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientIntentionPacket {
     #[var]
     pub protocol_version: u32,
@@ -13,12 +14,20 @@ pub struct ClientIntentionPacket {
     pub port: u16,
     pub intention: ConnectionProtocol,
     pub identifier: ClientIdentifier,
+    /// Player information added to the hostname by BungeeCord. This is
+    /// `None` unless we're pretending to be BungeeCord or a client connected
+    /// through it.
+    pub forwarding: Option<BungeeCordForwarding>,
 }
 
 impl McBufWritable for ClientIntentionPacket {
     fn write_into(&self, buf: &mut impl std::io::Write) -> Result<(), std::io::Error> {
         self.protocol_version.var_write_into(buf)?;
-        format!("{0}{1}", self.hostname, self.identifier).write_into(buf)?;
+        let forwarding = match &self.forwarding {
+            Some(forwarding) => forwarding.to_string(),
+            None => String::new(),
+        };
+        format!("{0}{1}{2}", self.hostname, forwarding, self.identifier).write_into(buf)?;
         self.port.write_into(buf)?;
         self.intention.write_into(buf)?;
         Ok(())
@@ -27,12 +36,14 @@ impl McBufWritable for ClientIntentionPacket {
 
 impl McBufReadable for ClientIntentionPacket {
     fn read_from(buf: &mut std::io::Cursor<&[u8]>) -> Result<Self, azalea_buf::BufReadError> {
-        let protocol_version = u32::read_from(buf)?;
+        let protocol_version = u32::var_read_from(buf)?;
         let (hostname, identifier) = ClientIdentifier::split_from_ip(String::read_from(buf)?);
+        let (hostname, forwarding) = BungeeCordForwarding::split_from_hostname(hostname);
         Ok(Self {
             protocol_version,
             hostname,
             identifier,
+            forwarding,
             port: u16::read_from(buf)?,
             intention: ConnectionProtocol::read_from(buf)?,
         })
@@ -13,10 +13,19 @@ declare_state_packets!(
 );
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClientIdentifier {
     Vanilla,
+    /// Forge for Minecraft 1.18 and later, which uses FML3.
     Forge,
+    /// Forge for Minecraft 1.13 to 1.17, which uses FML2.
+    ///
+    /// We only speak the [`ProtocolVersion`]s of 1.19.2 and later, which
+    /// servers on those versions reject before the Forge handshake starts, so
+    /// this can't be used to join them.
+    ///
+    /// [`ProtocolVersion`]: crate::packets::ProtocolVersion
+    ForgeFml2,
     Fabric,
 }
 
@@ -30,6 +39,7 @@ impl Display for ClientIdentifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
             ClientIdentifier::Forge => "\0FML3\0",
+            ClientIdentifier::ForgeFml2 => "\0FML2\0",
             _ => "",
         })
     }
@@ -37,20 +47,30 @@ impl Display for ClientIdentifier {
 
 impl ClientIdentifier {
     pub fn split_from_ip(ip: String) -> (String, ClientIdentifier) {
-        let identifier = if ip.ends_with("\0FML3\0") {
-            ClientIdentifier::Forge
-        } else {
-            ClientIdentifier::Vanilla
-        };
-
-        let hostname = match identifier {
-            ClientIdentifier::Forge => ip
-                .split_at(ip.len() - identifier.to_string().len())
-                .0
-                .to_string(),
-            _ => ip,
-        };
-
-        (hostname, identifier)
+        for identifier in [ClientIdentifier::Forge, ClientIdentifier::ForgeFml2] {
+            if let Some(hostname) = ip.strip_suffix(&identifier.to_string()) {
+                return (hostname.to_string(), identifier);
+            }
+        }
+        (ip, ClientIdentifier::Vanilla)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_from_ip() {
+        for identifier in [
+            ClientIdentifier::Vanilla,
+            ClientIdentifier::Forge,
+            ClientIdentifier::ForgeFml2,
+        ] {
+            assert_eq!(
+                ClientIdentifier::split_from_ip(format!("localhost{identifier}")),
+                ("localhost".to_string(), identifier)
+            );
+        }
     }
 }
@@ -4,6 +4,7 @@ use azalea_protocol_macros::ClientboundLoginPacket;
 use std::hash::Hash;
 
 #[derive(Hash, Clone, Debug, McBuf, ClientboundLoginPacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundCustomQueryPacket {
     #[var]
     pub transaction_id: u32,
@@ -3,6 +3,7 @@ use azalea_protocol_macros::ClientboundLoginPacket;
 use std::hash::Hash;
 
 #[derive(Hash, Clone, Debug, ClientboundLoginPacket, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundLoginCompressionPacket {
     #[var]
     pub compression_threshold: i32,
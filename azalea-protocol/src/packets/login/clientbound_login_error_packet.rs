@@ -2,6 +2,7 @@ use azalea_buf::{McBuf, UnsizedByteArray};
 use azalea_protocol_macros::ClientboundLoginPacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundLoginPacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundLoginErrorPacket {
     pub stuff: UnsizedByteArray,
 }
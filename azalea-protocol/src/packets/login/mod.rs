@@ -7,6 +7,7 @@ pub mod clientbound_login_error_packet;
 pub mod serverbound_custom_query_packet;
 pub mod serverbound_hello_packet;
 pub mod serverbound_key_packet;
+pub mod v760;
 
 use azalea_protocol_macros::declare_state_packets;
 
@@ -24,5 +25,22 @@ declare_state_packets!(
         0x03: clientbound_login_compression_packet::ClientboundLoginCompressionPacket,
         0x04: clientbound_custom_query_packet::ClientboundCustomQueryPacket,
         0x17: clientbound_login_error_packet::ClientboundLoginErrorPacket,
+    },
+    Versions => {
+        760 => {
+            Serverbound => {
+                0x00: serverbound_hello_packet::ServerboundHelloPacket as v760::ServerboundHelloPacketV760,
+                0x01: serverbound_key_packet::ServerboundKeyPacket as v760::ServerboundKeyPacketV760,
+                0x02: serverbound_custom_query_packet::ServerboundCustomQueryPacket,
+            },
+            Clientbound => {
+                0x00: clientbound_login_disconnect_packet::ClientboundLoginDisconnectPacket,
+                0x01: clientbound_hello_packet::ClientboundHelloPacket,
+                0x02: clientbound_game_profile_packet::ClientboundGameProfilePacket,
+                0x03: clientbound_login_compression_packet::ClientboundLoginCompressionPacket,
+                0x04: clientbound_custom_query_packet::ClientboundCustomQueryPacket,
+                0x17: clientbound_login_error_packet::ClientboundLoginErrorPacket,
+            },
+        },
     }
 );
@@ -3,6 +3,7 @@ use azalea_core::ResourceLocation;
 use azalea_protocol_macros::ServerboundLoginPacket;
 
 #[derive(Clone, Debug, McBuf, ServerboundLoginPacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundCustomQueryPacket {
     #[var]
     pub transaction_id: u32,
@@ -10,6 +11,7 @@ pub struct ServerboundCustomQueryPacket {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomQuery {
     pub identifier: Option<ResourceLocation>,
     pub data: UnsizedByteArray,
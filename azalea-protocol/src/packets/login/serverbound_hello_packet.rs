@@ -3,6 +3,7 @@ use azalea_protocol_macros::ServerboundLoginPacket;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, PartialEq, Eq, McBuf, ServerboundLoginPacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundHelloPacket {
     pub name: String,
     pub profile_id: Option<Uuid>,
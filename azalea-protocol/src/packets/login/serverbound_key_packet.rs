@@ -2,6 +2,7 @@ use azalea_buf::McBuf;
 use azalea_protocol_macros::ServerboundLoginPacket;
 
 #[derive(Clone, Debug, McBuf, ServerboundLoginPacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerboundKeyPacket {
     pub key_bytes: Vec<u8>,
     pub encrypted_challenge: Vec<u8>,
@@ -0,0 +1,91 @@
+//! Login packets that have a different layout in 1.19.2.
+
+use super::{
+    serverbound_hello_packet::ServerboundHelloPacket, serverbound_key_packet::ServerboundKeyPacket,
+};
+use crate::packets::game::serverbound_chat_session_update_packet::ProfilePublicKeyData;
+use azalea_buf::{BufReadError, McBuf, McBufReadable, McBufWritable};
+use std::io::{Cursor, Write};
+use uuid::Uuid;
+
+/// In 1.19.2 the client could also send its chat signing key here. We never
+/// send one and ignore it if we receive one.
+#[derive(Clone, Debug, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundHelloPacketV760 {
+    pub name: String,
+    pub public_key: Option<ProfilePublicKeyData>,
+    pub profile_id: Option<Uuid>,
+}
+
+impl From<ServerboundHelloPacket> for ServerboundHelloPacketV760 {
+    fn from(packet: ServerboundHelloPacket) -> Self {
+        Self {
+            name: packet.name,
+            public_key: None,
+            profile_id: packet.profile_id,
+        }
+    }
+}
+
+impl From<ServerboundHelloPacketV760> for ServerboundHelloPacket {
+    fn from(packet: ServerboundHelloPacketV760) -> Self {
+        Self {
+            name: packet.name,
+            profile_id: packet.profile_id,
+        }
+    }
+}
+
+/// In 1.19.2 clients that sent a public key would sign the nonce instead of
+/// encrypting it. We only support encrypted nonces, so reading a signed one is
+/// an error.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerboundKeyPacketV760 {
+    pub key_bytes: Vec<u8>,
+    pub encrypted_challenge: Vec<u8>,
+}
+
+impl McBufReadable for ServerboundKeyPacketV760 {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        let key_bytes = Vec::<u8>::read_from(buf)?;
+        if !bool::read_from(buf)? {
+            return Err(BufReadError::Custom(
+                "Signed nonces aren't supported".to_string(),
+            ));
+        }
+        let encrypted_challenge = Vec::<u8>::read_from(buf)?;
+        Ok(Self {
+            key_bytes,
+            encrypted_challenge,
+        })
+    }
+}
+
+impl McBufWritable for ServerboundKeyPacketV760 {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        self.key_bytes.write_into(buf)?;
+        // the nonce is encrypted, not signed
+        true.write_into(buf)?;
+        self.encrypted_challenge.write_into(buf)
+    }
+}
+
+impl From<ServerboundKeyPacket> for ServerboundKeyPacketV760 {
+    fn from(packet: ServerboundKeyPacket) -> Self {
+        Self {
+            key_bytes: packet.key_bytes,
+            encrypted_challenge: packet.encrypted_challenge,
+        }
+    }
+}
+
+impl From<ServerboundKeyPacketV760> for ServerboundKeyPacket {
+    fn from(packet: ServerboundKeyPacketV760) -> Self {
+        Self {
+            key_bytes: packet.key_bytes,
+            encrypted_challenge: packet.encrypted_challenge,
+        }
+    }
+}
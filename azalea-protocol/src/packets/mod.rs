@@ -12,9 +12,67 @@ use std::io::{Cursor, Write};
 // TODO: rename the packet files to just like clientbound_add_entity instead of
 // clientbound_add_entity_packet
 
-pub const PROTOCOL_VERSION: u32 = 761;
+/// The protocol version of the newest version of Minecraft we support.
+pub const PROTOCOL_VERSION: u32 = ProtocolVersion::LATEST.id();
+
+/// A version of the Minecraft protocol that we can connect with.
+///
+/// The packet structs always have the layout of the latest version. When a
+/// connection uses an older version, the packets are converted to and from that
+/// version's ids and layouts as they're written and read, and packets that
+/// don't exist in that version can't be sent or received.
+///
+/// Registry ids are converted too, with the remapper from
+/// [`crate::remap::remapper_for_version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProtocolVersion {
+    /// 1.19.2. Its block, item, and entity type ids are made from the 1.19.3
+    /// ones, as described in [`crate::remap::V760Remapper`]. Packets that
+    /// changed in ways we can't convert, like player info and entity data,
+    /// aren't supported with this version.
+    V760 = 760,
+    /// 1.19.3
+    V761 = 761,
+}
+
+impl ProtocolVersion {
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V761;
+    /// Every version we support, from oldest to newest.
+    pub const ALL: &'static [ProtocolVersion] = &[ProtocolVersion::V760, ProtocolVersion::V761];
+
+    /// The protocol version number that's sent in the handshake.
+    pub const fn id(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_id(id: u32) -> Option<Self> {
+        Self::ALL.iter().copied().find(|v| v.id() == id)
+    }
+
+    /// The Minecraft version name, like `1.19.3`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProtocolVersion::V760 => "1.19.2",
+            ProtocolVersion::V761 => "1.19.3",
+        }
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name(), self.id())
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConnectionProtocol {
     Handshake = -1,
     Game = 0,
@@ -45,6 +103,37 @@ where
     fn read(id: u32, buf: &mut Cursor<&[u8]>) -> Result<Self, Box<ReadPacketError>>;
 
     fn write(&self, buf: &mut impl Write) -> Result<(), std::io::Error>;
+
+    /// A fieldless enum with a variant for every packet in this state, so
+    /// you can talk about packets without having their data.
+    type Kind: Copy + Eq + std::hash::Hash + std::fmt::Debug + Send + Sync + 'static;
+
+    /// Which packet this is.
+    fn kind(&self) -> Self::Kind;
+
+    /// Which packet the given id is in the given protocol version, or `None`
+    /// if it's not a known packet. This doesn't read anything, so it's a cheap
+    /// way of deciding whether a packet is worth decoding.
+    fn kind_for_id(version: ProtocolVersion, id: u32) -> Option<Self::Kind>;
+
+    /// The id of this packet in the given protocol version, or `None` if the
+    /// packet can't be sent in that version.
+    fn id_for(&self, version: ProtocolVersion) -> Option<u32>;
+
+    /// Read a packet that was sent with the given protocol version.
+    fn read_for(
+        version: ProtocolVersion,
+        id: u32,
+        buf: &mut Cursor<&[u8]>,
+    ) -> Result<Self, Box<ReadPacketError>>;
+
+    /// Write a packet with the layout it has in the given protocol version.
+    /// This doesn't write the id, get that with [`Self::id_for`].
+    fn write_for(
+        &self,
+        version: ProtocolVersion,
+        buf: &mut impl Write,
+    ) -> Result<(), std::io::Error>;
 }
 
 impl azalea_buf::McBufReadable for ConnectionProtocol {
@@ -59,3 +148,211 @@ impl McBufWritable for ConnectionProtocol {
         (*self as i32).var_write_into(buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::game::{
+        clientbound_commands_packet::{BrigadierParser, NodeType},
+        serverbound_chat_packet::ServerboundChatPacket,
+        serverbound_chat_session_update_packet::{
+            ProfilePublicKeyData, RemoteChatSessionData, ServerboundChatSessionUpdatePacket,
+        },
+        serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
+        serverbound_set_creative_mode_slot_packet::ServerboundSetCreativeModeSlotPacket,
+        ClientboundGamePacket, ClientboundGamePacketKind, ServerboundGamePacket,
+        ServerboundGamePacketKind,
+    };
+    use crate::{read::deserialize_packet, remap::remapper_for_version};
+    use azalea_buf::remap::with_remapper;
+    use azalea_core::{Slot, SlotData};
+    use azalea_registry::Item;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_old_version_ids() {
+        let packet = ServerboundKeepAlivePacket { id: 1 }.get();
+        assert_eq!(packet.id_for(ProtocolVersion::V761), Some(0x11));
+        assert_eq!(packet.id_for(ProtocolVersion::V760), Some(0x12));
+
+        let mut buf = Vec::new();
+        packet.write_for(ProtocolVersion::V760, &mut buf).unwrap();
+        match ServerboundGamePacket::read_for(ProtocolVersion::V760, 0x12, &mut Cursor::new(&buf))
+            .unwrap()
+        {
+            ServerboundGamePacket::KeepAlive(p) => assert_eq!(p.id, 1),
+            p => panic!("unexpected packet {p:?}"),
+        }
+    }
+
+    #[test]
+    fn test_old_version_layout() {
+        let packet = ServerboundChatPacket {
+            message: "hello".to_string(),
+            timestamp: 1,
+            salt: 2,
+            signature: None,
+            last_seen_messages: Default::default(),
+        }
+        .get();
+        let mut new_buf = Vec::new();
+        packet
+            .write_for(ProtocolVersion::V761, &mut new_buf)
+            .unwrap();
+        let mut old_buf = Vec::new();
+        packet
+            .write_for(ProtocolVersion::V760, &mut old_buf)
+            .unwrap();
+        assert_ne!(new_buf, old_buf);
+
+        match ServerboundGamePacket::read_for(
+            ProtocolVersion::V760,
+            0x05,
+            &mut Cursor::new(&old_buf),
+        )
+        .unwrap()
+        {
+            ServerboundGamePacket::Chat(p) => assert_eq!(p.message, "hello"),
+            p => panic!("unexpected packet {p:?}"),
+        }
+
+        let packet = ServerboundChatSessionUpdatePacket {
+            chat_session: RemoteChatSessionData {
+                session_id: Uuid::nil(),
+                profile_public_key: ProfilePublicKeyData {
+                    expires_at: 0,
+                    key: Vec::new(),
+                    key_signature: Vec::new(),
+                },
+            },
+        }
+        .get();
+        assert_eq!(packet.id_for(ProtocolVersion::V760), None);
+    }
+
+    #[test]
+    fn test_old_version_registry_ids() {
+        let packet = ServerboundSetCreativeModeSlotPacket {
+            slot_num: 36,
+            item_stack: Slot::Present(SlotData::new(Item::EchoShard, 1)),
+        }
+        .get();
+        assert_eq!(packet.id_for(ProtocolVersion::V760), Some(0x2b));
+        let mut data = Vec::new();
+        0x2bu32.var_write_into(&mut data).unwrap();
+        with_remapper(remapper_for_version(ProtocolVersion::V760, None), || {
+            packet.write_for(ProtocolVersion::V760, &mut data)
+        })
+        .unwrap();
+        // 1.19.3 added 34 items before the last one
+        let mut expected = vec![0x2b, 0, 36, 1];
+        (Item::EchoShard as u32 - 34)
+            .var_write_into(&mut expected)
+            .unwrap();
+        assert!(data.starts_with(&expected));
+
+        match deserialize_packet(&mut Cursor::new(&data[..]), ProtocolVersion::V760).unwrap() {
+            ServerboundGamePacket::SetCreativeModeSlot(p) => assert_eq!(
+                p.item_stack,
+                Slot::Present(SlotData::new(Item::EchoShard, 1))
+            ),
+            p => panic!("unexpected packet {p:?}"),
+        }
+
+        assert_eq!(
+            ClientboundGamePacket::kind_for_id(ProtocolVersion::V760, 0x21),
+            Some(ClientboundGamePacketKind::LevelChunkWithLight)
+        );
+    }
+
+    #[test]
+    fn test_old_version_command_parsers() {
+        // a root node with an argument that uses 1.19.2's entity_summon parser
+        let mut data = vec![0x0f, 2, 0x00, 1, 1, 0x02, 0];
+        "entity".to_string().write_into(&mut data).unwrap();
+        data.extend([40, 0]);
+
+        let packet =
+            deserialize_packet(&mut Cursor::new(&data[..]), ProtocolVersion::V760).unwrap();
+        let ClientboundGamePacket::Commands(p) = &packet else {
+            panic!("unexpected packet {packet:?}");
+        };
+        match &p.entries[1].node_type {
+            NodeType::Argument {
+                parser: BrigadierParser::Resource { registry_key },
+                ..
+            } => assert_eq!(registry_key.to_string(), "minecraft:entity_type"),
+            node_type => panic!("unexpected node {node_type:?}"),
+        }
+        let mut buf = Vec::new();
+        packet.write_for(ProtocolVersion::V760, &mut buf).unwrap();
+        assert_eq!(buf, data[1..]);
+
+        // 1.19.2 doesn't have the gamemode parser, so it's sent as a word
+        let mut data = vec![2, 0x00, 1, 1, 0x02, 0];
+        "mode".to_string().write_into(&mut data).unwrap();
+        let mut expected = data.clone();
+        data.extend([39, 0]);
+        expected.extend([5, 0, 0]);
+        let packet = ClientboundGamePacket::read_for(
+            ProtocolVersion::V761,
+            0x0e,
+            &mut Cursor::new(&data[..]),
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        packet.write_for(ProtocolVersion::V760, &mut buf).unwrap();
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_kind_for_id() {
+        let packet = ServerboundKeepAlivePacket { id: 1 }.get();
+        assert_eq!(packet.kind(), ServerboundGamePacketKind::KeepAlive);
+        assert_eq!(
+            ServerboundGamePacket::kind_for_id(ProtocolVersion::V761, 0x11),
+            Some(ServerboundGamePacketKind::KeepAlive)
+        );
+        assert_eq!(
+            ServerboundGamePacket::kind_for_id(ProtocolVersion::V760, 0x12),
+            Some(ServerboundGamePacketKind::KeepAlive)
+        );
+        assert_eq!(
+            ServerboundGamePacket::kind_for_id(ProtocolVersion::V761, 0xff),
+            None
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use crate::packets::game::{
+            clientbound_block_update_packet::ClientboundBlockUpdatePacket,
+            clientbound_custom_payload_packet::ClientboundCustomPayloadPacket,
+            ClientboundGamePacket,
+        };
+        use azalea_block::BlockState;
+        use azalea_core::{BlockPos, ResourceLocation};
+
+        for packet in [
+            ClientboundBlockUpdatePacket {
+                pos: BlockPos::new(1, 2, 3),
+                block_state: BlockState::Stone.into(),
+            }
+            .get(),
+            ClientboundCustomPayloadPacket {
+                identifier: ResourceLocation::new("minecraft:brand").unwrap(),
+                data: vec![1, 2, 3].into(),
+            }
+            .get(),
+        ] {
+            let json = serde_json::to_string(&packet).unwrap();
+            let read: ClientboundGamePacket = serde_json::from_str(&json).unwrap();
+
+            let (mut expected, mut actual) = (Vec::new(), Vec::new());
+            packet.write(&mut expected).unwrap();
+            read.write(&mut actual).unwrap();
+            assert_eq!(expected, actual, "{json}");
+        }
+    }
+}
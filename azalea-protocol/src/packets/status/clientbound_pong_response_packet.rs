@@ -2,6 +2,7 @@ use azalea_buf::McBuf;
 use azalea_protocol_macros::ClientboundStatusPacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundStatusPacket)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientboundPongResponsePacket {
     pub time: u64,
 }
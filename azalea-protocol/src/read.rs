@@ -1,6 +1,8 @@
 //! Read packets from a stream.
 
-use crate::packets::ProtocolPacket;
+use crate::packets::{ProtocolPacket, ProtocolVersion};
+use crate::remap::remapper_for_version;
+use azalea_buf::remap::{current_remapper, with_remapper};
 use azalea_buf::BufReadError;
 use azalea_buf::McBufVarReadable;
 use azalea_crypto::Aes128CfbDec;
@@ -126,13 +128,19 @@ fn frame_splitter(buffer: &mut BytesMut) -> Result<Option<Vec<u8>>, FrameSplitte
     Ok(None)
 }
 
-fn packet_decoder<P: ProtocolPacket + Debug>(
+/// Read a packet's id and contents from a buffer that has already been
+/// decrypted and decompressed, like the ones returned by [`read_raw_packet`].
+pub fn deserialize_packet<P: ProtocolPacket + Debug>(
     stream: &mut Cursor<&[u8]>,
+    version: ProtocolVersion,
 ) -> Result<P, Box<ReadPacketError>> {
     // Packet ID
     let packet_id =
         u32::var_read_from(stream).map_err(|e| ReadPacketError::ReadPacketId { source: e })?;
-    P::read(packet_id, stream)
+    // the packet is read with the ids of its version on top of the remapper
+    // that the caller set
+    let remapper = remapper_for_version(version, current_remapper());
+    with_remapper(remapper, || P::read_for(version, packet_id, stream))
 }
 
 // this is always true in multiplayer, false in singleplayer
@@ -209,7 +217,41 @@ pub async fn read_packet<'a, P: ProtocolPacket + Debug, R>(
     buffer: &mut BytesMut,
     compression_threshold: Option<u32>,
     cipher: &mut Option<Aes128CfbDec>,
+    version: ProtocolVersion,
 ) -> Result<P, Box<ReadPacketError>>
+where
+    R: AsyncRead + std::marker::Unpin + std::marker::Send + std::marker::Sync,
+{
+    let buf = read_raw_packet(stream, buffer, compression_threshold, cipher).await?;
+    let packet = deserialize_packet(&mut Cursor::new(&buf[..]), version)?;
+    Ok(packet)
+}
+
+/// Read the bytes of a single packet from a stream, without parsing it. The
+/// returned bytes are decrypted and decompressed, and start with the packet
+/// id.
+pub async fn read_raw_packet<'a, R>(
+    stream: &'a mut R,
+    buffer: &mut BytesMut,
+    compression_threshold: Option<u32>,
+    cipher: &mut Option<Aes128CfbDec>,
+) -> Result<Vec<u8>, Box<ReadPacketError>>
+where
+    R: AsyncRead + std::marker::Unpin + std::marker::Send + std::marker::Sync,
+{
+    read_raw_packet_with_size(stream, buffer, compression_threshold, cipher)
+        .await
+        .map(|(buf, _)| buf)
+}
+
+/// Like [`read_raw_packet`], but also returns how many bytes the packet took
+/// up on the wire, including its length prefix.
+pub async fn read_raw_packet_with_size<'a, R>(
+    stream: &'a mut R,
+    buffer: &mut BytesMut,
+    compression_threshold: Option<u32>,
+    cipher: &mut Option<Aes128CfbDec>,
+) -> Result<(Vec<u8>, usize), Box<ReadPacketError>>
 where
     R: AsyncRead + std::marker::Unpin + std::marker::Send + std::marker::Sync,
 {
@@ -236,6 +278,8 @@ where
         };
     };
 
+    let wire_size = varint_size(buf.len() as u32) + buf.len();
+
     if let Some(compression_threshold) = compression_threshold {
         buf = compression_decoder(&mut Cursor::new(&buf[..]), compression_threshold)
             .map_err(ReadPacketError::from)?;
@@ -253,9 +297,17 @@ where
         trace!("Reading packet with bytes: {buf_string}");
     }
 
-    let packet = packet_decoder(&mut Cursor::new(&buf[..]))?;
+    Ok((buf, wire_size))
+}
 
-    Ok(packet)
+/// The number of bytes that a number takes up when it's written as a varint.
+pub(crate) fn varint_size(mut value: u32) -> usize {
+    let mut size = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        size += 1;
+    }
+    size
 }
 
 // #[cfg(test)]
@@ -283,7 +335,7 @@ where
 // 34, 58, 34, 91, 77, 69,             77, 66, 69, 82, 93, 32, 112, 108, 97,
 // 121, 101, 114, 49, 34, 125, 93, 44, 34, 116, 101,             120, 116, 34,
 // 58, 34, 34, 125, 0,         ]);
-//         let packet = packet_decoder::<ClientboundGamePacket>(&mut
+//         let packet = deserialize_packet::<ClientboundGamePacket>(&mut
 // buf).unwrap();         match &packet {
 //             ClientboundGamePacket::PlayerChat(m) => {
 //                 assert_eq!(
@@ -0,0 +1,546 @@
+//! Translate the ids that modded servers use into vanilla ones.
+//!
+//! Forge tells the client the id of every entry in its registries during
+//! login, and Fabric syncs them once the client is in the game. A
+//! [`RegistryRemapper`] is built from those, and is then used when
+//! decoding the packets on the connection so modded entries come through as
+//! [`MaybeModded::Modded`] instead of failing to decode, and when encoding
+//! them so the server gets back the ids it uses.
+//!
+//! Older protocol versions have different ids too, since new versions add
+//! entries to the registries. [`remapper_for_version`] adds a
+//! [`V760Remapper`] for 1.19.2 connections, and it's applied whenever packets
+//! are decoded or encoded for that version.
+//!
+//! [`MaybeModded::Modded`]: azalea_buf::remap::MaybeModded::Modded
+
+use crate::packets::{
+    fabric::FabricRegistries, forge::clientbound_registry_packet::ForgeRegistryData,
+    ProtocolVersion,
+};
+use azalea_block::BlockState;
+use azalea_buf::remap::{IdRemapper, ModdedEntry, Remappable, RemappedId};
+use azalea_core::ResourceLocation;
+use azalea_registry::{Block, EntityType, Item};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// The registries that have types which can be remapped.
+const REMAPPED_REGISTRIES: &[&str] = &[Block::REGISTRY, Item::REGISTRY, EntityType::REGISTRY];
+
+/// The vanilla id of an entry in one of the registries that we remap.
+fn vanilla_id(registry: &str, name: &str) -> Option<u32> {
+    fn id<T: FromStr + Remappable>(name: &str) -> Option<u32> {
+        T::from_str(name).ok().map(|value| value.vanilla_id())
+    }
+    match registry {
+        Block::REGISTRY => id::<Block>(name),
+        Item::REGISTRY => id::<Item>(name),
+        EntityType::REGISTRY => id::<EntityType>(name),
+        _ => None,
+    }
+}
+
+/// A table of the ids a server uses, built from Forge's registry packets or a
+/// Fabric registry sync.
+///
+/// Only blocks, items, and entity types are remapped. Forge gives modded
+/// blocks ids after the vanilla ones, so vanilla block states keep their ids
+/// and the states of modded blocks are read as unnamed modded entries, since
+/// Forge doesn't tell us how many states each modded block has.
+#[derive(Clone, Debug, Default)]
+pub struct RegistryRemapper {
+    /// The server's raw ids for each registry.
+    registries: HashMap<String, HashMap<u32, RemappedId>>,
+    /// The server's raw ids of the vanilla entries in each registry, by their
+    /// vanilla ids.
+    server_ids: HashMap<String, HashMap<u32, u32>>,
+}
+
+impl RegistryRemapper {
+    /// Add the ids from a Forge registry packet. Registries that we don't
+    /// remap are ignored.
+    pub fn add_forge_registry(&mut self, registry: &ResourceLocation, data: &ForgeRegistryData) {
+        self.add_registry(registry, data.ids.iter().map(|(name, id)| (name, *id)));
+    }
+
+    /// Add the ids from a Fabric registry sync. Registries that we don't remap
+    /// are ignored.
+    pub fn add_fabric_registries(&mut self, registries: &FabricRegistries) {
+        for (registry, ids) in registries.registries() {
+            self.add_registry(registry, ids.iter().map(|(name, id)| (name, *id)));
+        }
+    }
+
+    fn add_registry<'a>(
+        &mut self,
+        registry: &ResourceLocation,
+        entries: impl Iterator<Item = (&'a ResourceLocation, u32)>,
+    ) {
+        let registry = registry.to_string();
+        if !REMAPPED_REGISTRIES.contains(&registry.as_str()) {
+            return;
+        }
+        let ids = entries
+            .map(|(name, id)| {
+                let remapped = match vanilla_id(&registry, &name.to_string()) {
+                    Some(vanilla_id) => RemappedId::Vanilla(vanilla_id),
+                    None => RemappedId::Modded(ModdedEntry {
+                        id,
+                        name: Some(name.to_string()),
+                    }),
+                };
+                (id, remapped)
+            })
+            .collect::<HashMap<_, _>>();
+        let server_ids = ids
+            .iter()
+            .filter_map(|(id, remapped)| match remapped {
+                RemappedId::Vanilla(vanilla_id) => Some((*vanilla_id, *id)),
+                RemappedId::Modded(_) => None,
+            })
+            .collect();
+        self.server_ids.insert(registry.clone(), server_ids);
+        self.registries.insert(registry, ids);
+    }
+
+    /// Whether no registries were added, in which case there's nothing to
+    /// remap.
+    pub fn is_empty(&self) -> bool {
+        self.registries.is_empty()
+    }
+}
+
+impl IdRemapper for RegistryRemapper {
+    fn remap(&self, registry: &str, id: u32) -> RemappedId {
+        match self.registries.get(registry) {
+            Some(ids) => ids
+                .get(&id)
+                .cloned()
+                .unwrap_or(RemappedId::Modded(ModdedEntry { id, name: None })),
+            None => RemappedId::Vanilla(id),
+        }
+    }
+
+    fn server_id(&self, registry: &str, vanilla_id: u32) -> u32 {
+        // if the server doesn't have the entry, there's nothing better to
+        // send than the vanilla id
+        self.server_ids
+            .get(registry)
+            .and_then(|ids| ids.get(&vanilla_id))
+            .copied()
+            .unwrap_or(vanilla_id)
+    }
+
+    fn has_registry(&self, registry: &str) -> bool {
+        self.registries.contains_key(registry)
+    }
+}
+
+/// The blocks that 1.19.3 added, which 1.19.2 doesn't have.
+const V761_BLOCKS: &[Block] = &[
+    Block::BambooPlanks,
+    Block::BambooMosaic,
+    Block::BambooBlock,
+    Block::StrippedBambooBlock,
+    Block::ChiseledBookshelf,
+    Block::BambooSign,
+    Block::BambooWallSign,
+    Block::OakHangingSign,
+    Block::SpruceHangingSign,
+    Block::BirchHangingSign,
+    Block::AcaciaHangingSign,
+    Block::JungleHangingSign,
+    Block::DarkOakHangingSign,
+    Block::CrimsonHangingSign,
+    Block::WarpedHangingSign,
+    Block::MangroveHangingSign,
+    Block::BambooHangingSign,
+    Block::OakWallHangingSign,
+    Block::SpruceWallHangingSign,
+    Block::BirchWallHangingSign,
+    Block::AcaciaWallHangingSign,
+    Block::JungleWallHangingSign,
+    Block::DarkOakWallHangingSign,
+    Block::MangroveWallHangingSign,
+    Block::CrimsonWallHangingSign,
+    Block::WarpedWallHangingSign,
+    Block::BambooWallHangingSign,
+    Block::BambooPressurePlate,
+    Block::BambooTrapdoor,
+    Block::BambooButton,
+    Block::PiglinHead,
+    Block::PiglinWallHead,
+    Block::BambooStairs,
+    Block::BambooMosaicStairs,
+    Block::BambooSlab,
+    Block::BambooMosaicSlab,
+    Block::BambooFenceGate,
+    Block::BambooFence,
+    Block::BambooDoor,
+];
+
+/// The items that 1.19.3 added, which 1.19.2 doesn't have.
+const V761_ITEMS: &[Item] = &[
+    Item::BambooPlanks,
+    Item::BambooMosaic,
+    Item::BambooBlock,
+    Item::StrippedBambooBlock,
+    Item::BambooSlab,
+    Item::BambooMosaicSlab,
+    Item::ChiseledBookshelf,
+    Item::BambooFence,
+    Item::BambooStairs,
+    Item::BambooMosaicStairs,
+    Item::BambooButton,
+    Item::BambooPressurePlate,
+    Item::BambooDoor,
+    Item::BambooTrapdoor,
+    Item::BambooFenceGate,
+    Item::BambooRaft,
+    Item::BambooChestRaft,
+    Item::BambooSign,
+    Item::OakHangingSign,
+    Item::SpruceHangingSign,
+    Item::BirchHangingSign,
+    Item::JungleHangingSign,
+    Item::AcaciaHangingSign,
+    Item::DarkOakHangingSign,
+    Item::MangroveHangingSign,
+    Item::BambooHangingSign,
+    Item::CrimsonHangingSign,
+    Item::WarpedHangingSign,
+    Item::CamelSpawnEgg,
+    Item::EnderDragonSpawnEgg,
+    Item::IronGolemSpawnEgg,
+    Item::SnowGolemSpawnEgg,
+    Item::WitherSpawnEgg,
+    Item::PiglinHead,
+];
+
+/// The entity types that 1.19.3 added, which 1.19.2 doesn't have.
+const V761_ENTITY_TYPES: &[EntityType] = &[EntityType::Camel];
+
+/// The ids of a registry in an older version, made by leaving out the
+/// entries that were added after it from the vanilla registry.
+struct OldIds {
+    /// The vanilla id of each of the old ids.
+    vanilla_ids: Vec<u32>,
+    /// The old id of each vanilla id, or `None` if it was added later.
+    old_ids: Vec<Option<u32>>,
+}
+
+impl OldIds {
+    /// Make the table from whether each vanilla id was added after the old
+    /// version, in order.
+    fn new(added: impl Iterator<Item = bool>) -> Self {
+        let mut vanilla_ids = Vec::new();
+        let mut old_ids = Vec::new();
+        for (vanilla_id, added) in added.enumerate() {
+            if added {
+                old_ids.push(None);
+            } else {
+                old_ids.push(Some(vanilla_ids.len() as u32));
+                vanilla_ids.push(vanilla_id as u32);
+            }
+        }
+        Self {
+            vanilla_ids,
+            old_ids,
+        }
+    }
+
+    fn for_registry<T: Remappable + PartialEq>(added: &[T]) -> Self {
+        Self::new(
+            (0..)
+                .map_while(T::from_vanilla_id)
+                .map(|value| added.contains(&value)),
+        )
+    }
+
+    /// The block states of the blocks that were added are left out, so this
+    /// relies on the other blocks having the same states as before.
+    fn for_block_states(added: &[Block]) -> Self {
+        let added = added.iter().map(Block::to_string).collect::<HashSet<_>>();
+        Self::new((0..=BlockState::max_state()).map(|id| {
+            let state = BlockState::try_from(id).expect("all ids up to the max state are valid");
+            let block = Box::<dyn azalea_block::Block>::from(state);
+            added.contains(&format!("minecraft:{}", block.id()))
+        }))
+    }
+
+    fn remap(&self, id: u32) -> RemappedId {
+        match self.vanilla_ids.get(id as usize) {
+            Some(vanilla_id) => RemappedId::Vanilla(*vanilla_id),
+            None => RemappedId::Modded(ModdedEntry { id, name: None }),
+        }
+    }
+
+    fn server_id(&self, vanilla_id: u32) -> u32 {
+        // entries that the old version doesn't have can't be sent properly,
+        // so they keep their vanilla id like they would with a modded server
+        self.old_ids
+            .get(vanilla_id as usize)
+            .copied()
+            .flatten()
+            .unwrap_or(vanilla_id)
+    }
+}
+
+static V760_IDS: Lazy<HashMap<&'static str, OldIds>> = Lazy::new(|| {
+    HashMap::from([
+        (Block::REGISTRY, OldIds::for_registry(V761_BLOCKS)),
+        (BlockState::REGISTRY, OldIds::for_block_states(V761_BLOCKS)),
+        (Item::REGISTRY, OldIds::for_registry(V761_ITEMS)),
+        (
+            EntityType::REGISTRY,
+            OldIds::for_registry(V761_ENTITY_TYPES),
+        ),
+    ])
+});
+
+/// Translates the ids that 1.19.2 servers use into the 1.19.3 ones that
+/// azalea has.
+///
+/// We don't have the 1.19.2 registries, so the ids are made from the 1.19.3
+/// ones by leaving out what 1.19.3 added (the bamboo wood set, hanging signs,
+/// chiseled bookshelves, piglin heads, camels, and a few spawn eggs), since
+/// the entries that were already there kept their order. Only blocks, block
+/// states, items, and entity types are translated. Other registries, like
+/// block entity types and game events, are read as they are.
+///
+/// If the server is modded, the ids of the registries it sent are translated
+/// with its remapper instead, since those go by name.
+#[derive(Clone, Default)]
+pub struct V760Remapper {
+    modded: Option<Arc<dyn IdRemapper>>,
+}
+
+impl V760Remapper {
+    pub fn new(modded: Option<Arc<dyn IdRemapper>>) -> Self {
+        Self { modded }
+    }
+
+    fn modded_for(&self, registry: &str) -> Option<&Arc<dyn IdRemapper>> {
+        self.modded
+            .as_ref()
+            .filter(|modded| modded.has_registry(registry))
+    }
+}
+
+impl IdRemapper for V760Remapper {
+    fn remap(&self, registry: &str, id: u32) -> RemappedId {
+        if let Some(modded) = self.modded_for(registry) {
+            return modded.remap(registry, id);
+        }
+        match V760_IDS.get(registry) {
+            Some(ids) => ids.remap(id),
+            None => RemappedId::Vanilla(id),
+        }
+    }
+
+    fn server_id(&self, registry: &str, vanilla_id: u32) -> u32 {
+        if let Some(modded) = self.modded_for(registry) {
+            return modded.server_id(registry, vanilla_id);
+        }
+        match V760_IDS.get(registry) {
+            Some(ids) => ids.server_id(vanilla_id),
+            None => vanilla_id,
+        }
+    }
+}
+
+/// The remapper that packets in the given protocol version are read and
+/// written with, given the one for a modded server's registries.
+pub fn remapper_for_version(
+    version: ProtocolVersion,
+    modded: Option<Arc<dyn IdRemapper>>,
+) -> Option<Arc<dyn IdRemapper>> {
+    static V760_REMAPPER: Lazy<Arc<dyn IdRemapper>> =
+        Lazy::new(|| Arc::new(V760Remapper::default()));
+    match (version, modded) {
+        (ProtocolVersion::V760, None) => Some(V760_REMAPPER.clone()),
+        (ProtocolVersion::V760, Some(modded)) => Some(Arc::new(V760Remapper::new(Some(modded)))),
+        (ProtocolVersion::V761, modded) => modded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_block::BlockState;
+    use azalea_buf::{
+        remap::{with_remapper, MaybeModded},
+        McBufReadable, McBufVarWritable, McBufWritable,
+    };
+    use azalea_core::SlotData;
+    use std::{io::Cursor, sync::Arc};
+
+    fn read<T: Remappable>(id: u32) -> MaybeModded<T> {
+        let mut data = Vec::new();
+        id.var_write_into(&mut data).unwrap();
+        MaybeModded::read_from(&mut Cursor::new(&data[..])).unwrap()
+    }
+
+    #[test]
+    fn test_forge_remap() {
+        let mut remapper = RegistryRemapper::default();
+        remapper.add_forge_registry(
+            &ResourceLocation::new("minecraft:entity_type").unwrap(),
+            &ForgeRegistryData {
+                ids: HashMap::from([
+                    // swapped with what vanilla has
+                    (ResourceLocation::new("minecraft:allay").unwrap(), 1),
+                    (
+                        ResourceLocation::new("minecraft:area_effect_cloud").unwrap(),
+                        0,
+                    ),
+                    (ResourceLocation::new("examplemod:goblin").unwrap(), 5000),
+                ]),
+                aliases: HashMap::new(),
+                overrides: HashMap::new(),
+                blocked: Vec::new(),
+                dummied: Vec::new(),
+            },
+        );
+        assert!(!remapper.is_empty());
+
+        with_remapper(Some(Arc::new(remapper)), || {
+            assert_eq!(read(1), MaybeModded::Vanilla(EntityType::Allay));
+            assert_eq!(
+                read::<EntityType>(5000),
+                MaybeModded::Modded(ModdedEntry {
+                    id: 5000,
+                    name: Some("examplemod:goblin".to_string())
+                })
+            );
+            // items weren't synced, so they're vanilla
+            assert_eq!(read(1), MaybeModded::Vanilla(Item::Stone));
+            assert_eq!(
+                read(BlockState::max_state() + 1),
+                MaybeModded::<BlockState>::Modded(ModdedEntry {
+                    id: BlockState::max_state() + 1,
+                    name: None
+                })
+            );
+        });
+    }
+
+    fn write(value: &impl McBufWritable) -> Vec<u8> {
+        let mut data = Vec::new();
+        value.write_into(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_forge_remap_round_trip() {
+        let mut remapper = RegistryRemapper::default();
+        remapper.add_forge_registry(
+            &ResourceLocation::new("minecraft:item").unwrap(),
+            &ForgeRegistryData {
+                ids: HashMap::from([
+                    // swapped with what vanilla has
+                    (ResourceLocation::new("minecraft:stone").unwrap(), 2),
+                    (ResourceLocation::new("minecraft:granite").unwrap(), 1),
+                    (ResourceLocation::new("examplemod:ruby").unwrap(), 5000),
+                ]),
+                aliases: HashMap::new(),
+                overrides: HashMap::new(),
+                blocked: Vec::new(),
+                dummied: Vec::new(),
+            },
+        );
+
+        with_remapper(Some(Arc::new(remapper)), || {
+            // what we read is written back with the server's id
+            for id in [1, 2, 5000] {
+                let mut data = Vec::new();
+                id.var_write_into(&mut data).unwrap();
+                assert_eq!(write(&read::<Item>(id)), data);
+                // one of the item with no nbt
+                data.extend([1, 0]);
+                let slot = SlotData::read_from(&mut Cursor::new(&data[..])).unwrap();
+                assert_eq!(write(&slot), data);
+            }
+            assert_eq!(write(&Item::Stone), vec![2]);
+            assert_eq!(write(&SlotData::new(Item::Granite, 1)), vec![1, 1, 0]);
+        });
+        assert_eq!(write(&Item::Stone), vec![1]);
+    }
+
+    #[test]
+    fn test_v760_remap() {
+        let remapper = V760Remapper::default();
+        // 1.19.3 added the camel before these
+        assert_eq!(
+            remapper.remap(EntityType::REGISTRY, EntityType::Allay as u32),
+            RemappedId::Vanilla(EntityType::Allay as u32)
+        );
+        assert_eq!(
+            remapper.remap(EntityType::REGISTRY, EntityType::Zombie as u32 - 1),
+            RemappedId::Vanilla(EntityType::Zombie as u32)
+        );
+        assert_eq!(
+            remapper.server_id(EntityType::REGISTRY, EntityType::Zombie as u32),
+            EntityType::Zombie as u32 - 1
+        );
+        assert_eq!(
+            remapper.remap(EntityType::REGISTRY, 118),
+            RemappedId::Modded(ModdedEntry {
+                id: 118,
+                name: None
+            })
+        );
+
+        // every block state that 1.19.2 has goes back to the same id
+        let mut count = 0;
+        for id in 0..=BlockState::max_state() {
+            match remapper.remap(BlockState::REGISTRY, id) {
+                RemappedId::Vanilla(vanilla_id) => {
+                    assert_eq!(remapper.server_id(BlockState::REGISTRY, vanilla_id), id);
+                    count += 1;
+                }
+                RemappedId::Modded(_) => break,
+            }
+        }
+        assert_eq!(count, 21798);
+        // the bamboo planks and mosaic were added before bedrock
+        assert_eq!(
+            remapper.remap(BlockState::REGISTRY, BlockState::Bedrock as u32 - 2),
+            RemappedId::Vanilla(BlockState::Bedrock as u32)
+        );
+    }
+
+    #[test]
+    fn test_v760_modded_remap() {
+        let mut modded = RegistryRemapper::default();
+        modded.add_forge_registry(
+            &ResourceLocation::new("minecraft:item").unwrap(),
+            &ForgeRegistryData {
+                ids: HashMap::from([
+                    (ResourceLocation::new("minecraft:stone").unwrap(), 2),
+                    (ResourceLocation::new("examplemod:ruby").unwrap(), 5000),
+                ]),
+                aliases: HashMap::new(),
+                overrides: HashMap::new(),
+                blocked: Vec::new(),
+                dummied: Vec::new(),
+            },
+        );
+        let remapper = remapper_for_version(ProtocolVersion::V760, Some(Arc::new(modded)));
+        with_remapper(remapper, || {
+            // items come from the server's registry, since they're synced by name
+            assert_eq!(read(2), MaybeModded::Vanilla(Item::Stone));
+            assert_eq!(read::<Item>(5000).vanilla(), None);
+            // and entity types from the 1.19.2 table
+            assert_eq!(
+                read(EntityType::Zombie as u32 - 1),
+                MaybeModded::Vanilla(EntityType::Zombie)
+            );
+        });
+    }
+}
@@ -1,11 +1,24 @@
 //! Resolve IPs from hostnames.
+//!
+//! The [`resolve_address`] and [`resolve_srv`] functions use the default
+//! options and don't cache anything. If you're connecting many times (like in
+//! a swarm), make a [`Resolver`] and reuse it so lookups are cached.
 
 use crate::ServerAddress;
 use async_recursion::async_recursion;
-use std::net::{IpAddr, SocketAddr};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use thiserror::Error;
+use tokio::sync::OnceCell;
 use trust_dns_resolver::{
-    config::{ResolverConfig, ResolverOpts},
+    config::{NameServerConfig, Protocol, ResolverConfig, ResolverOpts},
+    error::{ResolveError, ResolveErrorKind},
     TokioAsyncResolver,
 };
 
@@ -15,51 +28,525 @@ pub enum ResolverError {
     NoSrvRecord,
     #[error("No IP found")]
     NoIp,
+    #[error("Couldn't create the resolver: {0}")]
+    Config(#[from] ResolveError),
+}
+
+/// Which DNS servers a [`Resolver`] asks.
+#[derive(Clone, Debug, Default)]
+pub enum Nameservers {
+    /// Cloudflare's public DNS servers. We use these by default because
+    /// trust_dns_resolver has an issue on Windows where it's really slow using
+    /// the system resolver.
+    #[default]
+    Cloudflare,
+    /// The system's configuration (like /etc/resolv.conf). This also uses the
+    /// hosts file.
+    System,
+    /// The given nameservers, which are asked over UDP and TCP.
+    Custom(Vec<SocketAddr>),
+}
+
+/// How a [`Resolver`] should look up addresses.
+#[derive(Clone, Debug)]
+pub struct ResolverOptions {
+    pub nameservers: Nameservers,
+    /// Hosts that always resolve to the given address, without doing any DNS
+    /// lookups. The port of the address is used instead of the one that was
+    /// requested. The keys are compared case-insensitively.
+    pub overrides: HashMap<String, SocketAddr>,
+    /// Whether to look up `_minecraft._tcp` SRV records.
+    pub srv: bool,
+    /// Whether to remember the results of lookups until their DNS records
+    /// expire. Concurrent lookups for the same address will also wait for a
+    /// single lookup.
+    pub cache: bool,
+}
+
+impl Default for ResolverOptions {
+    fn default() -> Self {
+        Self {
+            nameservers: Nameservers::default(),
+            overrides: HashMap::new(),
+            srv: true,
+            cache: true,
+        }
+    }
+}
+
+/// How long we remember that there's no SRV record for an address if the
+/// nameserver didn't say.
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(60);
+
+/// A value we looked up, and when it expires. Values that didn't need any DNS
+/// lookups (like overrides) never expire.
+type Expiring<V> = (V, Option<Instant>);
+
+type Cache<V> = Mutex<HashMap<(String, u16), Arc<OnceCell<Expiring<V>>>>>;
+
+/// The earlier of two expiry times.
+fn earliest(a: Option<Instant>, b: Option<Instant>) -> Option<Instant> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Resolves Minecraft server addresses with the given [`ResolverOptions`].
+/// This is cheap to clone, and clones share the same cache.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use azalea_protocol::resolver::{Resolver, ResolverOptions};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut options = ResolverOptions::default();
+/// options
+///     .overrides
+///     .insert("example.com".to_string(), "127.0.0.1:25565".parse()?);
+/// let resolver = Resolver::new(options)?;
+/// let address = resolver.resolve_address(&"example.com".try_into()?).await?;
+/// assert_eq!(address, "127.0.0.1:25565".parse()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Resolver {
+    inner: Arc<ResolverInner>,
+}
+
+struct ResolverInner {
+    options: ResolverOptions,
+    dns: TokioAsyncResolver,
+    addresses: Cache<SocketAddr>,
+    srv_addresses: Cache<ServerAddress>,
+}
+
+impl Resolver {
+    pub fn new(mut options: ResolverOptions) -> Result<Self, ResolverError> {
+        let dns = match &options.nameservers {
+            Nameservers::Cloudflare => {
+                TokioAsyncResolver::tokio(ResolverConfig::cloudflare(), ResolverOpts::default())?
+            }
+            Nameservers::System => TokioAsyncResolver::tokio_from_system_conf()?,
+            Nameservers::Custom(addresses) => {
+                let mut nameservers = Vec::new();
+                for address in addresses {
+                    nameservers.push(NameServerConfig::new(*address, Protocol::Udp));
+                    nameservers.push(NameServerConfig::new(*address, Protocol::Tcp));
+                }
+                TokioAsyncResolver::tokio(
+                    ResolverConfig::from_parts(None, Vec::new(), nameservers),
+                    ResolverOpts::default(),
+                )?
+            }
+        };
+        options.overrides = options
+            .overrides
+            .into_iter()
+            .map(|(host, address)| (host.to_lowercase(), address))
+            .collect();
+
+        Ok(Self {
+            inner: Arc::new(ResolverInner {
+                options,
+                dns,
+                addresses: Default::default(),
+                srv_addresses: Default::default(),
+            }),
+        })
+    }
+
+    pub fn options(&self) -> &ResolverOptions {
+        &self.inner.options
+    }
+
+    /// Resolve a Minecraft server address into an IP address and port.
+    /// If it's already an IP address, it's returned as-is.
+    pub async fn resolve_address(
+        &self,
+        address: &ServerAddress,
+    ) -> Result<SocketAddr, ResolverError> {
+        self.cached(&self.inner.addresses, address, || async {
+            let (address, expires) = self.resolve_srv_uncached(address).await?;
+
+            // If the address.host is already in the format of an ip address, return it.
+            if let Ok(ip) = address.host.parse::<IpAddr>() {
+                return Ok((SocketAddr::new(ip, address.port), expires));
+            }
+
+            // it's a hostname, so look up its ip
+            let lookup_ip_result = self.inner.dns.lookup_ip(address.host.clone()).await;
+            let lookup_ip = lookup_ip_result.map_err(|_| ResolverError::NoIp)?;
+
+            Ok((
+                SocketAddr::new(
+                    lookup_ip.iter().next().ok_or(ResolverError::NoIp)?,
+                    address.port,
+                ),
+                earliest(expires, Some(lookup_ip.valid_until())),
+            ))
+        })
+        .await
+    }
+
+    /// Follow the `_minecraft._tcp` SRV records for the address (if they're
+    /// enabled), without looking up the IP of the host we end up at. This is
+    /// useful when something else (like a proxy) resolves the hostname.
+    pub async fn resolve_srv(
+        &self,
+        address: &ServerAddress,
+    ) -> Result<ServerAddress, ResolverError> {
+        self.cached(&self.inner.srv_addresses, address, || {
+            self.resolve_srv_uncached(address)
+        })
+        .await
+    }
+
+    /// Follow the SRV records for the address, and return where we ended up
+    /// and when the records we followed expire.
+    #[async_recursion]
+    async fn resolve_srv_uncached(
+        &self,
+        address: &ServerAddress,
+    ) -> Result<Expiring<ServerAddress>, ResolverError> {
+        if let Some(overridden) = self
+            .inner
+            .options
+            .overrides
+            .get(&address.host.to_lowercase())
+        {
+            return Ok(((*overridden).into(), None));
+        }
+
+        // If the address.host is already in the format of an ip address, return it.
+        if !self.inner.options.srv || address.host.parse::<IpAddr>().is_ok() {
+            return Ok((address.clone(), None));
+        }
+
+        // first, we do a srv lookup for _minecraft._tcp.<host>
+        let srv_redirect_result = self
+            .inner
+            .dns
+            .srv_lookup(format!("_minecraft._tcp.{}", address.host).as_str())
+            .await;
+
+        // if it resolves that means it's a redirect so we call resolve_srv again
+        // with the new host
+        match srv_redirect_result {
+            Ok(redirect_result) => {
+                let redirect_srv = redirect_result
+                    .iter()
+                    .next()
+                    .ok_or(ResolverError::NoSrvRecord)?;
+                let redirect_address = ServerAddress {
+                    host: redirect_srv.target().to_utf8(),
+                    port: redirect_srv.port(),
+                };
+
+                // debug!("redirecting to {:?}", redirect_address);
+
+                let (address, expires) = self.resolve_srv_uncached(&redirect_address).await?;
+                Ok((
+                    address,
+                    earliest(expires, Some(redirect_result.as_lookup().valid_until())),
+                ))
+            }
+            Err(e) => {
+                // there's no SRV record, which we remember for as long as the
+                // nameserver said. if the lookup failed some other way, we try
+                // again next time.
+                let ttl = match e.kind() {
+                    ResolveErrorKind::NoRecordsFound { negative_ttl, .. } => negative_ttl
+                        .map_or(DEFAULT_NEGATIVE_TTL, |ttl| Duration::from_secs(ttl as u64)),
+                    _ => Duration::ZERO,
+                };
+                Ok((address.clone(), Some(Instant::now() + ttl)))
+            }
+        }
+    }
+
+    /// Run the lookup, or get its result from the cache if caching is
+    /// enabled and it hasn't expired. Errors aren't cached.
+    async fn cached<V, F, Fut>(
+        &self,
+        cache: &Cache<V>,
+        address: &ServerAddress,
+        lookup: F,
+    ) -> Result<V, ResolverError>
+    where
+        V: Clone,
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Expiring<V>, ResolverError>>,
+    {
+        if !self.inner.options.cache {
+            return lookup().await.map(|(value, _)| value);
+        }
+        let cell = {
+            let mut cache = cache.lock();
+            // forget expired lookups, and failed ones that nothing is waiting
+            // on anymore, so the cache doesn't keep growing. lookups that
+            // already have a cell can still use it.
+            let now = Instant::now();
+            cache.retain(|_, cell| match cell.get() {
+                Some((_, expires)) => expires.map_or(true, |expires| expires > now),
+                None => Arc::strong_count(cell) > 1,
+            });
+            cache
+                .entry((address.host.to_lowercase(), address.port))
+                .or_default()
+                .clone()
+        };
+        cell.get_or_try_init(lookup)
+            .await
+            .map(|(value, _)| value.clone())
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new(ResolverOptions::default()).expect("The default resolver should always work")
+    }
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resolver")
+            .field("options", &self.inner.options)
+            .finish_non_exhaustive()
+    }
 }
 
 /// Resolve a Minecraft server address into an IP address and port.
 /// If it's already an IP address, it's returned as-is.
-#[async_recursion]
+///
+/// This uses the default [`ResolverOptions`] without a cache.
 pub async fn resolve_address(address: &ServerAddress) -> Result<SocketAddr, ResolverError> {
-    // If the address.host is already in the format of an ip address, return it.
-    if let Ok(ip) = address.host.parse::<IpAddr>() {
-        return Ok(SocketAddr::new(ip, address.port));
-    }
-
-    // we specify Cloudflare instead of the default resolver because
-    // trust_dns_resolver has an issue on Windows where it's really slow using the
-    // default resolver
-    let resolver =
-        TokioAsyncResolver::tokio(ResolverConfig::cloudflare(), ResolverOpts::default()).unwrap();
-
-    // first, we do a srv lookup for _minecraft._tcp.<host>
-    let srv_redirect_result = resolver
-        .srv_lookup(format!("_minecraft._tcp.{}", address.host).as_str())
-        .await;
-
-    // if it resolves that means it's a redirect so we call resolve_address again
-    // with the new host
-    if let Ok(redirect_result) = srv_redirect_result {
-        let redirect_srv = redirect_result
-            .iter()
-            .next()
-            .ok_or(ResolverError::NoSrvRecord)?;
-        let redirect_address = ServerAddress {
-            host: redirect_srv.target().to_utf8(),
-            port: redirect_srv.port(),
-        };
+    uncached_resolver()?.resolve_address(address).await
+}
+
+/// Follow the `_minecraft._tcp` SRV records for the address, without looking
+/// up the IP of the host we end up at. See [`Resolver::resolve_srv`].
+///
+/// This uses the default [`ResolverOptions`] without a cache.
+pub async fn resolve_srv(address: &ServerAddress) -> Result<ServerAddress, ResolverError> {
+    uncached_resolver()?.resolve_srv(address).await
+}
+
+fn uncached_resolver() -> Result<Resolver, ResolverError> {
+    Resolver::new(ResolverOptions {
+        cache: false,
+        ..Default::default()
+    })
+}
 
-        // debug!("redirecting to {:?}", redirect_address);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::UdpSocket;
+    use trust_dns_resolver::proto::{
+        op::{Message, MessageType},
+        rr::{rdata::SRV, Name, RData, Record, RecordType},
+    };
 
-        return resolve_address(&redirect_address).await;
+    /// Start a DNS server that answers every A query with 127.0.0.1 and every
+    /// SRV query for `server.test` with `target.test.:25566`, and counts the
+    /// queries it gets. The records live for `ttl` seconds.
+    async fn dns_server(ttl: u32) -> (SocketAddr, Arc<AtomicUsize>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let queries = Arc::new(AtomicUsize::new(0));
+        let queries_clone = queries.clone();
+        tokio::spawn(async move {
+            let mut buf = [0; 512];
+            while let Ok((n, from)) = socket.recv_from(&mut buf).await {
+                let request = Message::from_vec(&buf[..n]).unwrap();
+                queries_clone.fetch_add(1, Ordering::SeqCst);
+                let mut response = request.clone();
+                response.set_message_type(MessageType::Response);
+                for query in request.queries() {
+                    let rdata = match query.query_type() {
+                        RecordType::A => RData::A([127, 0, 0, 1].into()),
+                        RecordType::SRV
+                            if query.name().to_ascii() == "_minecraft._tcp.server.test." =>
+                        {
+                            RData::SRV(SRV::new(
+                                0,
+                                0,
+                                25566,
+                                Name::from_ascii("target.test.").unwrap(),
+                            ))
+                        }
+                        _ => continue,
+                    };
+                    response.add_answer(Record::from_rdata(query.name().clone(), ttl, rdata));
+                }
+                socket
+                    .send_to(&response.to_vec().unwrap(), from)
+                    .await
+                    .unwrap();
+            }
+        });
+        (address, queries)
     }
 
-    // there's no redirect, try to resolve this as an ip address
-    let lookup_ip_result = resolver.lookup_ip(address.host.clone()).await;
-    let lookup_ip = lookup_ip_result.map_err(|_| ResolverError::NoIp)?;
+    fn address(host: &str) -> ServerAddress {
+        ServerAddress {
+            host: host.to_string(),
+            port: 25565,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overrides() {
+        let mut options = ResolverOptions::default();
+        options
+            .overrides
+            .insert("Example.com".to_string(), "10.0.0.1:1234".parse().unwrap());
+        let resolver = Resolver::new(options).unwrap();
+
+        assert_eq!(
+            resolver
+                .resolve_address(&address("example.COM"))
+                .await
+                .unwrap(),
+            "10.0.0.1:1234".parse().unwrap()
+        );
+        assert_eq!(
+            resolver
+                .resolve_srv(&address("example.com"))
+                .await
+                .unwrap()
+                .host,
+            "10.0.0.1"
+        );
+        assert_eq!(
+            resolver
+                .resolve_address(&address("127.0.0.1"))
+                .await
+                .unwrap(),
+            "127.0.0.1:25565".parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_nameserver() {
+        let (dns_address, _) = dns_server(60).await;
+        let resolver = Resolver::new(ResolverOptions {
+            nameservers: Nameservers::Custom(vec![dns_address]),
+            cache: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let srv = resolver.resolve_srv(&address("server.test")).await.unwrap();
+        assert_eq!(srv.host, "target.test.");
+        assert_eq!(srv.port, 25566);
+        assert_eq!(
+            resolver
+                .resolve_address(&address("server.test"))
+                .await
+                .unwrap(),
+            "127.0.0.1:25566".parse().unwrap()
+        );
+
+        let resolver = Resolver::new(ResolverOptions {
+            nameservers: Nameservers::Custom(vec![dns_address]),
+            srv: false,
+            cache: false,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            resolver
+                .resolve_address(&address("server.test"))
+                .await
+                .unwrap(),
+            "127.0.0.1:25565".parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let (dns_address, queries) = dns_server(60).await;
+        let resolver = Resolver::new(ResolverOptions {
+            nameservers: Nameservers::Custom(vec![dns_address]),
+            srv: false,
+            ..Default::default()
+        })
+        .unwrap();
 
-    Ok(SocketAddr::new(
-        lookup_ip.iter().next().unwrap(),
-        address.port,
-    ))
+        let lookups = (0..50).map(|_| {
+            let resolver = resolver.clone();
+            tokio::spawn(async move { resolver.resolve_address(&address("server.test")).await })
+        });
+        for lookup in futures::future::join_all(lookups).await {
+            assert_eq!(lookup.unwrap().unwrap(), "127.0.0.1:25565".parse().unwrap());
+        }
+        // trust-dns may ask for AAAA records too, but it shouldn't ask more
+        // than once for each
+        assert!(queries.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_cache_expires() {
+        let (dns_address, queries) = dns_server(1).await;
+        let resolver = Resolver::new(ResolverOptions {
+            nameservers: Nameservers::Custom(vec![dns_address]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        resolver
+            .resolve_address(&address("server.test"))
+            .await
+            .unwrap();
+        let first_queries = queries.load(Ordering::SeqCst);
+        resolver
+            .resolve_address(&address("server.test"))
+            .await
+            .unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), first_queries);
+
+        // once the records expire, we look them up again
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(
+            resolver
+                .resolve_address(&address("server.test"))
+                .await
+                .unwrap(),
+            "127.0.0.1:25566".parse().unwrap()
+        );
+        assert!(queries.load(Ordering::SeqCst) > first_queries);
+    }
+
+    #[tokio::test]
+    async fn test_cache_evicts_expired() {
+        let (dns_address, _) = dns_server(1).await;
+        let resolver = Resolver::new(ResolverOptions {
+            nameservers: Nameservers::Custom(vec![dns_address]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        resolver
+            .resolve_address(&address("server.test"))
+            .await
+            .unwrap();
+        assert_eq!(resolver.inner.addresses.lock().len(), 1);
+
+        // looking up anything else removes the expired entry
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        resolver
+            .resolve_address(&address("127.0.0.1"))
+            .await
+            .unwrap();
+        let addresses = resolver.inner.addresses.lock();
+        assert_eq!(
+            addresses.keys().collect::<Vec<_>>(),
+            [&("127.0.0.1".to_string(), 25565)]
+        );
+    }
 }
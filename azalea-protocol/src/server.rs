@@ -0,0 +1,879 @@
+//! Accept connections from Minecraft clients.
+//!
+//! This handles the parts of the protocol that every server has to implement
+//! in the same way: answering status pings and logging players in (including
+//! encryption, authentication and compression). What you do with the player
+//! once they're in the game state is up to you.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use azalea_protocol::{
+//!     packets::status::clientbound_status_response_packet::{
+//!         ClientboundStatusResponsePacket, Players, Version,
+//!     },
+//!     packets::PROTOCOL_VERSION,
+//!     server::{Listener, LoginOptions},
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let listener = Listener::bind(
+//!         "127.0.0.1:25565",
+//!         |_intent: &_| ClientboundStatusResponsePacket {
+//!             description: "An Azalea server".into(),
+//!             favicon: None,
+//!             players: Players {
+//!                 max: 20,
+//!                 online: 0,
+//!                 sample: Vec::new(),
+//!             },
+//!             version: Version {
+//!                 name: "1.19.3".to_string(),
+//!                 protocol: PROTOCOL_VERSION as i32,
+//!             },
+//!             previews_chat: None,
+//!             enforces_secure_chat: None,
+//!             forge: None,
+//!         },
+//!         LoginOptions::default(),
+//!     )
+//!     .await?;
+//!
+//!     loop {
+//!         let incoming = listener.accept().await?;
+//!         tokio::spawn(async move {
+//!             if let Ok(Some(player)) = incoming.process().await {
+//!                 println!("{} joined the game", player.profile.name);
+//!                 // player.conn is now in the game state
+//!             }
+//!         });
+//!     }
+//! }
+//! ```
+
+use crate::connect::Connection;
+use crate::forwarding::{self, BungeeCordForwarding, ForwardingError, ForwardingMode};
+use crate::legacy_ping::{self, LegacyPingError, LegacyPingRequest};
+use crate::packets::game::{ClientboundGamePacket, ServerboundGamePacket};
+use crate::packets::handshake::client_intention_packet::ClientIntentionPacket;
+use crate::packets::handshake::{
+    ClientIdentifier, ClientboundHandshakePacket, ServerboundHandshakePacket,
+};
+use crate::packets::login::clientbound_custom_query_packet::ClientboundCustomQueryPacket;
+use crate::packets::login::clientbound_game_profile_packet::ClientboundGameProfilePacket;
+use crate::packets::login::clientbound_hello_packet::ClientboundHelloPacket;
+use crate::packets::login::clientbound_login_compression_packet::ClientboundLoginCompressionPacket;
+use crate::packets::login::clientbound_login_disconnect_packet::ClientboundLoginDisconnectPacket;
+use crate::packets::login::serverbound_hello_packet::ServerboundHelloPacket;
+use crate::packets::login::{
+    ClientboundLoginPacket, ServerboundLoginPacket, ServerboundLoginPacketKind,
+};
+use crate::packets::status::clientbound_pong_response_packet::ClientboundPongResponsePacket;
+use crate::packets::status::clientbound_status_response_packet::ClientboundStatusResponsePacket;
+use crate::packets::status::{ClientboundStatusPacket, ServerboundStatusPacket};
+use crate::packets::{ConnectionProtocol, ProtocolPacket, ProtocolVersion};
+use crate::read::ReadPacketError;
+use azalea_auth::game_profile::GameProfile;
+use azalea_auth::sessionserver::ServerSessionServerError;
+use azalea_buf::{McBufReadable, McBufVarReadable};
+use azalea_chat::{
+    translatable_component::{StringOrComponent, TranslatableComponent},
+    Component,
+};
+use azalea_core::ResourceLocation;
+use azalea_crypto::ServerKeyPair;
+use log::{debug, warn};
+use std::io::Cursor;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+/// Something that decides what to reply with when a client pings the server.
+///
+/// This is implemented for closures that take the client's
+/// [`ClientIntentionPacket`] and return a [`ClientboundStatusResponsePacket`].
+pub trait StatusProvider: Send + Sync + 'static {
+    fn status(&self, intent: &ClientIntentionPacket) -> ClientboundStatusResponsePacket;
+}
+
+impl<F> StatusProvider for F
+where
+    F: Fn(&ClientIntentionPacket) -> ClientboundStatusResponsePacket + Send + Sync + 'static,
+{
+    fn status(&self, intent: &ClientIntentionPacket) -> ClientboundStatusResponsePacket {
+        self(intent)
+    }
+}
+
+/// How players should be logged in.
+#[derive(Clone, Debug)]
+pub struct LoginOptions {
+    /// Whether players have to be authenticated with Mojang's servers. This
+    /// also enables encryption, since the shared secret is needed to verify
+    /// the player.
+    pub online_mode: bool,
+    /// The maximum size that a packet is allowed to be without getting
+    /// compressed. Set this to `None` to disable compression.
+    pub compression_threshold: Option<i32>,
+    /// Send the player's IP to the sessionserver so it can check that it's the
+    /// same one that authenticated. This is the same as
+    /// `prevent-proxy-connections` in server.properties.
+    pub prevent_proxy_connections: bool,
+    /// Expect players to connect through a proxy that forwards their
+    /// information. Players are trusted to be who the proxy says they are, so
+    /// `online_mode` is ignored when this is set.
+    pub forwarding: Option<ForwardingMode>,
+}
+
+impl Default for LoginOptions {
+    fn default() -> Self {
+        Self {
+            online_mode: true,
+            compression_threshold: Some(256),
+            prevent_proxy_connections: false,
+            forwarding: None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    ReadPacket(#[from] Box<ReadPacketError>),
+    #[error("Client sent an unexpected packet: {0}")]
+    UnexpectedPacket(String),
+    #[error("Client tried to switch to {0:?} in the handshake")]
+    InvalidIntention(ConnectionProtocol),
+    #[error("Client tried to log in with unsupported protocol version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Couldn't generate a key pair: {0}")]
+    KeyPair(String),
+    #[error("Couldn't decrypt the client's response: {0}")]
+    Decrypt(String),
+    #[error("The shared secret isn't 16 bytes long")]
+    InvalidSecret,
+    #[error("The client didn't send back the right nonce")]
+    InvalidNonce,
+    #[error("{0}")]
+    SessionServer(#[from] ServerSessionServerError),
+    #[error("{0}")]
+    LegacyPing(#[from] LegacyPingError),
+    #[error("{0}")]
+    Forwarding(#[from] ForwardingError),
+}
+
+/// A TCP listener that accepts Minecraft clients.
+pub struct Listener {
+    listener: TcpListener,
+    status: Arc<dyn StatusProvider>,
+    options: LoginOptions,
+    key_pair: Option<Arc<ServerKeyPair>>,
+}
+
+impl Listener {
+    /// Start listening on the given address. If `options.online_mode` is
+    /// true, this will also generate the RSA key pair used for encryption.
+    pub async fn bind(
+        address: impl ToSocketAddrs,
+        status: impl StatusProvider,
+        options: LoginOptions,
+    ) -> Result<Self, ServerError> {
+        let listener = TcpListener::bind(address).await?;
+        let key_pair = if options.online_mode {
+            Some(Arc::new(
+                ServerKeyPair::generate().map_err(ServerError::KeyPair)?,
+            ))
+        } else {
+            None
+        };
+        Ok(Self {
+            listener,
+            status: Arc::new(status),
+            options,
+            key_pair,
+        })
+    }
+
+    /// The address that we're listening on. This is useful if you bound to
+    /// port 0.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Wait for a client to connect. This doesn't read anything from the
+    /// client, so you should call [`IncomingConnection::process`] in a new
+    /// task.
+    pub async fn accept(&self) -> std::io::Result<IncomingConnection> {
+        let (stream, address) = self.listener.accept().await?;
+        stream.set_nodelay(true)?;
+        Ok(IncomingConnection {
+            conn: Connection::wrap(stream),
+            address,
+            status: self.status.clone(),
+            options: self.options.clone(),
+            key_pair: self.key_pair.clone(),
+        })
+    }
+}
+
+/// A client that connected to a [`Listener`] but hasn't done the handshake
+/// yet.
+pub struct IncomingConnection {
+    pub conn: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket>,
+    pub address: SocketAddr,
+    status: Arc<dyn StatusProvider>,
+    options: LoginOptions,
+    key_pair: Option<Arc<ServerKeyPair>>,
+}
+
+/// A player that finished logging in and is now in the game state. The
+/// connection uses the protocol version the player connected with.
+pub struct JoinedPlayer {
+    pub conn: Connection<ServerboundGamePacket, ClientboundGamePacket>,
+    pub profile: GameProfile,
+    /// The handshake the client sent, which includes the hostname they used
+    /// to connect.
+    pub intent: ClientIntentionPacket,
+    pub address: SocketAddr,
+    /// The player's real IP address, if they connected through a proxy that
+    /// forwarded it.
+    pub forwarded_address: Option<IpAddr>,
+}
+
+impl IncomingConnection {
+    /// Read the handshake and either answer the status ping or log the player
+    /// in. If the client was only pinging, this returns `None`.
+    ///
+    /// Legacy pings from clients older than 1.7 are answered too.
+    pub async fn process(mut self) -> Result<Option<JoinedPlayer>, ServerError> {
+        if self.is_legacy_ping().await? {
+            self.handle_legacy_ping().await?;
+            return Ok(None);
+        }
+
+        let ServerboundHandshakePacket::ClientIntention(intent) = self.conn.read().await?;
+        debug!(
+            "New connection from {}, version {}, {:?}",
+            self.address, intent.protocol_version, intent.intention
+        );
+
+        match intent.intention {
+            ConnectionProtocol::Status => {
+                handle_status(self.conn.status(), &intent, self.status.as_ref()).await?;
+                Ok(None)
+            }
+            ConnectionProtocol::Login => {
+                let Some(version) = ProtocolVersion::from_id(intent.protocol_version) else {
+                    let mut conn = self.conn.login();
+                    let key = if intent.protocol_version < ProtocolVersion::LATEST.id() {
+                        "multiplayer.disconnect.outdated_client"
+                    } else {
+                        "multiplayer.disconnect.outdated_server"
+                    };
+                    conn.write(
+                        ClientboundLoginDisconnectPacket {
+                            reason: Component::Translatable(TranslatableComponent::new(
+                                key.to_string(),
+                                vec![StringOrComponent::String(
+                                    ProtocolVersion::LATEST.name().to_string(),
+                                )],
+                            )),
+                        }
+                        .get(),
+                    )
+                    .await?;
+                    return Err(ServerError::UnsupportedVersion(intent.protocol_version));
+                };
+                self.conn.set_version(version);
+
+                let ip = self.address.ip().to_string();
+                let (conn, profile, forwarded_address) = login(
+                    self.conn.login(),
+                    &self.options,
+                    self.key_pair.as_deref(),
+                    Some(&ip),
+                    intent.forwarding.as_ref(),
+                )
+                .await?;
+                Ok(Some(JoinedPlayer {
+                    conn,
+                    profile,
+                    intent,
+                    address: self.address,
+                    forwarded_address,
+                }))
+            }
+            intention => Err(ServerError::InvalidIntention(intention)),
+        }
+    }
+
+    /// Check whether the client started with `0xFE` instead of a handshake.
+    /// The bytes we read are left in the connection's buffer.
+    async fn is_legacy_ping(&mut self) -> std::io::Result<bool> {
+        let reader = &mut self.conn.reader;
+        if reader.buffer.is_empty() {
+            reader.read_stream.read_buf(&mut reader.buffer).await?;
+        }
+        Ok(reader.buffer.first() == Some(&0xFE))
+    }
+
+    async fn handle_legacy_ping(&mut self) -> Result<(), ServerError> {
+        let reader = &mut self.conn.reader;
+        // the buffer has whatever the client sent in its first write
+        let request =
+            LegacyPingRequest::read_from(&mut (&reader.buffer[..]).chain(&mut reader.read_stream))
+                .await?;
+        debug!("Legacy ping from {}: {request:?}", self.address);
+        // the status provider wants a handshake, so make one up from what the
+        // client told us
+        let (hostname, port) = match &request {
+            LegacyPingRequest::V1_6 { hostname, port, .. } => (hostname.clone(), *port),
+            _ => (String::new(), 0),
+        };
+        let intent = ClientIntentionPacket {
+            protocol_version: match request {
+                LegacyPingRequest::V1_6 {
+                    protocol_version, ..
+                } => protocol_version as u32,
+                _ => 0,
+            },
+            hostname,
+            port,
+            intention: ConnectionProtocol::Status,
+            identifier: ClientIdentifier::Vanilla,
+            forwarding: None,
+        };
+        let response = legacy_ping::serialize_response(&request, &self.status.status(&intent));
+        let write_stream = &mut self.conn.writer.write_stream;
+        write_stream.write_all(&response).await?;
+        write_stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// Answer status requests and pings until the client disconnects.
+pub async fn handle_status(
+    mut conn: Connection<ServerboundStatusPacket, ClientboundStatusPacket>,
+    intent: &ClientIntentionPacket,
+    status: &dyn StatusProvider,
+) -> Result<(), ServerError> {
+    loop {
+        match conn.read().await {
+            Ok(ServerboundStatusPacket::StatusRequest(_)) => {
+                conn.write(status.status(intent).get()).await?;
+            }
+            Ok(ServerboundStatusPacket::PingRequest(p)) => {
+                conn.write(ClientboundPongResponsePacket { time: p.time }.get())
+                    .await?;
+                // the vanilla server closes the connection after the pong
+                return Ok(());
+            }
+            Err(e) => match *e {
+                ReadPacketError::ConnectionClosed => return Ok(()),
+                e => return Err(Box::new(e).into()),
+            },
+        }
+    }
+}
+
+/// Log a player in, going through encryption and authentication if
+/// `key_pair` is present and `options.online_mode` is enabled.
+///
+/// The `ip` is only sent to Mojang if `options.prevent_proxy_connections` is
+/// enabled. If `options.forwarding` is BungeeCord, `bungeecord` has to be the
+/// information from the player's handshake.
+///
+/// The last thing returned is the player's real address if it was forwarded
+/// by a proxy.
+pub async fn login(
+    mut conn: Connection<ServerboundLoginPacket, ClientboundLoginPacket>,
+    options: &LoginOptions,
+    key_pair: Option<&ServerKeyPair>,
+    ip: Option<&str>,
+    bungeecord: Option<&BungeeCordForwarding>,
+) -> Result<
+    (
+        Connection<ServerboundGamePacket, ClientboundGamePacket>,
+        GameProfile,
+        Option<IpAddr>,
+    ),
+    ServerError,
+> {
+    let hello = match conn.read().await? {
+        ServerboundLoginPacket::Hello(p) => p,
+        p => return Err(ServerError::UnexpectedPacket(format!("{p:?}"))),
+    };
+
+    let (profile, forwarded_address) = match &options.forwarding {
+        Some(ForwardingMode::BungeeCord) => {
+            let Some(forwarding) = bungeecord else {
+                disconnect(
+                    &mut conn,
+                    "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!",
+                )
+                .await?;
+                return Err(ForwardingError::NotForwarded.into());
+            };
+            let mut profile = GameProfile::new(forwarding.uuid, hello.name);
+            profile.properties = forwarding.profile_properties();
+            (profile, Some(forwarding.address))
+        }
+        Some(ForwardingMode::Velocity { secret }) => {
+            let (address, profile) = velocity_login(&mut conn, secret).await?;
+            (profile, Some(address))
+        }
+        None => (
+            authenticate(&mut conn, hello, options, key_pair, ip).await?,
+            None,
+        ),
+    };
+
+    if let Some(threshold) = options.compression_threshold {
+        conn.write(
+            ClientboundLoginCompressionPacket {
+                compression_threshold: threshold,
+            }
+            .get(),
+        )
+        .await?;
+        conn.set_compression_threshold(threshold);
+    }
+
+    conn.write(
+        ClientboundGameProfilePacket {
+            game_profile: profile.clone(),
+        }
+        .get(),
+    )
+    .await?;
+
+    Ok((conn.game(), profile, forwarded_address))
+}
+
+/// Get the profile of a player that isn't using a proxy, authenticating them
+/// if we're in online mode.
+async fn authenticate(
+    conn: &mut Connection<ServerboundLoginPacket, ClientboundLoginPacket>,
+    hello: ServerboundHelloPacket,
+    options: &LoginOptions,
+    key_pair: Option<&ServerKeyPair>,
+    ip: Option<&str>,
+) -> Result<GameProfile, ServerError> {
+    let profile = match key_pair {
+        Some(key_pair) if options.online_mode => {
+            let nonce = azalea_crypto::generate_nonce();
+            conn.write(
+                ClientboundHelloPacket {
+                    server_id: String::new(),
+                    public_key: key_pair.public_key().to_vec(),
+                    nonce: nonce.to_vec(),
+                }
+                .get(),
+            )
+            .await?;
+
+            let key = match conn.read().await? {
+                ServerboundLoginPacket::Key(p) => p,
+                p => return Err(ServerError::UnexpectedPacket(format!("{p:?}"))),
+            };
+            let decrypted_nonce = key_pair
+                .decrypt(&key.encrypted_challenge)
+                .map_err(ServerError::Decrypt)?;
+            if decrypted_nonce != nonce {
+                return Err(ServerError::InvalidNonce);
+            }
+            let secret_key: [u8; 16] = key_pair
+                .decrypt(&key.key_bytes)
+                .map_err(ServerError::Decrypt)?
+                .try_into()
+                .map_err(|_| ServerError::InvalidSecret)?;
+
+            // everything after the key packet is encrypted
+            conn.set_encryption_key(secret_key);
+
+            let ip = if options.prevent_proxy_connections {
+                ip
+            } else {
+                None
+            };
+            match conn
+                .authenticate(&hello.name, key_pair.public_key(), &secret_key, ip)
+                .await
+            {
+                Ok(profile) => profile,
+                Err(e) => {
+                    warn!("Couldn't verify {}: {e}", hello.name);
+                    conn.write(
+                        ClientboundLoginDisconnectPacket {
+                            reason: Component::Translatable(TranslatableComponent::new(
+                                "multiplayer.disconnect.unverified_username".to_string(),
+                                vec![],
+                            )),
+                        }
+                        .get(),
+                    )
+                    .await?;
+                    return Err(e.into());
+                }
+            }
+        }
+        _ => GameProfile::new(azalea_auth::offline::generate_uuid(&hello.name), hello.name),
+    };
+    Ok(profile)
+}
+
+/// Ask the proxy for the player's information with a `velocity:player_info`
+/// query and check that it was signed with our secret.
+async fn velocity_login(
+    conn: &mut Connection<ServerboundLoginPacket, ClientboundLoginPacket>,
+    secret: &[u8],
+) -> Result<(IpAddr, GameProfile), ServerError> {
+    let transaction_id = u32::from_be_bytes(azalea_crypto::generate_nonce()) >> 1;
+    conn.write(
+        ClientboundCustomQueryPacket {
+            transaction_id,
+            identifier: ResourceLocation::new(forwarding::VELOCITY_CHANNEL)
+                .expect("The Velocity channel is a valid resource location"),
+            data: forwarding::velocity_request().into(),
+        }
+        .get(),
+    )
+    .await?;
+
+    // the response is read by hand since it isn't prefixed with a channel,
+    // which ServerboundCustomQueryPacket expects
+    let data = conn.reader.read_raw().await?;
+    let mut buf = Cursor::new(&data[..]);
+    let id = u32::var_read_from(&mut buf).map_err(ForwardingError::from)?;
+    if ServerboundLoginPacket::kind_for_id(conn.version(), id)
+        != Some(ServerboundLoginPacketKind::CustomQuery)
+    {
+        return Err(ServerError::UnexpectedPacket(format!(
+            "packet {id:#x} instead of a custom query"
+        )));
+    }
+    let response_id = u32::var_read_from(&mut buf).map_err(ForwardingError::from)?;
+    if response_id != transaction_id {
+        return Err(ServerError::UnexpectedPacket(format!(
+            "custom query response for transaction {response_id}"
+        )));
+    }
+    let understood = bool::read_from(&mut buf).map_err(ForwardingError::from)?;
+
+    let result = if understood {
+        forwarding::read_velocity_response(secret, &data[buf.position() as usize..])
+    } else {
+        Err(ForwardingError::NotForwarded)
+    };
+    match result {
+        Ok(result) => Ok(result),
+        Err(e) => {
+            let reason = match e {
+                ForwardingError::NotForwarded => {
+                    "This server requires you to connect with Velocity."
+                }
+                _ => "Unable to verify player details.",
+            };
+            disconnect(conn, reason).await?;
+            Err(e.into())
+        }
+    }
+}
+
+async fn disconnect(
+    conn: &mut Connection<ServerboundLoginPacket, ClientboundLoginPacket>,
+    reason: &str,
+) -> Result<(), ServerError> {
+    conn.write(
+        ClientboundLoginDisconnectPacket {
+            reason: reason.into(),
+        }
+        .get(),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::{
+        game::{
+            clientbound_server_data_packet::ClientboundServerDataPacket,
+            clientbound_update_enabled_features_packet::ClientboundUpdateEnabledFeaturesPacket,
+        },
+        status::{
+            clientbound_status_response_packet::{Players, Version},
+            serverbound_ping_request_packet::ServerboundPingRequestPacket,
+            serverbound_status_request_packet::ServerboundStatusRequestPacket,
+        },
+        PROTOCOL_VERSION,
+    };
+
+    fn status(_intent: &ClientIntentionPacket) -> ClientboundStatusResponsePacket {
+        ClientboundStatusResponsePacket {
+            description: "test".into(),
+            favicon: None,
+            players: Players {
+                max: 20,
+                online: 1,
+                sample: Vec::new(),
+            },
+            version: Version {
+                name: "1.19.3".to_string(),
+                protocol: PROTOCOL_VERSION as i32,
+            },
+            previews_chat: None,
+            enforces_secure_chat: None,
+            forge: None,
+        }
+    }
+
+    async fn connect(
+        listener: &Listener,
+        intention: ConnectionProtocol,
+        version: ProtocolVersion,
+    ) -> Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
+        let address = listener.local_addr().unwrap();
+        let mut conn = Connection::new(&address).await.unwrap();
+        conn.set_version(version);
+        conn.write(
+            ClientIntentionPacket {
+                protocol_version: version.id(),
+                hostname: address.ip().to_string(),
+                port: address.port(),
+                intention,
+                identifier: ClientIdentifier::Vanilla,
+                forwarding: None,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let listener = Listener::bind(
+            "127.0.0.1:0",
+            status,
+            LoginOptions {
+                online_mode: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut conn = connect(
+            &listener,
+            ConnectionProtocol::Status,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .status();
+        let incoming = listener.accept().await.unwrap();
+        let server = tokio::spawn(incoming.process());
+
+        conn.write(ServerboundStatusRequestPacket {}.get())
+            .await
+            .unwrap();
+        match conn.read().await.unwrap() {
+            ClientboundStatusPacket::StatusResponse(p) => assert_eq!(p.players.online, 1),
+            p => panic!("unexpected packet {p:?}"),
+        }
+        conn.write(ServerboundPingRequestPacket { time: 123 }.get())
+            .await
+            .unwrap();
+        match conn.read().await.unwrap() {
+            ClientboundStatusPacket::PongResponse(p) => assert_eq!(p.time, 123),
+            p => panic!("unexpected packet {p:?}"),
+        }
+
+        assert!(server.await.unwrap().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_ping() {
+        let listener = Listener::bind("127.0.0.1:0", status, LoginOptions::default())
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+
+        for request in [
+            LegacyPingRequest::Beta,
+            LegacyPingRequest::V1_6 {
+                protocol_version: legacy_ping::LEGACY_PROTOCOL_VERSION,
+                hostname: address.ip().to_string(),
+                port: address.port(),
+            },
+        ] {
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            stream.write_all(&request.serialize()).await.unwrap();
+            let incoming = listener.accept().await.unwrap();
+            let server = tokio::spawn(incoming.process());
+
+            let response = legacy_ping::read_response(&mut stream).await.unwrap();
+            assert_eq!(response.description.to_string(), "test");
+            assert_eq!(response.players.online, 1);
+            assert!(server.await.unwrap().unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offline_login() {
+        let listener = Listener::bind(
+            "127.0.0.1:0",
+            status,
+            LoginOptions {
+                online_mode: false,
+                compression_threshold: Some(256),
+                prevent_proxy_connections: false,
+                forwarding: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut conn = connect(
+            &listener,
+            ConnectionProtocol::Login,
+            ProtocolVersion::LATEST,
+        )
+        .await
+        .login();
+        let incoming = listener.accept().await.unwrap();
+        let server = tokio::spawn(incoming.process());
+
+        conn.write(
+            ServerboundHelloPacket {
+                name: "Notch".to_string(),
+                profile_id: None,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+
+        match conn.read().await.unwrap() {
+            ClientboundLoginPacket::LoginCompression(p) => {
+                assert_eq!(p.compression_threshold, 256);
+                conn.set_compression_threshold(p.compression_threshold);
+            }
+            p => panic!("unexpected packet {p:?}"),
+        }
+        let profile = match conn.read().await.unwrap() {
+            ClientboundLoginPacket::GameProfile(p) => p.game_profile,
+            p => panic!("unexpected packet {p:?}"),
+        };
+
+        let player = server.await.unwrap().unwrap().unwrap();
+        assert_eq!(player.profile, profile);
+        assert_eq!(profile.uuid, azalea_auth::offline::generate_uuid("Notch"));
+    }
+
+    #[tokio::test]
+    async fn test_offline_login_old_version() {
+        let listener = Listener::bind(
+            "127.0.0.1:0",
+            status,
+            LoginOptions {
+                online_mode: false,
+                compression_threshold: None,
+                prevent_proxy_connections: false,
+                forwarding: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut conn = connect(&listener, ConnectionProtocol::Login, ProtocolVersion::V760)
+            .await
+            .login();
+        let incoming = listener.accept().await.unwrap();
+        let server = tokio::spawn(incoming.process());
+
+        conn.write(
+            ServerboundHelloPacket {
+                name: "Notch".to_string(),
+                profile_id: None,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        match conn.read().await.unwrap() {
+            ClientboundLoginPacket::GameProfile(p) => assert_eq!(p.game_profile.name, "Notch"),
+            p => panic!("unexpected packet {p:?}"),
+        };
+        let mut conn = conn.game();
+
+        let mut player = server.await.unwrap().unwrap().unwrap();
+        assert_eq!(player.conn.version(), ProtocolVersion::V760);
+
+        // this packet has a different layout in 1.19.2
+        player
+            .conn
+            .write(
+                ClientboundServerDataPacket {
+                    motd: None,
+                    icon_base64: None,
+                    enforces_secure_chat: true,
+                }
+                .get(),
+            )
+            .await
+            .unwrap();
+        match conn.read().await.unwrap() {
+            ClientboundGamePacket::ServerData(p) => assert!(p.enforces_secure_chat),
+            p => panic!("unexpected packet {p:?}"),
+        }
+
+        // and this one doesn't exist in 1.19.2
+        assert!(player
+            .conn
+            .write(ClientboundUpdateEnabledFeaturesPacket { features: vec![] }.get())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_version() {
+        let listener = Listener::bind("127.0.0.1:0", status, LoginOptions::default())
+            .await
+            .unwrap();
+
+        let address = listener.local_addr().unwrap();
+        let mut conn = Connection::new(&address).await.unwrap();
+        conn.write(
+            ClientIntentionPacket {
+                protocol_version: 47,
+                hostname: address.ip().to_string(),
+                port: address.port(),
+                intention: ConnectionProtocol::Login,
+                identifier: ClientIdentifier::Vanilla,
+                forwarding: None,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        let mut conn = conn.login();
+        let incoming = listener.accept().await.unwrap();
+
+        assert!(matches!(
+            incoming.process().await,
+            Err(ServerError::UnsupportedVersion(47))
+        ));
+        assert!(matches!(
+            conn.read().await.unwrap(),
+            ClientboundLoginPacket::LoginDisconnect(_)
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+//! Count the packets and bytes going through a connection.
+
+use parking_lot::Mutex;
+use std::{collections::HashMap, hash::Hash, sync::Arc, time::Duration};
+
+/// Traffic statistics that are shared between a connection and whoever wants
+/// to look at them. The connection keeps updating them while it's used.
+pub type SharedTrafficStats<K> = Arc<Mutex<TrafficStats<K>>>;
+
+/// Statistics about one type of packet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PacketStats {
+    /// The number of packets of this type.
+    pub count: u64,
+    /// The decompressed size of the packets, including their ids.
+    pub bytes: u64,
+    /// How long decoding these packets took in total. This is only counted
+    /// for packets that were received and decoded.
+    pub decode_time: Duration,
+}
+
+impl PacketStats {
+    fn merge(&mut self, other: &PacketStats) {
+        self.count += other.count;
+        self.bytes += other.bytes;
+        self.decode_time += other.decode_time;
+    }
+}
+
+/// Statistics about the packets going in one direction of a connection. `K`
+/// is the [`ProtocolPacket::Kind`] of the packets.
+///
+/// [`ProtocolPacket::Kind`]: crate::packets::ProtocolPacket::Kind
+#[derive(Clone, Debug)]
+pub struct TrafficStats<K> {
+    /// The number of packets.
+    pub packets: u64,
+    /// The number of bytes that were actually sent over the network, after
+    /// compression and including the length prefix.
+    pub wire_bytes: u64,
+    /// The number of bytes before compression.
+    pub bytes: u64,
+    /// How long decoding packets took in total.
+    pub decode_time: Duration,
+    /// The statistics for each type of packet.
+    pub by_kind: HashMap<K, PacketStats>,
+}
+
+impl<K> Default for TrafficStats<K> {
+    fn default() -> Self {
+        Self {
+            packets: 0,
+            wire_bytes: 0,
+            bytes: 0,
+            decode_time: Duration::ZERO,
+            by_kind: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash> TrafficStats<K> {
+    /// Count a packet. `kind` is `None` if the packet id wasn't known.
+    pub fn record(&mut self, kind: Option<K>, bytes: usize, wire_bytes: usize) {
+        self.packets += 1;
+        self.bytes += bytes as u64;
+        self.wire_bytes += wire_bytes as u64;
+        if let Some(kind) = kind {
+            let stats = self.by_kind.entry(kind).or_default();
+            stats.count += 1;
+            stats.bytes += bytes as u64;
+        }
+    }
+
+    /// Add the time it took to decode a packet.
+    pub fn record_decode(&mut self, kind: K, time: Duration) {
+        self.decode_time += time;
+        self.by_kind.entry(kind).or_default().decode_time += time;
+    }
+
+    /// The size on the wire divided by the decompressed size, so lower is
+    /// better. This is 1 if nothing was sent yet.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.bytes == 0 {
+            1.
+        } else {
+            self.wire_bytes as f64 / self.bytes as f64
+        }
+    }
+
+    /// The packet types sorted by how many times they were sent, most
+    /// frequent first.
+    pub fn most_frequent(&self) -> Vec<(K, PacketStats)> {
+        let mut kinds: Vec<(K, PacketStats)> = self
+            .by_kind
+            .iter()
+            .map(|(kind, stats)| (*kind, *stats))
+            .collect();
+        kinds.sort_by(|(_, a), (_, b)| b.count.cmp(&a.count));
+        kinds
+    }
+
+    /// Add the statistics from another connection to these ones.
+    pub fn merge(&mut self, other: &TrafficStats<K>) {
+        self.packets += other.packets;
+        self.wire_bytes += other.wire_bytes;
+        self.bytes += other.bytes;
+        self.decode_time += other.decode_time;
+        for (kind, stats) in &other.by_kind {
+            self.by_kind.entry(*kind).or_default().merge(stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_merge() {
+        let mut a = TrafficStats::default();
+        a.record(Some("chat"), 100, 50);
+        a.record(Some("move"), 10, 12);
+        a.record(Some("move"), 10, 12);
+        a.record(None, 4, 6);
+        a.record_decode("move", Duration::from_millis(2));
+
+        assert_eq!(a.packets, 4);
+        assert_eq!(a.bytes, 124);
+        assert_eq!(a.wire_bytes, 80);
+        assert_eq!(a.by_kind["move"].count, 2);
+        assert_eq!(a.by_kind["move"].decode_time, Duration::from_millis(2));
+        assert_eq!(a.most_frequent()[0].0, "move");
+
+        let mut b = TrafficStats::default();
+        b.record(Some("chat"), 20, 20);
+        b.merge(&a);
+        assert_eq!(b.packets, 5);
+        assert_eq!(b.by_kind["chat"].count, 2);
+        assert_eq!(b.by_kind["chat"].bytes, 120);
+        assert!((b.compression_ratio() - 100. / 144.).abs() < f64::EPSILON);
+    }
+}
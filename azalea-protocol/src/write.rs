@@ -1,11 +1,18 @@
 //! Write packets to a stream.
 
-use crate::{packets::ProtocolPacket, read::MAXIMUM_UNCOMPRESSED_LENGTH};
+use crate::{
+    packets::{ProtocolPacket, ProtocolVersion},
+    read::MAXIMUM_UNCOMPRESSED_LENGTH,
+    remap::remapper_for_version,
+};
 use async_compression::tokio::bufread::ZlibEncoder;
-use azalea_buf::McBufVarWritable;
+use azalea_buf::{
+    remap::{with_remapper, IdRemapper},
+    McBufVarWritable,
+};
 use azalea_crypto::Aes128CfbEnc;
 use log::trace;
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 use thiserror::Error;
 use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
@@ -27,14 +34,26 @@ pub enum PacketEncodeError {
         maximum: usize,
         packet_string: String,
     },
+    #[error("Packet can't be sent in protocol version {version}: {packet_string}")]
+    UnsupportedPacket {
+        version: ProtocolVersion,
+        packet_string: String,
+    },
 }
 
 fn packet_encoder<P: ProtocolPacket + std::fmt::Debug>(
     packet: &P,
+    version: ProtocolVersion,
 ) -> Result<Vec<u8>, PacketEncodeError> {
     let mut buf = Vec::new();
-    packet.id().var_write_into(&mut buf)?;
-    packet.write(&mut buf)?;
+    let Some(id) = packet.id_for(version) else {
+        return Err(PacketEncodeError::UnsupportedPacket {
+            version,
+            packet_string: format!("{packet:?}"),
+        });
+    };
+    id.var_write_into(&mut buf)?;
+    packet.write_for(version, &mut buf)?;
     if buf.len() > MAXIMUM_UNCOMPRESSED_LENGTH as usize {
         return Err(PacketEncodeError::TooBig {
             actual: buf.len(),
@@ -77,13 +96,58 @@ pub async fn write_packet<P, W>(
     stream: &mut W,
     compression_threshold: Option<u32>,
     cipher: &mut Option<Aes128CfbEnc>,
+    version: ProtocolVersion,
 ) -> std::io::Result<()>
 where
     P: ProtocolPacket + Debug,
     W: AsyncWrite + Unpin + Send,
+{
+    let (buf, _) = encode_packet(packet, compression_threshold, cipher, version, None).await?;
+    stream.write_all(&buf).await
+}
+
+/// Get the bytes that would be written to the stream for a packet, and the
+/// size of the packet before it was compressed. This updates the cipher, so
+/// the bytes have to actually be sent afterwards.
+///
+/// Registry ids in the packet are written with the ids that `version` uses,
+/// and with the `remapper` if there is one.
+pub async fn encode_packet<P>(
+    packet: &P,
+    compression_threshold: Option<u32>,
+    cipher: &mut Option<Aes128CfbEnc>,
+    version: ProtocolVersion,
+    remapper: Option<Arc<dyn IdRemapper>>,
+) -> std::io::Result<(Vec<u8>, usize)>
+where
+    P: ProtocolPacket + Debug,
 {
     trace!("Sending packet: {:?}", packet);
-    let mut buf = packet_encoder(packet).unwrap();
+    let remapper = remapper_for_version(version, remapper);
+    let buf = match with_remapper(remapper, || packet_encoder(packet, version)) {
+        Ok(buf) => buf,
+        Err(e @ PacketEncodeError::UnsupportedPacket { .. }) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+        }
+        Err(e) => panic!("{e}"),
+    };
+    let size = buf.len();
+    Ok((
+        encode_raw_packet(buf, compression_threshold, cipher).await,
+        size,
+    ))
+}
+
+/// Compress, frame, and encrypt the bytes of a packet that was already
+/// serialized, like one from [`ReadConnection::read_raw`]. Like
+/// [`encode_packet`], this updates the cipher.
+///
+/// [`ReadConnection::read_raw`]: crate::connect::ReadConnection::read_raw
+pub async fn encode_raw_packet(
+    mut buf: Vec<u8>,
+    compression_threshold: Option<u32>,
+    cipher: &mut Option<Aes128CfbEnc>,
+) -> Vec<u8> {
     if let Some(threshold) = compression_threshold {
         buf = compression_encoder(&buf, threshold).await.unwrap();
     }
@@ -92,5 +156,5 @@ where
     if let Some(cipher) = cipher {
         azalea_crypto::encrypt_packet(cipher, &mut buf);
     }
-    stream.write_all(&buf).await
+    buf
 }
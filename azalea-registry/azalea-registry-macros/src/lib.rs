@@ -5,11 +5,13 @@ use syn::{
     parse::{Parse, ParseStream, Result},
     parse_macro_input,
     punctuated::Punctuated,
-    Ident, LitStr, Token,
+    Ident, LitInt, LitStr, Token,
 };
 
 struct RegistryItem {
     name: Ident,
+    /// The protocol id, if it isn't one more than the previous item's.
+    protocol_id: Option<u32>,
     id: String,
 }
 
@@ -20,11 +22,22 @@ struct Registry {
 
 impl Parse for RegistryItem {
     // Air => "minecraft:air"
+    // Speed = 1 => "minecraft:speed"
     fn parse(input: ParseStream) -> Result<Self> {
         let name = input.parse()?;
+        let protocol_id = if !input.peek(Token![=>]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse::<LitInt>()?.base10_parse()?)
+        } else {
+            None
+        };
         input.parse::<Token![=>]>()?;
         let id = input.parse::<LitStr>()?.value();
-        Ok(RegistryItem { name, id })
+        Ok(RegistryItem {
+            name,
+            protocol_id,
+            id,
+        })
     }
 }
 
@@ -58,26 +71,49 @@ pub fn registry(input: TokenStream) -> TokenStream {
     //     Air = 0,
     //     Stone,
     // }
+    // the ids have to be contiguous, since we check them with a range
+    let min_id = input
+        .items
+        .first()
+        .and_then(|item| item.protocol_id)
+        .unwrap_or(0);
     let mut enum_items = quote! {};
     for (i, item) in input.items.iter().enumerate() {
         let name = &item.name;
-        let protocol_id = i as u32;
+        let protocol_id = min_id + i as u32;
+        if let Some(explicit_id) = item.protocol_id {
+            if explicit_id != protocol_id {
+                return syn::Error::new(
+                    name.span(),
+                    "Registry ids must be contiguous, only the first item can have an explicit id",
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
         enum_items.extend(quote! {
             #name = #protocol_id,
         });
     }
     generated.extend(quote! {
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, azalea_buf::McBuf)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[repr(u32)]
         pub enum #name {
             #enum_items
         }
     });
 
-    let max_id = input.items.len() as u32;
+    // exclusive
+    let max_id = min_id + input.items.len() as u32;
 
     let doc_0 = format!("Transmutes a u32 to a {name}.");
-    let doc_1 = format!("The `id` should be at most {max_id}.");
+    let doc_1 = format!("The `id` should be at least {min_id} and less than {max_id}.");
+    let is_valid_id = if min_id == 0 {
+        quote! { id < #max_id }
+    } else {
+        quote! { (#min_id..#max_id).contains(&id) }
+    };
 
     generated.extend(quote! {
         impl #name {
@@ -92,7 +128,7 @@ pub fn registry(input: TokenStream) -> TokenStream {
 
             #[inline]
             pub fn is_valid_id(id: u32) -> bool {
-                id <= #max_id
+                #is_valid_id
             }
         }
         impl Registry for #name {
@@ -145,5 +181,69 @@ pub fn registry(input: TokenStream) -> TokenStream {
         }
     });
 
+    // FromStr that uses registry ids
+    let mut from_str_items = quote! {};
+    for item in input.items.iter() {
+        let name = &item.name;
+        let id = &item.id;
+        from_str_items.extend(quote! {
+            #id => Ok(Self::#name),
+        });
+    }
+    generated.extend(quote! {
+        impl std::str::FromStr for #name {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    #from_str_items
+                    _ => Err(()),
+                }
+            }
+        }
+    });
+
+    // the registry's own id, like minecraft:entity_type for EntityType
+    let mut registry_id = "minecraft:".to_string();
+    for (i, c) in name.to_string().chars().enumerate() {
+        if c.is_uppercase() && i != 0 {
+            registry_id.push('_');
+        }
+        registry_id.push(c.to_ascii_lowercase());
+    }
+    generated.extend(quote! {
+        impl azalea_buf::remap::Remappable for #name {
+            const REGISTRY: &'static str = #registry_id;
+
+            fn from_vanilla_id(id: u32) -> Option<Self> {
+                <Self as Registry>::from_u32(id)
+            }
+            fn vanilla_id(&self) -> u32 {
+                *self as u32
+            }
+        }
+    });
+
+    // read and write through the remapper, since modded servers can have
+    // different ids
+    generated.extend(quote! {
+        impl azalea_buf::McBufReadable for #name {
+            fn read_from(buf: &mut std::io::Cursor<&[u8]>) -> Result<Self, azalea_buf::BufReadError> {
+                let id = <u32 as azalea_buf::McBufVarReadable>::var_read_from(buf)?;
+                match azalea_buf::remap::remap(<Self as azalea_buf::remap::Remappable>::REGISTRY, id) {
+                    azalea_buf::remap::RemappedId::Vanilla(id) => <Self as Registry>::from_u32(id),
+                    azalea_buf::remap::RemappedId::Modded(_) => None,
+                }
+                .ok_or(azalea_buf::BufReadError::UnexpectedEnumVariant { id: id as i32 })
+            }
+        }
+        impl azalea_buf::McBufWritable for #name {
+            fn write_into(&self, buf: &mut impl std::io::Write) -> Result<(), std::io::Error> {
+                let id = azalea_buf::remap::server_id(<Self as azalea_buf::remap::Remappable>::REGISTRY, *self as u32);
+                azalea_buf::McBufVarWritable::var_write_into(&id, buf)
+            }
+        }
+    });
+
     generated.into()
 }
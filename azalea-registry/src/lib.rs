@@ -15,6 +15,7 @@ where
 /// A registry that might not be present. This is transmitted as a single
 /// varint in the protocol.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OptionalRegistry<T: Registry>(Option<T>);
 
 impl<T: Registry> McBufReadable for OptionalRegistry<T> {
@@ -2968,7 +2969,7 @@ registry!(Menu, {
 });
 
 registry!(MobEffect, {
-    Speed => "minecraft:speed",
+    Speed = 1 => "minecraft:speed",
     Slowness => "minecraft:slowness",
     Haste => "minecraft:haste",
     MiningFatigue => "minecraft:mining_fatigue",
@@ -1,7 +1,10 @@
 use crate::palette::PalettedContainer;
 use crate::palette::PalettedContainerType;
 use azalea_block::BlockState;
-use azalea_buf::{BufReadError, McBufReadable, McBufWritable};
+use azalea_buf::{
+    remap::{remap, Remappable, RemappedId},
+    BufReadError, McBufReadable, McBufWritable,
+};
 use azalea_core::{BlockPos, ChunkBlockPos, ChunkPos, ChunkSectionBlockPos};
 use log::{debug, trace, warn};
 use parking_lot::RwLock;
@@ -317,7 +320,13 @@ impl McBufReadable for Section {
         //     "A section has more blocks than what should be possible. This is a bug!"
         // );
 
-        let states = PalettedContainer::read_with_type(buf, &PalettedContainerType::BlockStates)?;
+        let mut states =
+            PalettedContainer::read_with_type(buf, &PalettedContainerType::BlockStates)?;
+        // modded servers and older versions can have different block state ids
+        states.map_ids(|id| match remap(BlockState::REGISTRY, id) {
+            RemappedId::Vanilla(id) => id,
+            RemappedId::Modded(entry) => entry.id,
+        });
 
         for i in 0..states.storage.size() {
             if !BlockState::is_valid_state(states.storage.get(i) as u32) {
@@ -68,6 +68,7 @@ impl AttributeInstance {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttributeModifier {
     pub uuid: Uuid,
     pub name: String,
@@ -76,6 +77,7 @@ pub struct AttributeModifier {
 }
 
 #[derive(Clone, Debug, Copy, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttributeModifierOperation {
     Addition,
     MultiplyBase,
@@ -9,9 +9,11 @@ use std::io::{Cursor, Write};
 use uuid::Uuid;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityMetadataItems(pub Vec<EntityDataItem>);
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityDataItem {
     // we can't identify what the index is for here because we don't know the
     // entity type
@@ -46,6 +48,7 @@ impl McBufWritable for EntityMetadataItems {
 }
 
 #[derive(Clone, Debug, EnumAsInner, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EntityDataValue {
     Byte(u8),
     Int(#[var] i32),
@@ -77,6 +80,7 @@ pub enum EntityDataValue {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OptionalUnsignedInt(pub Option<u32>);
 
 impl McBufReadable for OptionalUnsignedInt {
@@ -99,6 +103,7 @@ impl McBufWritable for OptionalUnsignedInt {
 }
 
 #[derive(Clone, Debug, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rotations {
     pub x: f32,
     pub y: f32,
@@ -106,6 +111,7 @@ pub struct Rotations {
 }
 
 #[derive(Clone, Debug, Copy, McBuf, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pose {
     #[default]
     Standing = 0,
@@ -119,6 +125,7 @@ pub enum Pose {
 }
 
 #[derive(Debug, Clone, McBuf)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VillagerData {
     pub kind: azalea_registry::VillagerType,
     pub profession: azalea_registry::VillagerProfession,
@@ -56,6 +56,25 @@ impl PalettedContainer {
         })
     }
 
+    /// Replace every id in the container with what `f` returns for it, like
+    /// when translating the ids a server sent into vanilla ones.
+    pub fn map_ids(&mut self, f: impl Fn(u32) -> u32) {
+        match &mut self.palette {
+            Palette::SingleValue(id) => *id = f(*id),
+            Palette::Linear(ids) | Palette::Hashmap(ids) => {
+                for id in ids {
+                    *id = f(*id);
+                }
+            }
+            Palette::Global => {
+                for i in 0..self.storage.size() {
+                    let id = f(self.storage.get(i) as u32);
+                    self.storage.set(i, id as u64);
+                }
+            }
+        }
+    }
+
     /// Calculates the index of the given coordinates.
     pub fn get_index(&self, x: usize, y: usize, z: usize) -> usize {
         let size_bits = self.container_type.size_bits();
@@ -1,7 +1,9 @@
 use crate::{bot, pathfinder, HandleFn};
-use azalea_client::{Account, Client, Plugins};
+use azalea_client::{filter::PacketFilter, Account, Client, JoinOptions, Plugins};
 use azalea_protocol::{
-    packets::handshake::client_intention_packet::ClientIdentifier, ServerAddress,
+    packets::{handshake::client_intention_packet::ClientIdentifier, ProtocolVersion},
+    resolver::Resolver,
+    ServerAddress,
 };
 use std::{future::Future, sync::Arc};
 use thiserror::Error;
@@ -43,8 +45,18 @@ where
     pub address: A,
     /// Marks the client as either vanilla, forge, or fabric
     pub identifier: ClientIdentifier,
+    /// The protocol version to connect with. This should usually be
+    /// [`ProtocolVersion::LATEST`], since bots on older versions can't see the
+    /// world.
+    pub version: ProtocolVersion,
     /// The account that's going to join the server.
     pub account: Account,
+    /// How the server's address is resolved. You can usually leave this as
+    /// `Resolver::default()`.
+    pub resolver: Resolver,
+    /// Which packets the bot decodes. You can usually leave this as
+    /// `PacketFilter::default()`, which decodes everything.
+    pub packet_filter: PacketFilter,
     /// The plugins that are going to be used. Plugins are external crates that
     /// add extra functionality to Azalea. You should use the [`plugins`] macro
     /// for this field.
@@ -100,6 +112,9 @@ pub enum StartError {
 ///     account,
 ///     address: "localhost",
 ///     identifier: ClientIdentifier::Vanilla,
+///     version: ProtocolVersion::LATEST,
+///     resolver: Resolver::default(),
+///     packet_filter: PacketFilter::default(),
 ///     state: State::default(),
 ///     plugins: plugins![azalea_pathfinder::Plugin],
 ///     handle,
@@ -117,7 +132,18 @@ pub async fn start<
         Err(_) => return Err(StartError::InvalidAddress),
     };
 
-    let (mut bot, mut rx) = Client::join(&options.account, address, options.identifier).await?;
+    let (mut bot, mut rx) = Client::join(
+        &options.account,
+        address,
+        JoinOptions {
+            identifier: options.identifier,
+            version: options.version,
+            resolver: Some(options.resolver),
+            packet_filter: options.packet_filter,
+            ..Default::default()
+        },
+    )
+    .await?;
 
     let mut plugins = options.plugins;
     // DEFAULT PLUGINS
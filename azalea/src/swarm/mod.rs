@@ -4,11 +4,14 @@ mod plugins;
 
 pub use self::plugins::*;
 use crate::{bot, HandleFn};
-use azalea_client::{Account, ChatPacket, Client, Event, JoinError, Plugins};
+use azalea_client::{
+    filter::PacketFilter, Account, ChatPacket, Client, ClientStats, Event, JoinError, Plugins,
+};
 use azalea_protocol::{
     connect::{Connection, ConnectionError},
-    packets::handshake::client_intention_packet::ClientIdentifier,
-    resolver::{self, ResolverError},
+    packets::{handshake::client_intention_packet::ClientIdentifier, ProtocolVersion},
+    proxy::Proxy,
+    resolver::{Resolver, ResolverError},
     ServerAddress,
 };
 use azalea_world::WeakWorldContainer;
@@ -53,6 +56,9 @@ pub struct Swarm<S> {
 
     resolved_address: SocketAddr,
     address: ServerAddress,
+    version: ProtocolVersion,
+    resolver: Resolver,
+    packet_filter: PacketFilter,
     pub worlds: Arc<RwLock<WeakWorldContainer>>,
     /// Plugins that are set for new bots
     plugins: Plugins,
@@ -63,6 +69,7 @@ pub struct Swarm<S> {
 
 /// An event about something that doesn't have to do with a single bot.
 #[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum SwarmEvent {
     /// All the bots in the swarm have successfully joined the server.
     Login,
@@ -98,8 +105,25 @@ where
     pub address: A,
     /// The accounts that are going to join the server.
     pub accounts: Vec<Account>,
+    /// Proxies that the bots connect through. Accounts that don't have a
+    /// [`proxy`](Account::proxy) set are spread across these evenly. Leave
+    /// this empty to connect directly.
+    pub proxies: Vec<Proxy>,
+    /// How the server's address is resolved. The resolver is shared by all
+    /// the bots, so lookups for proxies that don't resolve hostnames
+    /// themselves are cached. You can usually leave this as
+    /// `Resolver::default()`.
+    pub resolver: Resolver,
+    /// Which packets the bots decode. Since the bots share a world, you
+    /// probably want [`PacketFilter::skip_known_chunks`] so each chunk is
+    /// only decoded by the first bot that receives it.
+    pub packet_filter: PacketFilter,
     /// Marks the client as either vanilla, forge, or fabric
     pub identifier: ClientIdentifier,
+    /// The protocol version that all the bots connect with. This should
+    /// usually be [`ProtocolVersion::LATEST`], since bots on older versions
+    /// can't see the world.
+    pub version: ProtocolVersion,
     /// The plugins that are going to be used for all the bots.
     ///
     /// You can usually leave this as `plugins![]`.
@@ -165,8 +189,12 @@ pub enum SwarmStartError {
 ///     loop {
 ///         let e = azalea::start_swarm(azalea::SwarmOptions {
 ///             accounts: accounts.clone(),
+///             proxies: Vec::new(),
+///             resolver: Resolver::default(),
+///             packet_filter: PacketFilter::default().skip_known_chunks(true),
 ///             address: "localhost",
 ///             identifier: ClientIdentifier::Vanilla
+///             version: ProtocolVersion::LATEST,
 ///
 ///             states: states.clone(),
 ///             swarm_state: SwarmState::default(),
@@ -232,7 +260,7 @@ pub async fn start_swarm<
     };
 
     // resolve the address
-    let resolved_address = resolver::resolve_address(&address).await?;
+    let resolved_address = options.resolver.resolve_address(&address).await?;
 
     let world_container = Arc::new(RwLock::new(WeakWorldContainer::default()));
 
@@ -253,6 +281,9 @@ pub async fn start_swarm<
 
         resolved_address,
         address,
+        version: options.version,
+        resolver: options.resolver,
+        packet_filter: options.packet_filter,
         worlds: world_container,
         plugins,
 
@@ -273,10 +304,11 @@ pub async fn start_swarm<
     let swarm_plugins = swarm_plugins.build();
 
     let mut swarm_clone = swarm.clone();
+    let accounts = assign_proxies(options.accounts, &options.proxies);
     let join_task = tokio::spawn(async move {
         if let Some(join_delay) = options.join_delay {
             // if there's a join delay, then join one by one
-            for (account, state) in options.accounts.iter().zip(options.states) {
+            for (account, state) in accounts.iter().zip(options.states) {
                 swarm_clone
                     .add_with_exponential_backoff(account, options.identifier, state.clone())
                     .await;
@@ -284,7 +316,7 @@ pub async fn start_swarm<
             }
         } else {
             let swarm_borrow = &swarm_clone;
-            join_all(options.accounts.iter().zip(options.states).map(
+            join_all(accounts.iter().zip(options.states).map(
                 async move |(account, state)| -> Result<(), JoinError> {
                     swarm_borrow
                         .clone()
@@ -346,6 +378,22 @@ pub async fn start_swarm<
     Ok(())
 }
 
+/// Give every account that doesn't already have a proxy one from the list,
+/// going through the list in order.
+fn assign_proxies(mut accounts: Vec<Account>, proxies: &[Proxy]) -> Vec<Account> {
+    if proxies.is_empty() {
+        return accounts;
+    }
+    for (account, proxy) in accounts
+        .iter_mut()
+        .filter(|account| account.proxy.is_none())
+        .zip(proxies.iter().cycle())
+    {
+        account.proxy = Some(proxy.clone());
+    }
+    accounts
+}
+
 impl<S> Swarm<S>
 where
     S: Send + Sync + Clone + 'static,
@@ -358,14 +406,30 @@ where
         identifier: ClientIdentifier,
         state: S,
     ) -> Result<Client, JoinError> {
-        let conn = Connection::new(&self.resolved_address).await?;
-        let (conn, game_profile) =
-            Client::handshake(conn, identifier, account, &self.address.clone()).await?;
+        let conn = match &account.proxy {
+            Some(proxy) => {
+                let target = proxy.resolve(&self.resolver, &self.address).await?;
+                Connection::new_with_proxy(&target, proxy).await?
+            }
+            None => Connection::new(&self.resolved_address).await?,
+        };
+        let (conn, game_profile) = Client::handshake(
+            conn,
+            identifier,
+            self.version,
+            account,
+            &self.address.clone(),
+        )
+        .await?;
 
         // tx is moved to the bot so it can send us events
         // rx is used to receive events from the bot
         let (tx, mut rx) = mpsc::channel(1);
         let mut bot = Client::new(game_profile, conn, Some(self.worlds.clone()));
+        bot.set_packet_filter(self.packet_filter.clone());
+        if let Some(brand) = &account.brand {
+            bot.set_brand(brand);
+        }
         tx.send(Event::Init).await.expect("Failed to send event");
         bot.start_tasks(tx);
 
@@ -433,6 +497,16 @@ where
             }
         }
     }
+
+    /// Get the traffic statistics of every bot in the swarm added together.
+    /// Bots that disconnected aren't counted.
+    pub fn stats(&self) -> ClientStats {
+        let mut stats = ClientStats::default();
+        for (bot, _) in self.bot_datas.lock().iter() {
+            stats.merge(&bot.stats());
+        }
+        stats
+    }
 }
 
 impl<S> IntoIterator for Swarm<S>
@@ -465,3 +539,29 @@ impl From<ConnectionError> for SwarmStartError {
         SwarmStartError::from(JoinError::from(e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_proxies() {
+        let proxies: Vec<Proxy> = ["socks5://127.0.0.1:1080", "socks5://127.0.0.1:1081"]
+            .iter()
+            .map(|p| p.parse().unwrap())
+            .collect();
+        let own_proxy: Proxy = "http://127.0.0.1:8080".parse().unwrap();
+        let accounts = vec![
+            Account::offline("bot0"),
+            Account::offline("bot1").with_proxy(own_proxy),
+            Account::offline("bot2"),
+            Account::offline("bot3"),
+        ];
+
+        let ports: Vec<u16> = assign_proxies(accounts, &proxies)
+            .iter()
+            .map(|a| a.proxy.as_ref().unwrap().address.port)
+            .collect();
+        assert_eq!(ports, [1080, 8080, 1081, 1080]);
+    }
+}
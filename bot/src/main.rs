@@ -1,9 +1,10 @@
 use azalea::pathfinder::BlockPosGoal;
 // use azalea::ClientInformation;
+use azalea::{filter::PacketFilter, Account, Client, Event, Resolver};
 use azalea::{prelude::*, BlockPos, Swarm, SwarmEvent, WalkDirection};
-use azalea::{Account, Client, Event};
 use azalea_protocol::packets::game::serverbound_client_command_packet::ServerboundClientCommandPacket;
 use azalea_protocol::packets::handshake::client_intention_packet::ClientIdentifier;
+use azalea_protocol::packets::ProtocolVersion;
 use std::time::Duration;
 
 #[derive(Default, Clone)]
@@ -51,8 +52,12 @@ async fn main() -> anyhow::Result<()> {
     loop {
         let e = azalea::start_swarm(azalea::SwarmOptions {
             accounts: accounts.clone(),
+            proxies: Vec::new(),
+            resolver: Resolver::default(),
+            packet_filter: PacketFilter::default().skip_known_chunks(true),
             address: "localhost",
             identifier: ClientIdentifier::Vanilla,
+            version: ProtocolVersion::LATEST,
 
             states: states.clone(),
             swarm_state: SwarmState::default(),
@@ -13,7 +13,7 @@ BLOCKS_RS_DIR = get_dir_location('../azalea-block/src/blocks.rs')
 # - Block: Has properties and states.
 
 
-def generate_blocks(blocks_burger: dict, blocks_report: dict, ordered_blocks: list[str], mappings: Mappings):
+def generate_blocks(blocks_burger: dict, blocks_report: dict, pixlyzer_block_datas: dict, ordered_blocks: list[str], mappings: Mappings):
     with open(BLOCKS_RS_DIR, 'r') as f:
         existing_code = f.read().splitlines()
 
@@ -134,9 +134,11 @@ def generate_blocks(blocks_burger: dict, blocks_report: dict, ordered_blocks: li
         else:
             properties_code += '\n        }'
 
-        # TODO: use burger to generate the blockbehavior
+        behavior_code = generate_block_behavior(
+            pixlyzer_block_datas['minecraft:' + block_id], block_data_report)
+
         new_make_block_states_macro_code.append(
-            f'        {block_id} => BlockBehavior::default(), {properties_code},')
+            f'        {block_id} => {behavior_code}, {properties_code},')
 
     new_make_block_states_macro_code.append('    }')
     new_make_block_states_macro_code.append('}')
@@ -161,6 +163,22 @@ def generate_blocks(blocks_burger: dict, blocks_report: dict, ordered_blocks: li
         f.write('\n'.join(new_code))
 
 
+def generate_block_behavior(block_data_pixlyzer: dict, block_data_report: dict) -> str:
+    # the hardness and whether it needs a tool are the same for every state
+    # of a block, so we just use the default one
+    default_state_id = next(
+        state['id'] for state in block_data_report['states'] if state.get('default'))
+    state_data_pixlyzer = block_data_pixlyzer['states'][str(default_state_id)]
+
+    behavior_code = 'BlockBehavior::default()'
+    destroy_time = float(state_data_pixlyzer.get('hardness', 0))
+    if destroy_time != 0:
+        behavior_code += f'.destroy_time({destroy_time})'
+    if state_data_pixlyzer.get('requires_tool'):
+        behavior_code += '.requires_correct_tool_for_drops()'
+    return behavior_code
+
+
 def get_property_struct_name(property: Optional[dict], block_data_burger: dict, property_variants: list[str], mappings: Mappings) -> str:
     # these are hardcoded because otherwise they cause conflicts
     # some names inspired by https://github.com/feather-rs/feather/blob/main/feather/blocks/src/generated/table.rs
@@ -33,6 +33,8 @@ def generate_packet(burger_packets, mappings: Mappings, target_packet_id, target
 
         generated_packet_code.append(
             f'#[derive(Clone, Debug, McBuf, {packet_derive_name})]')
+        generated_packet_code.append(
+            '#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]')
         uses.add(f'azalea_protocol_macros::{packet_derive_name}')
         uses.add(f'azalea_buf::McBuf')
 
@@ -26,6 +26,7 @@ where
 /// A registry that might not be present. This is transmitted as a single
 /// varint in the protocol.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OptionalRegistry<T: Registry>(Option<T>);
 
 impl<T: Registry> McBufReadable for OptionalRegistry<T> {
@@ -58,9 +59,12 @@ impl<T: Registry> McBufWritable for OptionalRegistry<T> {
         code.append(f'registry!({registry_struct_name}, {{')
         registry_entries = sorted(
             registry['entries'].items(), key=lambda x: x[1]['protocol_id'])
-        for variant_name, _variant in registry_entries:
+        for i, (variant_name, variant) in enumerate(registry_entries):
             variant_struct_name = to_camel_case(
                 variant_name.split(':')[1])
+            # some registries (like mob_effect) don't start at 0
+            if i == 0 and variant['protocol_id'] != 0:
+                variant_struct_name += f' = {variant["protocol_id"]}'
             code.append(f'\t{variant_struct_name} => "{variant_name}",')
         code.append('});')
         code.append('')
@@ -122,7 +122,7 @@ if old_ordered_blocks != new_ordered_blocks:
         new_version_id, 'blocks')
 
     lib.code.blocks.generate_blocks(
-        block_states_burger, block_states_report, new_ordered_blocks, new_mappings)
+        block_states_burger, block_states_report, pixlyzer_block_datas, new_ordered_blocks, new_mappings)
     lib.code.shapes.generate_block_shapes(
         pixlyzer_block_datas, shape_datas['shapes'], shape_datas['aabbs'], block_states_report, block_states_burger, new_mappings)
 